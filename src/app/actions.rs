@@ -1,13 +1,25 @@
 use crate::app::state::{
-    CacheTTLSetting, CategoryItem, CleanupItemData, DevSweep, QuarantineItemData,
-    QuarantineRecordData, SuperCategoryItem, SuperCategoryType,
+    CacheTTLSetting, CategoryItem, CheckerProgress, CheckerStatus, CleanupItemData, DevSweep,
+    QuarantineItemData, QuarantineRecordData, SuperCategoryItem, SuperCategoryType,
 };
+use crate::backend::CategoryData;
+use crate::cancel::CancellationToken;
+use crate::checkers::registry;
 use crate::custom_paths::CustomPathsConfig;
+use crate::overlap;
+use crate::scan_mode::{ScanMode, ScanSettings};
+use crate::scan_progress::ScanEvent;
+use crate::types;
 use crate::ui::sidebar::Tab;
 use crate::update_checker;
 use crate::utils;
 use gpui::*;
 use std::path::PathBuf;
+use std::sync::mpsc::{self, TryRecvError};
+use std::time::Duration;
+
+/// How often the UI picks up scan events while a scan is running
+const SCAN_EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 impl DevSweep {
     pub fn refresh_quarantine(&mut self) {
@@ -49,7 +61,11 @@ impl DevSweep {
             .iter()
             .flat_map(|record| {
                 record.items.iter().enumerate().map(|(idx, item)| {
-                    let error_msg = item.error_message.clone().unwrap_or_default();
+                    // Records from older releases only have the message
+                    let error_msg = match &item.error {
+                        Some(error) => error.explain(),
+                        None => item.error_message.clone().unwrap_or_default(),
+                    };
                     QuarantineItemData {
                         item_type: item.item_type.clone().into(),
                         original_path: item.original_path.display().to_string().into(),
@@ -81,7 +97,6 @@ impl DevSweep {
                 ttl_minutes: (*ttl_sec / 60) as i32,
             })
             .collect();
-        self.cache_ttls.sort_by(|a, b| a.category.cmp(&b.category));
     }
 
     /// Increase TTL for a category
@@ -171,74 +186,89 @@ impl DevSweep {
         self.selected_items.clear();
         self.selected_items_count = 0;
         self.selected_items_size = "0 B".into();
+        self.scan_progress.clear();
+        self.set_category_data(Vec::new());
 
         // Notify immediately to update UI and show disabled state
         cx.notify();
 
         let backend = self.backend.clone();
+        let (sender, events) = mpsc::channel();
+        let cancel = CancellationToken::new();
+        self.cancel_token = Some(cancel.clone());
+
+        // Run the scan on its own thread; results stream back as events
+        let scan = std::thread::spawn(move || {
+            let mut backend = backend.lock().unwrap();
+            backend.scan_with_events(use_cache, sender, &cancel)
+        });
 
         cx.spawn(|this, mut cx| async move {
-            // Run scan in background
-            let result = {
-                let mut backend = backend.lock().unwrap();
-                let cats = backend.scan_with_cache(use_cache);
-                let total = backend.get_total_reclaimable();
-                (cats, total)
-            };
+            loop {
+                cx.background_executor()
+                    .timer(SCAN_EVENT_POLL_INTERVAL)
+                    .await;
+
+                let mut batch = Vec::new();
+                let mut done = false;
+                loop {
+                    match events.try_recv() {
+                        Ok(event) => batch.push(event),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => {
+                            done = true;
+                            break;
+                        }
+                    }
+                }
+
+                if !batch.is_empty() {
+                    let _ = cx.update(|cx| {
+                        let _ = this.update(cx, |this, cx| {
+                            for event in batch {
+                                this.apply_scan_event(event);
+                            }
+                            cx.notify();
+                        });
+                    });
+                }
+
+                if done {
+                    break;
+                }
+            }
 
-            let categories = result.0;
-            let total = result.1;
+            // The sender is dropped when the scan returns, so this doesn't block
+            let scanned = scan.join();
 
             // Update UI on main thread
             let _ = cx.update(|cx| {
                 let _ = this.update(cx, |this, cx| {
-                    this.category_data = categories.clone();
-
-                    // Convert to UI models with super category assignment
-                    this.categories = categories
-                        .iter()
-                        .map(|c| CategoryItem {
-                            name: c.name.clone().into(),
-                            size: c.size.clone().into(),
-                            total_size: c.total_size,
-                            item_count: c.item_count,
-                            checked: false,
-                            expanded: false,
-                            super_category: SuperCategoryType::from_category_name(&c.name),
-                        })
-                        .collect();
-
-                    this.all_items.clear();
-                    for (cat_idx, cat) in categories.iter().enumerate() {
-                        for item in &cat.items {
-                            let path_str = item
-                                .path
-                                .as_ref()
-                                .map(|p| p.display().to_string())
-                                .unwrap_or_default();
-                            let warning = item.warning.clone().unwrap_or_default();
-
-                            this.all_items.push(CleanupItemData {
-                                item_type: item.item_type.clone().into(),
-                                path: path_str.into(),
-                                size_str: item.size_str.clone().into(),
-                                size: item.size,
-                                safe_to_delete: item.safe_to_delete,
-                                warning: warning.into(),
-                                has_warning: item.warning.is_some(),
-                                selected: false,
-                                category_index: cat_idx,
-                            });
-                        }
-                    }
-
-                    // Build super categories (only non-empty ones)
-                    this.build_super_categories();
-
-                    this.total_reclaimable = utils::format_size(total).into();
                     this.is_scanning = false;
+                    let cancelled = this
+                        .cancel_token
+                        .take()
+                        .is_some_and(|token| token.is_cancelled());
+                    // The scan thread panicked; keep what it reported so far
+                    let Ok(categories) = scanned else {
+                        this.status_text =
+                            "✗ The scan failed; results shown may be incomplete".into();
+                        cx.notify();
+                        return;
+                    };
+
+                    // Final results, in registry order
+                    this.set_category_data(categories);
+                    let total: u64 = this.category_data.iter().map(|c| c.total_size).sum();
+                    this.total_reclaimable = utils::format_size(total).into();
 
-                    if total > 0 {
+                    if cancelled {
+                        this.status_text = format!(
+                            "Scan cancelled — showing {} completed categories",
+                            this.category_data.len()
+                        )
+                        .into();
+                    } else if total > 0 {
                         this.status_text =
                             format!("Found {} that can be cleaned", utils::format_size(total))
                                 .into();
@@ -254,6 +284,181 @@ impl DevSweep {
         .detach();
     }
 
+    /// Update scan progress and results from a single scan event
+    fn apply_scan_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Started { checkers, cached } => {
+                self.scan_progress = checkers
+                    .iter()
+                    .map(|name| CheckerProgress::new(name, CheckerStatus::Pending))
+                    .chain(
+                        cached
+                            .iter()
+                            .map(|name| CheckerProgress::new(name, CheckerStatus::Cached)),
+                    )
+                    .collect();
+            }
+            ScanEvent::CacheHit { category } => {
+                if let Some(progress) = self.checker_progress_mut(&category.name) {
+                    progress.size = category.size.clone().into();
+                }
+                self.add_scanned_category(category);
+            }
+            ScanEvent::Estimated { category } => {
+                if let Some(progress) = self.checker_progress_mut(&category.name) {
+                    progress.size = category.size.clone().into();
+                }
+                self.add_scanned_category(category);
+            }
+            ScanEvent::CheckerStarted { name } => {
+                if let Some(progress) = self.checker_progress_mut(&name) {
+                    progress.status = CheckerStatus::Running;
+                }
+            }
+            ScanEvent::Progress { name, path, bytes } => {
+                if let Some(progress) = self.checker_progress_mut(&name) {
+                    progress.current_path = path.display().to_string().into();
+                    progress.size = utils::format_size(bytes).into();
+                }
+            }
+            ScanEvent::CheckerFinished { category, .. } => {
+                if let Some(progress) = self.checker_progress_mut(&category.name) {
+                    progress.status = if category.failure.is_some() {
+                        CheckerStatus::Failed
+                    } else {
+                        CheckerStatus::Done
+                    };
+                    progress.current_path = SharedString::default();
+                    progress.size = category.size.clone().into();
+                }
+                self.add_scanned_category(category);
+            }
+            ScanEvent::Finished { .. } => {}
+        }
+
+        let done = self
+            .scan_progress
+            .iter()
+            .filter(|p| p.status != CheckerStatus::Pending && p.status != CheckerStatus::Running)
+            .count();
+        if self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            return;
+        }
+        self.status_text = format!(
+            "Scanning... {} of {} categories done",
+            done,
+            self.scan_progress.len()
+        )
+        .into();
+    }
+
+    fn checker_progress_mut(&mut self, name: &str) -> Option<&mut CheckerProgress> {
+        self.scan_progress.iter_mut().find(|p| p.name == name)
+    }
+
+    /// Show a category that finished scanning, keeping registry order
+    fn add_scanned_category(&mut self, category: CategoryData) {
+        let mut categories = self.category_data.clone();
+        categories.retain(|c| c.name != category.name);
+        categories.push(category);
+
+        let position = |name: &str| {
+            registry::all()
+                .iter()
+                .position(|c| c.name() == name)
+                .unwrap_or(usize::MAX)
+        };
+        categories.sort_by_key(|c| position(&c.name));
+
+        self.set_category_data(categories);
+        let total: u64 = self.category_data.iter().map(|c| c.total_size).sum();
+        self.total_reclaimable = utils::format_size(total).into();
+    }
+
+    /// Replace the scan results shown in the scan tab, keeping the current
+    /// selection and expanded categories
+    fn set_category_data(&mut self, mut categories: Vec<CategoryData>) {
+        overlap::mark_overlaps(&mut categories);
+        let expanded: Vec<String> = self
+            .categories
+            .iter()
+            .filter(|c| c.expanded)
+            .map(|c| c.name.to_string())
+            .collect();
+        let expanded_super: Vec<SuperCategoryType> = self
+            .super_categories
+            .iter()
+            .filter(|s| s.expanded)
+            .map(|s| s.super_type)
+            .collect();
+        let is_selected = |item: &types::CleanupItem| {
+            self.selected_items
+                .iter()
+                .any(|si| si.item_type == item.item_type && si.path == item.path)
+        };
+
+        // Convert to UI models with super category assignment
+        let category_items: Vec<CategoryItem> = categories
+            .iter()
+            .map(|c| CategoryItem {
+                name: c.name.clone().into(),
+                size: c.size.clone().into(),
+                total_size: c.total_size,
+                item_count: c.item_count,
+                checked: !c.items.is_empty() && c.items.iter().all(is_selected),
+                expanded: expanded.contains(&c.name),
+                super_category: SuperCategoryType::from_category_name(&c.name),
+            })
+            .collect();
+
+        let mut all_items = Vec::new();
+        for (cat_idx, cat) in categories.iter().enumerate() {
+            for item in &cat.items {
+                let path_str = item
+                    .path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                let warning = item.warning.clone().unwrap_or_default();
+                let covered_by = item
+                    .covered_by
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+
+                all_items.push(CleanupItemData {
+                    item_type: item.item_type.clone().into(),
+                    path: path_str.into(),
+                    size_str: item.size_str.clone().into(),
+                    size: item.size,
+                    safe_to_delete: item.safe_to_delete,
+                    warning: warning.into(),
+                    has_warning: item.warning.is_some(),
+                    selected: is_selected(item),
+                    covered_by: covered_by.into(),
+                    is_estimate: item.is_estimate,
+                    in_use: item
+                        .in_use
+                        .as_ref()
+                        .map(|in_use| in_use.to_string())
+                        .unwrap_or_default()
+                        .into(),
+                    category_index: cat_idx,
+                });
+            }
+        }
+
+        self.categories = category_items;
+        self.all_items = all_items;
+        self.category_data = categories;
+
+        // Build super categories (only non-empty ones)
+        self.build_super_categories();
+        for super_cat in &mut self.super_categories {
+            super_cat.expanded = expanded_super.contains(&super_cat.super_type);
+        }
+    }
+
     pub fn toggle_category(&mut self, index: usize, _cx: &mut ViewContext<Self>) {
         if index >= self.categories.len() {
             return;
@@ -265,8 +470,9 @@ impl DevSweep {
         // Update all items in this category
         if let Some(cat_data) = self.category_data.get(index) {
             if checked {
-                // Add all items from this category to selected
-                for item in &cat_data.items {
+                // Add all items from this category to selected; estimated
+                // ones can't be cleaned yet
+                for item in cat_data.items.iter().filter(|item| !item.is_estimate) {
                     if !self
                         .selected_items
                         .iter()
@@ -308,6 +514,11 @@ impl DevSweep {
         }
 
         let item_data = &mut self.all_items[index];
+        // Estimated sizes are replaced once the scan finishes; only then can
+        // the item be cleaned
+        if item_data.is_estimate {
+            return;
+        }
         item_data.selected = !item_data.selected;
         let selected = item_data.selected;
         let cat_idx = item_data.category_index;
@@ -355,7 +566,8 @@ impl DevSweep {
         }
         self.selected_items.clear();
         for cat in &self.category_data {
-            self.selected_items.extend(cat.items.clone());
+            self.selected_items
+                .extend(cat.items.iter().filter(|item| !item.is_estimate).cloned());
         }
         self.update_selection_counts();
     }
@@ -372,6 +584,12 @@ impl DevSweep {
     }
 
     pub fn update_selection_counts(&mut self) {
+        // An item inside another selected item is cleaned with it; never select both
+        overlap::drop_covered(&mut self.selected_items);
+        self.sync_selection_checkboxes();
+        // A review of the previous selection no longer applies
+        self.pending_preflight = None;
+
         let total_size: u64 = self.selected_items.iter().map(|si| si.size).sum();
         self.selected_items_count = self.selected_items.len() as i32;
         self.selected_items_size = utils::format_size(total_size).into();
@@ -380,6 +598,30 @@ impl DevSweep {
         self.update_super_category_states();
     }
 
+    /// Refresh item and category checkboxes from `selected_items`. A category
+    /// counts as checked when each of its items is selected or inside a
+    /// selected item.
+    fn sync_selection_checkboxes(&mut self) {
+        let selected = &self.selected_items;
+        let is_selected = |item: &types::CleanupItem| {
+            selected
+                .iter()
+                .any(|si| si.item_type == item.item_type && si.path == item.path)
+        };
+        let is_included = |item: &types::CleanupItem| {
+            is_selected(item) || selected.iter().any(|si| overlap::covers(si, item))
+        };
+
+        // `all_items` is built category by category, item by item
+        let backend_items = self.category_data.iter().flat_map(|c| c.items.iter());
+        for (item_data, item) in self.all_items.iter_mut().zip(backend_items) {
+            item_data.selected = is_selected(item);
+        }
+        for (category, cat_data) in self.categories.iter_mut().zip(&self.category_data) {
+            category.checked = !cat_data.items.is_empty() && cat_data.items.iter().all(is_included);
+        }
+    }
+
     /// Build super categories from the current categories (filters out empty ones)
     pub fn build_super_categories(&mut self) {
         self.super_categories.clear();
@@ -476,28 +718,128 @@ impl DevSweep {
         }
 
         self.is_cleaning = true;
-        self.status_text = format!("Deleting {} items...", self.selected_items.len()).into();
+        self.pending_preflight = None;
+        self.status_text = "Checking selected items...".into();
         cx.notify();
 
+        // Sizing directories that changed since the scan can take a moment
         let backend = self.backend.clone();
-        let items_to_clean = self.selected_items.clone();
+        let items = self.selected_items.clone();
+        let check = std::thread::spawn(move || backend.lock().unwrap().preflight(&items));
 
         cx.spawn(|this, mut cx| async move {
-            let result = {
-                let mut backend = backend.lock().unwrap();
-                backend.execute_cleanup_with_history(&items_to_clean, true)
+            while !check.is_finished() {
+                cx.background_executor()
+                    .timer(SCAN_EVENT_POLL_INTERVAL)
+                    .await;
+            }
+            let checked = check.join();
+
+            let _ = cx.update(|cx| {
+                let _ = this.update(cx, |this, cx| {
+                    this.is_cleaning = false;
+                    let Ok(preflight) = checked else {
+                        this.status_text =
+                            "✗ Could not check the selected items; nothing was cleaned".into();
+                        cx.notify();
+                        return;
+                    };
+                    if preflight.has_changes() {
+                        // Let the user review the differences before anything is moved
+                        this.status_text = format!("⚠ {}", preflight.summary()).into();
+                        this.pending_preflight = Some(preflight);
+                        cx.notify();
+                    } else {
+                        this.run_cleanup(preflight.items(), true, cx);
+                    }
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// Clean the selection as it is now, after reviewing what changed since the scan
+    pub fn confirm_preflight(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(preflight) = self.pending_preflight.take() else {
+            return;
+        };
+        let items = preflight.items();
+        if items.is_empty() {
+            self.status_text = "Nothing left to clean; rescan to refresh the results".into();
+            cx.notify();
+            return;
+        }
+        self.run_cleanup(items, true, cx);
+    }
+
+    pub fn dismiss_preflight(&mut self, cx: &mut ViewContext<Self>) {
+        self.pending_preflight = None;
+        self.status_text = "Cleanup cancelled".into();
+        cx.notify();
+    }
+
+    /// Delete the items that could not be quarantined by the last cleanup
+    pub fn retry_cleanup_permanently(&mut self, cx: &mut ViewContext<Self>) {
+        let items_to_clean = std::mem::take(&mut self.retry_permanent_items);
+        if !items_to_clean.is_empty() {
+            self.run_cleanup(items_to_clean, false, cx);
+        }
+    }
+
+    fn run_cleanup(
+        &mut self,
+        items_to_clean: Vec<types::CleanupItem>,
+        use_quarantine: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.is_cleaning = true;
+        self.retry_permanent_items.clear();
+        self.status_text = format!("Deleting {} items...", items_to_clean.len()).into();
+        cx.notify();
+
+        let backend = self.backend.clone();
+        let cancel = CancellationToken::new();
+        self.cancel_token = Some(cancel.clone());
+
+        let items = items_to_clean.clone();
+        // Run the cleanup on its own thread so the Cancel button stays responsive
+        let cleanup = std::thread::spawn(move || {
+            let mut backend = backend.lock().unwrap();
+            backend.execute_cleanup_with_cancel(&items, use_quarantine, &cancel)
+        });
+
+        cx.spawn(|this, mut cx| async move {
+            while !cleanup.is_finished() {
+                cx.background_executor()
+                    .timer(SCAN_EVENT_POLL_INTERVAL)
+                    .await;
+            }
+            let record = match cleanup.join() {
+                Ok(record) => record,
+                Err(_) => return,
             };
 
             let _ = cx.update(|cx| {
                 let _ = this.update(cx, |this, cx| {
                     this.is_cleaning = false;
+                    this.cancel_token = None;
 
-                    match result {
-                        Ok(msg) => {
-                            this.status_text = format!("✓ {}", msg).into();
+                    match record.failures().next() {
+                        None if record.cancelled => {
+                            this.status_text = format!("⏹ {}", record.summary()).into();
                         }
-                        Err(e) => {
-                            this.status_text = format!("⚠ {}", e).into();
+                        None => {
+                            this.status_text = format!("✓ {}", record.summary()).into();
+                        }
+                        Some((item, error)) => {
+                            let mut text =
+                                format!("⚠ {} — {}: {}", record.summary(), item.item_type, error);
+                            if let Some(remedy) = error.remedy() {
+                                text.push_str(&format!(". {}", remedy));
+                            }
+                            this.status_text = text.into();
+                            this.retry_permanent_items =
+                                record.permanent_retry_items(&items_to_clean);
                         }
                     }
 
@@ -509,6 +851,15 @@ impl DevSweep {
         .detach();
     }
 
+    /// Ask the running scan or cleanup to stop after its current unit of work
+    pub fn cancel_operation(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(token) = &self.cancel_token {
+            token.cancel();
+            self.status_text = "Cancelling...".into();
+            cx.notify();
+        }
+    }
+
     pub fn toggle_quarantine_record_expand(&mut self, index: usize, _cx: &mut ViewContext<Self>) {
         if index < self.quarantine_records.len() {
             self.quarantine_records[index].expanded = !self.quarantine_records[index].expanded;
@@ -533,11 +884,15 @@ impl DevSweep {
                     this.is_cleaning = false;
 
                     match result {
-                        Ok(msg) => {
-                            this.status_text = format!("✓ {}", msg).into();
-                        }
+                        Ok(result) => match result.errors.first() {
+                            None => this.status_text = format!("✓ {}", result.summary()).into(),
+                            Some(error) => {
+                                this.status_text =
+                                    format!("⚠ {} — {}", result.summary(), error.explain()).into();
+                            }
+                        },
                         Err(e) => {
-                            this.status_text = format!("✗ {}", e).into();
+                            this.status_text = format!("✗ {}", e.explain()).into();
                         }
                     }
 
@@ -577,7 +932,7 @@ impl DevSweep {
                             this.status_text = format!("✓ {}", msg).into();
                         }
                         Err(e) => {
-                            this.status_text = format!("✗ {}", e).into();
+                            this.status_text = format!("✗ {}", e.explain()).into();
                         }
                     }
 
@@ -612,7 +967,7 @@ impl DevSweep {
                             this.status_text = format!("✓ {}", msg).into();
                         }
                         Err(e) => {
-                            this.status_text = format!("✗ {}", e).into();
+                            this.status_text = format!("✗ {}", e.explain()).into();
                         }
                     }
 
@@ -632,6 +987,19 @@ impl DevSweep {
         self.refresh_cache_ttls();
     }
 
+    /// Switch scan mode and save it for future launches and the CLI
+    pub fn set_scan_mode(&mut self, mode: ScanMode, _cx: &mut ViewContext<Self>) {
+        self.scan_mode = mode;
+        self.backend.lock().unwrap().set_scan_mode(mode);
+        let settings = ScanSettings {
+            mode,
+            ..ScanSettings::load()
+        };
+        if let Err(e) = settings.save() {
+            eprintln!("Failed to save scan settings: {}", e);
+        }
+    }
+
     /// Check for updates from GitHub releases
     pub fn check_for_updates(&mut self, cx: &mut ViewContext<Self>) {
         // Don't check if already checking
@@ -1,5 +1,8 @@
 use crate::backend::{CategoryData, StorageBackend};
+use crate::cancel::CancellationToken;
 use crate::custom_paths::{CustomPath, CustomPathsConfig};
+use crate::preflight::Preflight;
+use crate::scan_mode::ScanMode;
 use crate::types;
 use crate::ui::sidebar::Tab;
 use crate::ui::ThemeMode;
@@ -9,72 +12,7 @@ use gpui::*;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-// Super category definitions
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum SuperCategoryType {
-    DevelopmentTools,
-    PackageManagers,
-    ProjectFiles,
-    SystemAndBrowsers,
-    Trash,
-}
-
-impl SuperCategoryType {
-    pub fn name(&self) -> &'static str {
-        match self {
-            Self::DevelopmentTools => "Development Tools",
-            Self::PackageManagers => "Package Managers",
-            Self::ProjectFiles => "Project Files",
-            Self::SystemAndBrowsers => "System & Browsers",
-            Self::Trash => "Trash",
-        }
-    }
-
-    pub fn icon(&self) -> &'static str {
-        match self {
-            Self::DevelopmentTools => "🛠",
-            Self::PackageManagers => "📦",
-            Self::ProjectFiles => "📁",
-            Self::SystemAndBrowsers => "🌐",
-            Self::Trash => "🗑",
-        }
-    }
-
-    /// Map a category name to its super category
-    pub fn from_category_name(name: &str) -> Self {
-        match name {
-            // Development Tools
-            "Docker" | "Homebrew" | "Xcode" | "IDE Caches" => Self::DevelopmentTools,
-            // Package Managers (match actual checker names)
-            "Node.js Package Managers" | "Python" | "Rust/Cargo" | "Go" | "Java Build Tools" => {
-                Self::PackageManagers
-            }
-            // Project Files
-            "node_modules in Projects" | "Git Repositories" | "Custom Paths" => Self::ProjectFiles,
-            // System & Browsers (match actual checker names)
-            "System Logs & Crash Reports"
-            | "Browser Caches"
-            | "Shell Caches"
-            | "Database Caches"
-            | "General Caches" => Self::SystemAndBrowsers,
-            // Trash
-            "Trash" => Self::Trash,
-            // Default to System & Browsers for unknown categories
-            _ => Self::SystemAndBrowsers,
-        }
-    }
-
-    /// Get all super category types in display order
-    pub fn all() -> Vec<Self> {
-        vec![
-            Self::DevelopmentTools,
-            Self::PackageManagers,
-            Self::ProjectFiles,
-            Self::SystemAndBrowsers,
-            Self::Trash,
-        ]
-    }
-}
+pub use crate::types::SuperCategoryType;
 
 #[derive(Clone)]
 pub struct SuperCategoryItem {
@@ -117,9 +55,48 @@ pub struct CleanupItemData {
     pub warning: SharedString,
     pub has_warning: bool,
     pub selected: bool,
+    /// Path of the item that already includes this one, empty if none
+    pub covered_by: SharedString,
+    /// The size is an estimate and the item can't be selected yet
+    pub is_estimate: bool,
+    /// What is using the item (e.g. "In use by node (pid 412)"), empty if nothing
+    pub in_use: SharedString,
     pub category_index: usize,
 }
 
+/// Where a checker is in the current scan
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CheckerStatus {
+    Pending,
+    Running,
+    Done,
+    Cached,
+    /// Timed out or crashed; the category's status says which
+    Failed,
+}
+
+/// Per-checker progress shown while a scan runs
+#[derive(Clone)]
+pub struct CheckerProgress {
+    pub name: SharedString,
+    pub status: CheckerStatus,
+    /// Directory the checker is currently looking at
+    pub current_path: SharedString,
+    /// Size counted so far, or the category total once done
+    pub size: SharedString,
+}
+
+impl CheckerProgress {
+    pub fn new(name: &str, status: CheckerStatus) -> Self {
+        Self {
+            name: name.to_string().into(),
+            status,
+            current_path: SharedString::default(),
+            size: SharedString::default(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct QuarantineRecordData {
     pub id: SharedString,
@@ -160,6 +137,8 @@ pub struct DevSweep {
     pub theme_mode: ThemeMode,
     pub is_scanning: bool,
     pub is_cleaning: bool,
+    /// Token for the running scan or cleanup, if it can be cancelled
+    pub cancel_token: Option<CancellationToken>,
     pub status_text: SharedString,
     pub storage_available: SharedString,
     pub total_reclaimable: SharedString,
@@ -169,12 +148,20 @@ pub struct DevSweep {
     pub categories: Vec<CategoryItem>,
     pub all_items: Vec<CleanupItemData>,
     pub category_data: Vec<CategoryData>,
+    pub scan_progress: Vec<CheckerProgress>,
     pub selected_items: Vec<types::CleanupItem>,
+    /// Items the last cleanup could not quarantine, offered for permanent deletion
+    pub retry_permanent_items: Vec<types::CleanupItem>,
+    /// Selected items that changed since the scan, waiting for the user to
+    /// confirm the cleanup
+    pub pending_preflight: Option<Preflight>,
     pub quarantine_records: Vec<QuarantineRecordData>,
     pub quarantine_items: Vec<QuarantineItemData>,
     pub quarantine_total_size: SharedString,
     pub quarantine_total_items: i32,
     pub cache_ttls: Vec<CacheTTLSetting>,
+    /// How hard scans may work the machine, as saved in settings
+    pub scan_mode: ScanMode,
     // Update checker state
     pub is_checking_update: bool,
     pub update_info: Option<UpdateInfo>,
@@ -198,14 +185,15 @@ impl DevSweep {
 
         // Load initial cache TTLs
         let ttls = backend.lock().unwrap().get_all_cache_ttls();
-        let mut cache_ttls: Vec<CacheTTLSetting> = ttls
+        let cache_ttls: Vec<CacheTTLSetting> = ttls
             .iter()
             .map(|(cat, ttl_sec)| CacheTTLSetting {
                 category: cat.clone().into(),
                 ttl_minutes: (*ttl_sec / 60) as i32,
             })
             .collect();
-        cache_ttls.sort_by(|a, b| a.category.cmp(&b.category));
+
+        let scan_mode = backend.lock().unwrap().scan_context.scan_mode;
 
         // Get initial storage info
         let storage_available = if let Ok(stat) = fs2::statvfs("/") {
@@ -220,6 +208,7 @@ impl DevSweep {
             theme_mode: ThemeMode::default(),
             is_scanning: false,
             is_cleaning: false,
+            cancel_token: None,
             status_text: "Click 'Scan' to analyze your storage".into(),
             storage_available,
             total_reclaimable: "0 B".into(),
@@ -229,12 +218,16 @@ impl DevSweep {
             categories: Vec::new(),
             all_items: Vec::new(),
             category_data: Vec::new(),
+            scan_progress: Vec::new(),
             selected_items: Vec::new(),
+            retry_permanent_items: Vec::new(),
+            pending_preflight: None,
             quarantine_records: Vec::new(),
             quarantine_items: Vec::new(),
             quarantine_total_size: "0 B".into(),
             quarantine_total_items: 0,
             cache_ttls,
+            scan_mode,
             // Update checker state
             is_checking_update: false,
             update_info: None,
@@ -1,4 +1,7 @@
-use crate::app::state::{CategoryItem, CleanupItemData, DevSweep, SuperCategoryItem};
+use crate::app::state::{
+    CategoryItem, CheckerProgress, CheckerStatus, CleanupItemData, DevSweep, SuperCategoryItem,
+};
+use crate::preflight::Preflight;
 use crate::ui::Theme;
 use gpui::prelude::FluentBuilder;
 use gpui::*;
@@ -7,13 +10,20 @@ impl DevSweep {
     pub fn render_scan_tab(&mut self, cx: &mut ViewContext<Self>) -> Div {
         let is_scanning = self.is_scanning;
         let is_cleaning = self.is_cleaning;
+        let can_cancel = self.cancel_token.is_some();
         let status_text = self.status_text.clone();
         let total_reclaimable = self.total_reclaimable.clone();
         let selected_count = self.selected_items_count;
         let selected_size = self.selected_items_size.clone();
+        let retry_count = self.retry_permanent_items.len();
         let super_categories = self.super_categories.clone();
         let categories = self.categories.clone();
         let items = self.all_items.clone();
+        let scan_progress = self.scan_progress.clone();
+        let preflight_review = self
+            .pending_preflight
+            .clone()
+            .map(|preflight| self.render_preflight_review(&preflight, cx));
 
         div()
             .w_full()
@@ -74,6 +84,32 @@ impl DevSweep {
                                         ),
                                 )
                             })
+                            .when((is_scanning || is_cleaning) && can_cancel, |d| {
+                                d.child(
+                                    div()
+                                        .id("cancel-btn")
+                                        .px_4()
+                                        .py_2()
+                                        .bg(Theme::surface0(self.theme_mode))
+                                        .border_1()
+                                        .border_color(Theme::red(self.theme_mode))
+                                        .rounded_md()
+                                        .cursor_pointer()
+                                        .hover(|style| style.bg(Theme::surface1(self.theme_mode)))
+                                        .active(|style| {
+                                            style.bg(Theme::surface2(self.theme_mode)).opacity(0.9)
+                                        })
+                                        .on_click(cx.listener(|this, _event, cx| {
+                                            this.cancel_operation(cx);
+                                        }))
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .text_color(Theme::red(self.theme_mode))
+                                                .child("Cancel"),
+                                        ),
+                                )
+                            })
                             .when(!is_scanning && !is_cleaning, |d| {
                                 d.child(
                                     div()
@@ -235,6 +271,35 @@ impl DevSweep {
                                             .child("Deselect All"),
                                     ),
                             )
+                            .when(retry_count > 0 && !is_cleaning, |d| {
+                                d.child(
+                                    div()
+                                        .id("retry-permanent-btn")
+                                        .px_4()
+                                        .py_2()
+                                        .bg(Theme::surface0(self.theme_mode))
+                                        .border_1()
+                                        .border_color(Theme::red(self.theme_mode))
+                                        .rounded_md()
+                                        .cursor_pointer()
+                                        .hover(|style| style.bg(Theme::surface1(self.theme_mode)))
+                                        .active(|style| {
+                                            style.bg(Theme::surface2(self.theme_mode)).opacity(0.9)
+                                        })
+                                        .on_click(cx.listener(|this, _event, cx| {
+                                            this.retry_cleanup_permanently(cx);
+                                        }))
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .text_color(Theme::red(self.theme_mode))
+                                                .child(format!(
+                                                    "Delete {} Permanently",
+                                                    retry_count
+                                                )),
+                                        ),
+                                )
+                            })
                             .when(selected_count > 0 && !is_cleaning, |d| {
                                 d.child(
                                     div()
@@ -279,6 +344,12 @@ impl DevSweep {
                             }),
                     ),
             )
+            // What changed since the scan, before a cleanup goes ahead
+            .children(preflight_review)
+            // Per-checker progress while scanning
+            .when(is_scanning && !scan_progress.is_empty(), |d| {
+                d.child(self.render_scan_progress(&scan_progress))
+            })
             // Super categories list
             .child(
                 div()
@@ -286,7 +357,9 @@ impl DevSweep {
                     .flex_1()
                     .w_full()
                     .overflow_y_scroll()
-                    .child(if super_categories.is_empty() {
+                    .child(if super_categories.is_empty() && is_scanning {
+                        self.empty_state("Results appear here as each category finishes")
+                    } else if super_categories.is_empty() {
                         self.empty_state("Click 'Scan' to analyze your storage")
                     } else {
                         div().w_full().flex().flex_col().children(
@@ -307,6 +380,161 @@ impl DevSweep {
             )
     }
 
+    /// Compact list of checkers with their state, current directory and size so far
+    pub fn render_scan_progress(&self, progress: &[CheckerProgress]) -> Div {
+        div()
+            .w_full()
+            .px_4()
+            .py_2()
+            .flex()
+            .flex_wrap()
+            .gap_2()
+            .border_b_1()
+            .border_color(Theme::surface0(self.theme_mode))
+            .children(progress.iter().map(|checker| {
+                let (icon, color) = match checker.status {
+                    CheckerStatus::Pending => ("○", Theme::overlay0(self.theme_mode)),
+                    CheckerStatus::Running => ("◐", Theme::blue(self.theme_mode)),
+                    CheckerStatus::Done => ("✓", Theme::green(self.theme_mode)),
+                    CheckerStatus::Cached => ("📦", Theme::subtext0(self.theme_mode)),
+                    CheckerStatus::Failed => ("✗", Theme::red(self.theme_mode)),
+                };
+                let detail = if checker.status == CheckerStatus::Running {
+                    checker.current_path.clone()
+                } else {
+                    SharedString::default()
+                };
+
+                div()
+                    .px_2()
+                    .py_1()
+                    .bg(Theme::surface0(self.theme_mode))
+                    .rounded_md()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .max_w(px(360.0))
+                    .child(div().text_xs().text_color(color).child(icon))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(Theme::text(self.theme_mode))
+                            .child(checker.name.clone()),
+                    )
+                    .when(!checker.size.is_empty(), |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .text_color(Theme::peach(self.theme_mode))
+                                .child(checker.size.clone()),
+                        )
+                    })
+                    .when(!detail.is_empty(), |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .text_color(Theme::overlay1(self.theme_mode))
+                                .overflow_hidden()
+                                .whitespace_nowrap()
+                                .child(detail),
+                        )
+                    })
+            }))
+    }
+
+    /// Items that vanished or changed size since the scan, with buttons to
+    /// clean the selection as it is now or to cancel
+    pub fn render_preflight_review(
+        &self,
+        preflight: &Preflight,
+        cx: &mut ViewContext<Self>,
+    ) -> Div {
+        div()
+            .w_full()
+            .px_4()
+            .py_3()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .bg(Theme::surface0(self.theme_mode))
+            .border_b_1()
+            .border_color(Theme::yellow(self.theme_mode))
+            .child(
+                div()
+                    .text_sm()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .text_color(Theme::yellow(self.theme_mode))
+                    .child(preflight.summary()),
+            )
+            .children(preflight.changes().map(|(item, change)| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(Theme::text(self.theme_mode))
+                            .child(item.item_type.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(Theme::subtext0(self.theme_mode))
+                            .child(change.describe()),
+                    )
+            }))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .id("preflight-confirm-btn")
+                            .px_4()
+                            .py_2()
+                            .bg(Theme::red(self.theme_mode))
+                            .rounded_md()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(Theme::red_hover(self.theme_mode)))
+                            .active(|style| {
+                                style.bg(Theme::red_active(self.theme_mode)).opacity(0.9)
+                            })
+                            .on_click(cx.listener(|this, _event, cx| {
+                                this.confirm_preflight(cx);
+                            }))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(Theme::crust(self.theme_mode))
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .child("Clean Remaining Items"),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .id("preflight-cancel-btn")
+                            .px_4()
+                            .py_2()
+                            .bg(Theme::surface1(self.theme_mode))
+                            .rounded_md()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(Theme::surface2(self.theme_mode)))
+                            .active(|style| style.bg(Theme::surface2(self.theme_mode)).opacity(0.9))
+                            .on_click(cx.listener(|this, _event, cx| {
+                                this.dismiss_preflight(cx);
+                            }))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(Theme::text(self.theme_mode))
+                                    .child("Cancel"),
+                            ),
+                    ),
+            )
+    }
+
     pub fn empty_state(&self, message: &str) -> Div {
         div()
             .w_full()
@@ -603,6 +831,9 @@ impl DevSweep {
         let has_warning = item.has_warning;
         let safe_to_delete = item.safe_to_delete;
         let path_empty = item.path.is_empty();
+        let covered = !item.covered_by.is_empty();
+        let estimate = item.is_estimate;
+        let in_use = !item.in_use.is_empty();
 
         div()
             .id(SharedString::from(format!("item-{}", global_idx)))
@@ -671,6 +902,30 @@ impl DevSweep {
                                 .text_color(Theme::overlay0(self.theme_mode))
                                 .child(item.path.clone()),
                         )
+                    })
+                    .when(covered, |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .text_color(Theme::subtext0(self.theme_mode))
+                                .child(format!("Included in {}", item.covered_by)),
+                        )
+                    })
+                    .when(estimate, |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .text_color(Theme::subtext0(self.theme_mode))
+                                .child("Estimated size — measuring…"),
+                        )
+                    })
+                    .when(in_use, |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .text_color(Theme::red(self.theme_mode))
+                                .child(format!("{} — quit it before cleaning", item.in_use)),
+                        )
                     }),
             )
             // Warning indicator
@@ -1,5 +1,6 @@
 use crate::app::state::{CacheTTLSetting, DevSweep, SuperCategoryType};
 use crate::custom_paths::CustomPath;
+use crate::scan_mode::ScanMode;
 use crate::ui::Theme;
 use gpui::*;
 
@@ -68,6 +69,8 @@ impl DevSweep {
                             .flex()
                             .flex_col()
                             .gap_6()
+                            // Scan mode Section
+                            .child(self.render_scan_mode_section(cx))
                             // Cache TTL Section
                             .child(self.render_cache_ttl_section(&cache_ttls, cx))
                             // Info section
@@ -109,6 +112,71 @@ impl DevSweep {
             )
     }
 
+    /// Render the choice between normal and low-impact scanning
+    fn render_scan_mode_section(&self, cx: &mut ViewContext<Self>) -> Div {
+        div()
+            .w_full()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(Theme::text(self.theme_mode))
+                            .child("Scan Mode"),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(Theme::subtext0(self.theme_mode))
+                            .child(
+                                "Background scans use two low-priority threads and throttle disk reads, so they take longer but don't slow down builds or calls.",
+                            ),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .children(ScanMode::all().into_iter().map(|mode| {
+                        let active = self.scan_mode == mode;
+                        div()
+                            .id(SharedString::from(format!("scan-mode-{}", mode.name())))
+                            .px_4()
+                            .py_2()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .bg(if active {
+                                Theme::blue(self.theme_mode)
+                            } else {
+                                Theme::surface0(self.theme_mode)
+                            })
+                            .hover(|style| style.opacity(0.9))
+                            .on_click(cx.listener(move |this, _event, cx| {
+                                this.set_scan_mode(mode, cx);
+                                cx.notify();
+                            }))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(if active {
+                                        Theme::crust(self.theme_mode)
+                                    } else {
+                                        Theme::text(self.theme_mode)
+                                    })
+                                    .child(mode.label()),
+                            )
+                    })),
+            )
+    }
+
     /// Render the entire Cache TTL section grouped by super category
     fn render_cache_ttl_section(
         &self,
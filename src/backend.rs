@@ -1,38 +1,101 @@
-use crate::checkers;
-use crate::cleanup_history::{CleanupHistory, CleanupItemRecord, CleanupRecord};
-use crate::custom_paths;
+use crate::cancel::CancellationToken;
+use crate::checkers::registry::{self, Checker};
+use crate::checkers::{ProjectWalk, ScanContext};
+use crate::cleanup_history::{CleanupHistory, CleanupItemRecord, CleanupRecord, UndoResult};
+use crate::cleanup_plan::{CleanupMode, CleanupPlan, PlannedOperation};
+use crate::deletion_policy::{DeletionPolicy, DeletionSettings};
+use crate::error::DevSweepError;
+use crate::in_use::InUseDetector;
+use crate::mounts::{self, MountTable};
+use crate::overlap;
+use crate::preflight::Preflight;
 use crate::scan_cache::{PathTracker, ScanCache};
-use crate::types::{CheckResult, CleanupItem};
+use crate::scan_mode::{ScanMode, ScanSettings};
+use crate::scan_progress::{ProgressReporter, ScanEvent};
+use crate::size_index::SizeIndex;
+use crate::types::{CheckFailure, CheckResult, CleanupAction, CleanupItem, ExtraData};
 use crate::utils::format_size;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::SystemTime;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-// Type alias for checker functions to simplify type signatures
-type CheckerFn = fn() -> CheckResult;
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryData {
     pub name: String,
     pub size: String,
     pub total_size: u64,
     pub item_count: i32,
     pub items: Vec<CleanupItem>,
+    /// Status reported by the checker (e.g. "Docker not installed")
+    pub status: Option<String>,
+    pub extra_data: ExtraData,
+    /// When the underlying check ran
+    #[serde(with = "crate::report::timestamp")]
+    pub scanned_at: SystemTime,
+    /// Whether the result came from the scan cache rather than a fresh check
+    pub from_cache: bool,
+    /// Set when the checker timed out or crashed; `status` says which
+    #[serde(default)]
+    pub failure: Option<CheckFailure>,
 }
 
 impl CategoryData {
     pub fn new(name: String, result: CheckResult) -> Self {
         let item_count = result.items.len() as i32;
-        Self {
+        let mut category = Self {
             name,
-            size: format_size(result.total_size),
-            total_size: result.total_size,
+            size: String::new(),
+            total_size: 0,
             item_count,
             items: result.items,
-        }
+            status: result.status,
+            extra_data: result.extra_data,
+            scanned_at: SystemTime::now(),
+            from_cache: false,
+            failure: result.failure,
+        };
+        category.set_total_size(result.total_size);
+        category
+    }
+
+    /// Data from the quick first pass of a scan, every item marked as an estimate
+    pub fn estimated(name: String, mut result: CheckResult) -> Self {
+        result.items = result
+            .items
+            .into_iter()
+            .map(CleanupItem::into_estimate)
+            .collect();
+        Self::new(name, result)
+    }
+
+    /// Whether any item's size is still an estimate
+    pub fn is_estimate(&self) -> bool {
+        self.items.iter().any(|item| item.is_estimate)
+    }
+
+    /// Set `total_size` and its display string, marked `~` while estimated
+    pub fn set_total_size(&mut self, total_size: u64) {
+        self.total_size = total_size;
+        self.size = if self.is_estimate() {
+            format!("~{}", format_size(total_size))
+        } else {
+            format_size(total_size)
+        };
+    }
+
+    /// Mark this data as served from a cached scan made at `scanned_at`
+    pub fn with_cached_at(mut self, scanned_at: SystemTime) -> Self {
+        self.scanned_at = scanned_at;
+        self.from_cache = true;
+        self
     }
 }
 
@@ -40,6 +103,11 @@ pub struct StorageBackend {
     pub categories: HashMap<String, CategoryData>,
     pub scan_cache: ScanCache,
     pub cleanup_history: CleanupHistory,
+    pub scan_context: ScanContext,
+    /// Directory sizes from previous scans, so unchanged trees aren't walked again
+    pub size_index: Arc<SizeIndex>,
+    /// The user's additions to the deletion policy every cleanup is checked against
+    pub deletion_settings: DeletionSettings,
 }
 
 impl StorageBackend {
@@ -48,51 +116,95 @@ impl StorageBackend {
             categories: HashMap::new(),
             scan_cache: ScanCache::load(),
             cleanup_history: CleanupHistory::load(),
+            scan_context: ScanSettings::load().apply(ScanContext::new()),
+            size_index: Arc::new(SizeIndex::load()),
+            deletion_settings: DeletionSettings::load(),
         }
     }
 
+    /// Scan a different home directory or environment than the current user's
+    pub fn with_scan_context(mut self, scan_context: ScanContext) -> Self {
+        self.scan_context = scan_context;
+        self
+    }
+
     /// Scan with optional cache usage (incremental scanning)
     pub fn scan_with_cache(&mut self, use_cache: bool) -> Vec<CategoryData> {
-        // Define all checks with their names (in desired display order)
-        let all_checks: Vec<(&str, CheckerFn)> = vec![
-            ("Docker", checkers::check_docker),
-            ("Homebrew", checkers::check_homebrew),
-            ("Node.js/npm/yarn", checkers::check_npm_yarn),
-            ("Python", checkers::check_python),
-            ("Rust/Cargo", checkers::check_rust),
-            ("Xcode", checkers::check_xcode),
-            ("Java (Gradle/Maven)", checkers::check_gradle_maven),
-            ("Go", checkers::check_go),
-            ("IDE Caches", checkers::check_ide_caches),
-            ("Shell Caches", checkers::check_shell_caches),
-            ("Database Caches", checkers::check_db_caches),
-            ("System Logs", checkers::check_system_logs),
-            ("Browser Caches", checkers::check_browser_caches),
-            ("node_modules in Projects", checkers::check_node_modules),
-            ("Git Repositories", checkers::check_git_repos),
-            ("General Caches", checkers::check_general_caches),
-            ("Custom Paths", custom_paths::check_custom_paths),
-            ("Trash", checkers::check_trash),
-        ];
+        self.scan(use_cache, None, &CancellationToken::new())
+    }
+
+    /// Like `scan_with_cache`, but sends a `ScanEvent` to `events` as each
+    /// checker starts, makes progress and finishes. Results are also returned
+    /// in registry order once everything is done.
+    ///
+    /// When `cancel` is triggered, checkers that haven't started are skipped
+    /// and those still running stop early; only categories that completed
+    /// before cancellation are returned and cached.
+    pub fn scan_with_events(
+        &mut self,
+        use_cache: bool,
+        events: Sender<ScanEvent>,
+        cancel: &CancellationToken,
+    ) -> Vec<CategoryData> {
+        self.scan(use_cache, Some(events), cancel)
+    }
+
+    /// Set how hard scans may work the machine
+    pub fn set_scan_mode(&mut self, mode: ScanMode) {
+        self.scan_context.scan_mode = mode;
+    }
+
+    fn scan(
+        &mut self,
+        use_cache: bool,
+        events: Option<Sender<ScanEvent>>,
+        cancel: &CancellationToken,
+    ) -> Vec<CategoryData> {
+        // Parallel iterators run on the pool that `install` was called on;
+        // checkers, on threads of their own, are installed into it too
+        match self.scan_context.scan_mode.thread_pool().map(Arc::new) {
+            Some(pool) => {
+                pool.install(|| self.scan_in_pool(use_cache, events, cancel, Some(&pool)))
+            }
+            None => self.scan_in_pool(use_cache, events, cancel, None),
+        }
+    }
+
+    fn scan_in_pool(
+        &mut self,
+        use_cache: bool,
+        events: Option<Sender<ScanEvent>>,
+        cancel: &CancellationToken,
+        pool: Option<&Arc<rayon::ThreadPool>>,
+    ) -> Vec<CategoryData> {
+        // The receiver may have gone away; events are best effort
+        let send = |event: ScanEvent| {
+            if let Some(events) = &events {
+                let _ = events.send(event);
+            }
+        };
+
+        // All registered checkers, in display order
+        let all_checks = registry::all();
 
         self.categories.clear();
 
         let total_checks = all_checks.len();
 
         // Determine which checks need to run based on cache validity and TTL
-        let checks_to_run: Vec<_> = if use_cache {
+        let checks_to_run: Vec<&dyn Checker> = if use_cache {
             all_checks
                 .iter()
-                .filter(|(name, _)| {
+                .filter(|checker| {
                     // Check if category needs rescanning based on:
                     // 1. TTL expiration (if TTL = 0, always rescan)
                     // 2. File/directory metadata changes
-                    self.scan_cache.needs_rescan(name)
+                    self.scan_cache.needs_rescan(checker.name())
                 })
                 .copied()
                 .collect()
         } else {
-            all_checks.clone()
+            all_checks.to_vec()
         };
 
         let cached_count = if use_cache {
@@ -102,83 +214,298 @@ impl StorageBackend {
         };
 
         if cached_count > 0 {
-            println!("📦 Using cached results for {} categories", cached_count);
+            eprintln!("📦 Using cached results for {} categories", cached_count);
+        }
+
+        let cached_names: Vec<&str> = all_checks
+            .iter()
+            .map(|c| c.name())
+            .filter(|name| !checks_to_run.iter().any(|c| c.name() == *name))
+            .collect();
+        send(ScanEvent::Started {
+            checkers: checks_to_run.iter().map(|c| c.name().to_string()).collect(),
+            cached: cached_names.iter().map(|n| n.to_string()).collect(),
+        });
+        for name in &cached_names {
+            if let Some(category) = self.cached_category(name) {
+                send(ScanEvent::CacheHit { category });
+            }
         }
 
         // Run checks in parallel but collect into a HashMap to preserve order
-        let results_map: HashMap<String, (CheckResult, HashMap<PathBuf, _>)> = checks_to_run
+        let mut ctx = self
+            .scan_context
+            .clone()
+            .with_cancel(cancel.clone())
+            .with_size_index(self.size_index.clone())
+            .with_projects(Arc::new(ProjectWalk::new()))
+            .with_mounts(
+                self.scan_context
+                    .mounts
+                    .clone()
+                    .unwrap_or_else(|| Arc::new(MountTable::load())),
+            );
+        if let Some(pool) = pool {
+            ctx = ctx.with_thread_pool(pool.clone());
+        }
+        let ctx = &ctx;
+
+        // A first scan walks everything from scratch, which can take minutes;
+        // show every category with estimated sizes while it does
+        if events.is_some() && self.needs_estimate_pass() {
+            self.run_estimates(&checks_to_run, ctx, &|category| {
+                send(ScanEvent::Estimated { category })
+            });
+        }
+
+        let results_map: HashMap<&str, (CheckResult, HashMap<PathBuf, _>)> = checks_to_run
             .par_iter()
-            .map(|(name, check_fn)| {
-                let result = check_fn();
+            .filter_map(|checker| {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+
+                let started = Instant::now();
+                let tracker = Arc::new(Mutex::new(PathTracker::new()));
+                let skipped_mounts = Arc::new(Mutex::new(Vec::new()));
+                let checker_ctx = ctx
+                    .clone()
+                    .with_tracker(tracker.clone())
+                    .with_skipped_mounts(skipped_mounts.clone());
+                let mut result = match &events {
+                    Some(events) => {
+                        send(ScanEvent::CheckerStarted {
+                            name: checker.name().to_string(),
+                        });
+                        let reporter = ProgressReporter::new(checker.name(), events.clone());
+                        run_isolated(*checker, &checker_ctx.with_progress(reporter))
+                    }
+                    None => run_isolated(*checker, &checker_ctx),
+                };
 
-                // Track paths for caching
-                let mut tracker = PathTracker::new();
+                // A checker cut short by cancellation has partial results
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                let skipped_mounts = skipped_mounts.lock().map(|s| s.clone()).unwrap_or_default();
+                if let Some(note) = mounts::describe_skipped(&skipped_mounts) {
+                    result.status = Some(match result.status.take() {
+                        Some(status) => format!("{}; {}", status, note),
+                        None => note,
+                    });
+                }
+                send(ScanEvent::CheckerFinished {
+                    category: CategoryData::new(checker.name().to_string(), result.clone()),
+                    duration: started.elapsed(),
+                });
+
+                // Track item paths, plus the directories listed to find them
+                let mut tracker = match tracker.lock() {
+                    Ok(mut tracker) => std::mem::take(&mut *tracker),
+                    Err(_) => PathTracker::new(),
+                };
                 for item in &result.items {
                     if let Some(path) = &item.path {
                         tracker.track(path);
                     }
                 }
 
-                (name.to_string(), (result, tracker.into_paths()))
+                Some((checker.name(), (result, tracker.into_paths())))
             })
             .collect();
 
         // Update cache and convert to CategoryData in original order
         let mut final_results = Vec::new();
 
-        // Process results in the original order defined in all_checks vector
-        for (name, _) in &all_checks {
-            if let Some((result, tracked_paths)) = results_map.get(*name) {
-                // Update cache
-                self.scan_cache.update_category(
-                    name.to_string(),
-                    result.clone(),
-                    tracked_paths.clone(),
-                );
+        // Process results in registry order
+        for checker in all_checks {
+            let name = checker.name();
+            if let Some((result, tracked_paths)) = results_map.get(name) {
+                // Update cache; a checker that didn't finish is retried next scan
+                if result.failure.is_none() {
+                    self.scan_cache.update_category(
+                        name.to_string(),
+                        result.clone(),
+                        tracked_paths.clone(),
+                    );
+                }
 
                 // Convert to CategoryData
-                let category_data = CategoryData::new(name.to_string(), result.clone());
-                self.categories
-                    .insert(category_data.name.clone(), category_data.clone());
-                final_results.push(category_data);
+                final_results.push(CategoryData::new(name.to_string(), result.clone()));
             } else if use_cache {
                 // Add cached result if available (maintaining order)
-                if let Some(cached_result) = self.scan_cache.get_valid_category(name) {
-                    let category_data = CategoryData::new(name.to_string(), cached_result);
-                    self.categories
-                        .insert(category_data.name.clone(), category_data.clone());
+                if let Some(category_data) = self.cached_category(name) {
                     final_results.push(category_data);
                 }
             }
         }
 
+        // Items reported by several categories only count once
+        overlap::mark_overlaps(&mut final_results);
+        InUseDetector::new(ctx).mark(&mut final_results);
+        for category_data in &final_results {
+            self.categories
+                .insert(category_data.name.clone(), category_data.clone());
+        }
+
         // Save cache
         let _ = self.scan_cache.save();
+        let _ = self.size_index.save();
+
+        send(ScanEvent::Finished {
+            total_size: self.get_total_reclaimable(),
+            cancelled: cancel.is_cancelled(),
+        });
 
         final_results
     }
 
+    /// Whether a scan should show estimated sizes before the exact ones:
+    /// only when no directory has been sized yet, i.e. on a first scan
+    pub fn needs_estimate_pass(&self) -> bool {
+        self.size_index.is_empty()
+    }
+
+    /// Run every checker with estimated directory sizes, in registry order.
+    /// Much faster than a scan on a cold machine, but nothing is cached and
+    /// every item is marked as an estimate, so none can be cleaned.
+    pub fn scan_estimate(&self, cancel: &CancellationToken) -> Vec<CategoryData> {
+        let ctx = self
+            .scan_context
+            .clone()
+            .with_cancel(cancel.clone())
+            .with_projects(Arc::new(ProjectWalk::new()));
+        let mut categories = match self.scan_context.scan_mode.thread_pool().map(Arc::new) {
+            Some(pool) => {
+                let ctx = ctx.with_thread_pool(pool.clone());
+                pool.install(|| self.run_estimates(registry::all(), &ctx, &|_| {}))
+            }
+            None => self.run_estimates(registry::all(), &ctx, &|_| {}),
+        };
+        overlap::mark_overlaps(&mut categories);
+        categories
+    }
+
+    /// Run `checkers` in estimate mode, passing each category to
+    /// `on_estimate` as it is done. Checkers cut short by cancellation are
+    /// left out.
+    fn run_estimates(
+        &self,
+        checkers: &[&'static dyn Checker],
+        ctx: &ScanContext,
+        on_estimate: &(dyn Fn(CategoryData) + Sync),
+    ) -> Vec<CategoryData> {
+        let ctx = ctx.clone().with_estimate(true);
+        checkers
+            .par_iter()
+            .filter_map(|checker| {
+                if ctx.is_cancelled() {
+                    return None;
+                }
+                let result = run_isolated(*checker, &ctx);
+                if ctx.is_cancelled() {
+                    return None;
+                }
+                let category = CategoryData::estimated(checker.name().to_string(), result);
+                on_estimate(category.clone());
+                Some(category)
+            })
+            .collect()
+    }
+
+    /// A category's valid cached result, stamped with when it was scanned
+    fn cached_category(&self, name: &str) -> Option<CategoryData> {
+        let cached_result = self.scan_cache.get_valid_category(name)?;
+        let mut category_data = CategoryData::new(name.to_string(), cached_result);
+        if let Some(scanned_at) = self.scan_cache.scanned_at(name) {
+            category_data = category_data.with_cached_at(scanned_at);
+        }
+        Some(category_data)
+    }
+
     pub fn get_total_reclaimable(&self) -> u64 {
         self.categories.values().map(|c| c.total_size).sum()
     }
 
-    pub fn execute_cleanup(&self, item: &CleanupItem) -> Result<String, String> {
+    /// Re-check `items` against the disk before they are cleaned: vanished
+    /// paths are dropped and paths changed since the scan re-sized (see
+    /// `preflight`)
+    pub fn preflight(&self, items: &[CleanupItem]) -> Preflight {
+        let ctx = self
+            .scan_context
+            .clone()
+            .with_size_index(self.size_index.clone());
+        Preflight::run(items, &ctx, &self.scan_cache)
+    }
+
+    pub fn execute_cleanup(&self, item: &CleanupItem) -> Result<String, DevSweepError> {
+        self.execute_item(item, &self.deletion_policy())
+    }
+
+    /// The policy deletions are checked against: the scanned home and
+    /// project roots, the current mounts, the quarantine directory and the
+    /// user's denylist
+    pub fn deletion_policy(&self) -> DeletionPolicy {
+        DeletionPolicy::for_context(&self.scan_context)
+            .deny(self.cleanup_history.quarantine_dir())
+            .with_settings(&self.deletion_settings, self.scan_context.home.as_deref())
+    }
+
+    fn execute_item(
+        &self,
+        item: &CleanupItem,
+        policy: &DeletionPolicy,
+    ) -> Result<String, DevSweepError> {
         // Handle different types of cleanup
-        if let Some(cmd) = &item.cleanup_command {
-            self.execute_shell_command(cmd)
+        if let Some(action) = &item.cleanup_action {
+            self.execute_action(action)
         } else if let Some(path) = &item.path {
-            self.delete_path(path)
+            self.delete_path(path, policy)
         } else {
-            Err("No cleanup action available for this item".to_string())
+            Err(DevSweepError::NoCleanupAction)
         }
     }
 
-    /// Execute cleanup with history tracking and quarantine support
+    /// Execute cleanup with history tracking and quarantine support.
+    ///
+    /// Individual failures don't stop the cleanup; they are recorded with
+    /// their error on the returned record (see `CleanupRecord::failures`).
     pub fn execute_cleanup_with_history(
         &mut self,
         items: &[CleanupItem],
         use_quarantine: bool,
-    ) -> Result<String, String> {
+    ) -> CleanupRecord {
+        self.execute_cleanup_with_cancel(items, use_quarantine, &CancellationToken::new())
+    }
+
+    /// Like `execute_cleanup_with_history`, but stops before the next item
+    /// once `cancel` is triggered. The item being processed is always finished,
+    /// and the record lists exactly the items that were processed.
+    pub fn execute_cleanup_with_cancel(
+        &mut self,
+        items: &[CleanupItem],
+        use_quarantine: bool,
+        cancel: &CancellationToken,
+    ) -> CleanupRecord {
+        let plan = CleanupPlan::new(items, use_quarantine);
+        self.run_operations(&plan.operations, false, cancel)
+    }
+
+    /// Apply a saved or reviewed plan.
+    ///
+    /// Each operation is checked against the filesystem right before it runs;
+    /// paths that changed since the plan was made are recorded as
+    /// `DevSweepError::PlanMismatch` failures and left untouched.
+    pub fn apply_plan(&mut self, plan: &CleanupPlan, cancel: &CancellationToken) -> CleanupRecord {
+        self.run_operations(&plan.operations, true, cancel)
+    }
+
+    fn run_operations(
+        &mut self,
+        operations: &[PlannedOperation],
+        verify: bool,
+        cancel: &CancellationToken,
+    ) -> CleanupRecord {
         let record_id = format!(
             "cleanup_{}",
             SystemTime::now()
@@ -188,139 +515,147 @@ impl StorageBackend {
         );
 
         let mut record = CleanupRecord::new(record_id.clone());
-        let mut success_count = 0;
-        let mut error_count = 0;
-        let mut error_messages = Vec::new();
-
-        for item in items {
-            // Check if this item should NOT be quarantined (special cases)
-            let should_not_quarantine = if let Some(path) = &item.path {
-                // Items that should be deleted directly, not quarantined:
-                // 1. Trash itself
-                // 2. Items using cleanup commands instead of path deletion
-                path.ends_with(".Trash")
-            } else {
-                // Items without path (e.g., using cleanup_command) - delete directly
-                false
-            };
+        let policy = self.deletion_policy();
+        let in_use = InUseDetector::new(&self.scan_context);
 
-            let result = if should_not_quarantine || item.cleanup_command.is_some() {
-                // Delete directly, not quarantined (can't undo)
-                match self.execute_cleanup(item) {
-                    Ok(msg) => {
-                        success_count += 1;
-                        record.add_item(CleanupItemRecord::success(item, None));
-                        Ok(msg)
-                    }
-                    Err(e) => {
-                        error_count += 1;
-                        let error_msg = format!("{}: {}", item.item_type, e);
-                        error_messages.push(error_msg.clone());
-                        record.add_item(CleanupItemRecord::error(item, e.clone()));
-                        Err(e)
-                    }
-                }
-            } else if use_quarantine && item.path.is_some() {
-                // Move to quarantine (can undo later)
-                match self.cleanup_history.quarantine_item(item) {
-                    Ok(quarantine_path) => {
-                        success_count += 1;
-                        record.add_item(CleanupItemRecord::success(item, Some(quarantine_path)));
-                        Ok(format!("Quarantined: {}", item.item_type))
-                    }
-                    Err(e) => {
-                        error_count += 1;
-                        let error_msg = format!("{}: {}", item.item_type, e);
-                        error_messages.push(error_msg.clone());
-                        record.add_item(CleanupItemRecord::error(item, e.clone()));
-                        Err(e)
-                    }
-                }
+        for (index, op) in operations.iter().enumerate() {
+            if cancel.is_cancelled() {
+                record.mark_cancelled(operations.len() - index);
+                eprintln!(
+                    "⏹ Cleanup cancelled, {} items skipped",
+                    operations.len() - index
+                );
+                break;
+            }
+
+            let item = &op.item;
+            let verified = if item.is_estimate {
+                Err(DevSweepError::SizeEstimated {
+                    item_type: item.item_type.clone(),
+                })
+            } else if verify {
+                op.verify()
             } else {
-                // Delete permanently (no undo possible)
-                match self.execute_cleanup(item) {
-                    Ok(msg) => {
-                        success_count += 1;
-                        record.add_item(CleanupItemRecord::success(item, None));
-                        Ok(msg)
-                    }
-                    Err(e) => {
-                        error_count += 1;
-                        let error_msg = format!("{}: {}", item.item_type, e);
-                        error_messages.push(error_msg.clone());
-                        record.add_item(CleanupItemRecord::error(item, e.clone()));
-                        Err(e)
-                    }
+                Ok(())
+            }
+            // Whatever the scan found, don't pull files from under a running process
+            .and_then(|()| match in_use.check_item(item) {
+                Some(by) => Err(DevSweepError::InUse {
+                    path: item.path.clone().unwrap_or_default(),
+                    by: Some(by),
+                }),
+                None => Ok(()),
+            });
+            let result = match (verified, op.mode) {
+                (Err(e), _) => Err(e),
+                // Move to quarantine (can undo later)
+                (Ok(()), CleanupMode::Quarantine) => self
+                    .cleanup_history
+                    .quarantine_item(item, &policy)
+                    .map(|quarantine_path| {
+                        (
+                            format!("Quarantined: {}", item.item_type),
+                            Some(quarantine_path),
+                        )
+                    }),
+                // Delete directly or run the command, not quarantined (can't undo)
+                (Ok(()), CleanupMode::Permanent | CleanupMode::Command) => {
+                    self.execute_item(item, &policy).map(|msg| (msg, None))
                 }
             };
 
             match result {
-                Ok(msg) => println!("✓ {}", msg),
-                Err(e) => eprintln!("✗ {}", e),
+                Ok((msg, quarantine_path)) => {
+                    record.add_item(CleanupItemRecord::success(item, quarantine_path));
+                    eprintln!("✓ {}", msg);
+                }
+                Err(e) => {
+                    eprintln!("✗ {}", e);
+                    record.add_item(CleanupItemRecord::error(item, e));
+                }
             }
         }
 
-        // Add record to history
-        self.cleanup_history.add_record(record);
-        let _ = self.cleanup_history.save();
+        // Print detailed errors to console
+        if record.error_count > 0 {
+            eprintln!("\n❌ Cleanup Errors:");
+            for (item, error) in record.failures() {
+                eprintln!("  • {}: {}", item.item_type, error);
+            }
+        }
+
+        // Add record to history (nothing to record if cancelled before the first item)
+        if !record.items.is_empty() {
+            self.cleanup_history.add_record(record.clone());
+            let _ = self.cleanup_history.save();
+        }
 
         // Invalidate cache for affected categories
-        self.invalidate_cache_for_items(items);
+        let items: Vec<CleanupItem> = operations.iter().map(|op| op.item.clone()).collect();
+        self.invalidate_cache_for_items(&items);
 
-        if error_count == 0 {
-            Ok(format!("Successfully cleaned {} items", success_count))
-        } else {
-            // Print detailed errors to console
-            if !error_messages.is_empty() {
-                eprintln!("\n❌ Cleanup Errors:");
-                for error_msg in &error_messages {
-                    eprintln!("  • {}", error_msg);
-                }
-            }
-            Err(format!(
-                "Cleaned {} items with {} errors",
-                success_count, error_count
-            ))
-        }
+        record
     }
 
     /// Undo the last cleanup operation
-    pub fn undo_cleanup(&mut self, record_id: &str) -> Result<String, String> {
+    pub fn undo_cleanup(&mut self, record_id: &str) -> Result<UndoResult, DevSweepError> {
+        let restored: Vec<PathBuf> = self
+            .cleanup_history
+            .get_record(record_id)
+            .map(|record| {
+                record
+                    .items
+                    .iter()
+                    .filter(|item| item.can_restore())
+                    .map(|item| item.original_path.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let result = self.cleanup_history.undo(record_id)?;
 
-        // Invalidate cache since files were restored
-        self.scan_cache.clear();
-        let _ = self.scan_cache.save();
+        // Only the categories owning restored files need rescanning
+        let invalidated = self.scan_cache.invalidate_paths(&restored);
+        self.finish_invalidation(&invalidated);
 
-        if result.error_count == 0 {
-            Ok(format!(
-                "Successfully restored {} items",
-                result.success_count
-            ))
-        } else {
-            Err(format!(
-                "Restored {} items with {} errors",
-                result.success_count, result.error_count
-            ))
-        }
+        Ok(result)
     }
 
-    /// Invalidate cache for items that were cleaned
-    fn invalidate_cache_for_items(&mut self, _items: &[CleanupItem]) {
-        // Simple approach: clear entire cache
-        // More sophisticated: only invalidate affected categories
-        self.scan_cache.clear();
+    /// Invalidate the cached categories the cleaned items belong to
+    fn invalidate_cache_for_items(&mut self, items: &[CleanupItem]) {
+        let invalidated = self.scan_cache.invalidate_items(items);
+        self.finish_invalidation(&invalidated);
+    }
+
+    fn finish_invalidation(&mut self, invalidated: &[String]) {
+        if invalidated.is_empty() {
+            return;
+        }
+        eprintln!(
+            "♻ Invalidated cached results for: {}",
+            invalidated.join(", ")
+        );
         let _ = self.scan_cache.save();
     }
 
-    /// Get all category TTL settings
+    /// Get all category TTL settings, in registry order
     pub fn get_all_cache_ttls(&self) -> Vec<(String, u64)> {
         let config = self.scan_cache.get_config();
-        config
+        let mut ttls: Vec<(String, u64)> = config
             .category_ttls
             .iter()
             .map(|(k, v)| (k.clone(), *v))
-            .collect()
+            .collect();
+
+        // Registered categories first in display order, anything unknown after them by name
+        let position = |name: &str| {
+            registry::all()
+                .iter()
+                .position(|c| c.name() == name)
+                .unwrap_or(usize::MAX)
+        };
+        ttls.sort_by(|(a, _), (b, _)| position(a).cmp(&position(b)).then_with(|| a.cmp(b)));
+        ttls
     }
 
     /// Set TTL for a specific category (in seconds)
@@ -337,63 +672,78 @@ impl StorageBackend {
         self.scan_cache.set_config(default_config);
     }
 
-    fn execute_shell_command(&self, command: &str) -> Result<String, String> {
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .output()
-            .map_err(|e| format!("Failed to execute command: {}", e))?;
+    /// Run `action`'s program directly (through `sh -c` only for shell actions)
+    fn execute_action(&self, action: &CleanupAction) -> Result<String, DevSweepError> {
+        let output = self
+            .scan_context
+            .runner
+            .run(&action.invocation(), &CancellationToken::new())
+            .map_err(|e| DevSweepError::CommandFailed {
+                command: action.to_string(),
+                exit_code: None,
+                stderr: e.to_string(),
+            })?;
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        if action.is_success(output.exit_code) {
+            Ok(output.stdout)
         } else {
-            Err(String::from_utf8_lossy(&output.stderr).to_string())
+            Err(DevSweepError::CommandFailed {
+                command: action.to_string(),
+                exit_code: output.exit_code,
+                stderr: output.stderr,
+            })
         }
     }
 
-    fn delete_path(&self, path: &PathBuf) -> Result<String, String> {
-        if !path.exists() {
-            return Err(format!("Path does not exist: {}", path.display()));
-        }
+    fn delete_path(&self, path: &Path, policy: &DeletionPolicy) -> Result<String, DevSweepError> {
+        policy.check(path)?;
 
         // Special handling for Trash - delete contents, not the directory itself
         if path.ends_with(".Trash") {
-            return self.empty_trash();
+            return self.empty_trash(policy);
         }
 
-        if path.is_dir() {
-            fs::remove_dir_all(path).map_err(|e| format!("Failed to delete directory: {}", e))?;
+        // A symlink is removed itself, never what it points to
+        let metadata = fs::symlink_metadata(path).map_err(|e| DevSweepError::from_io(&e, path))?;
+        if metadata.is_dir() {
+            fs::remove_dir_all(path).map_err(|e| DevSweepError::from_io(&e, path))?;
             Ok(format!("Deleted directory: {}", path.display()))
         } else {
-            fs::remove_file(path).map_err(|e| format!("Failed to delete file: {}", e))?;
+            fs::remove_file(path).map_err(|e| DevSweepError::from_io(&e, path))?;
             Ok(format!("Deleted file: {}", path.display()))
         }
     }
 
-    fn empty_trash(&self) -> Result<String, String> {
+    fn empty_trash(&self, policy: &DeletionPolicy) -> Result<String, DevSweepError> {
         // Use shell command to empty trash contents (not delete the .Trash folder itself)
         // This avoids permission issues with the .Trash directory
-        let home =
-            dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+        let home = self
+            .scan_context
+            .home
+            .clone()
+            .ok_or(DevSweepError::NoHomeDirectory)?;
 
         let trash_path = home.join(".Trash");
 
         if !trash_path.exists() {
             return Ok("Trash is already empty".to_string());
         }
+        policy.check(&trash_path)?;
 
         // Delete all contents of .Trash/* but not .Trash itself
-        let entries = fs::read_dir(&trash_path)
-            .map_err(|e| format!("Failed to read Trash directory: {}", e))?;
+        let entries =
+            fs::read_dir(&trash_path).map_err(|e| DevSweepError::from_io(&e, &trash_path))?;
 
         let mut deleted_count = 0;
         let mut errors = Vec::new();
+        let mut first_error = None;
 
         for entry in entries {
             match entry {
                 Ok(entry) => {
                     let path = entry.path();
-                    let result = if path.is_dir() {
+                    let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                    let result = if is_dir {
                         fs::remove_dir_all(&path)
                     } else {
                         fs::remove_file(&path)
@@ -401,7 +751,10 @@ impl StorageBackend {
 
                     match result {
                         Ok(_) => deleted_count += 1,
-                        Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                        Err(e) => {
+                            errors.push(format!("{}: {}", path.display(), e));
+                            first_error.get_or_insert_with(|| DevSweepError::from_io(&e, &path));
+                        }
                     }
                 }
                 Err(e) => errors.push(format!("Failed to read entry: {}", e)),
@@ -409,12 +762,23 @@ impl StorageBackend {
         }
 
         if !errors.is_empty() {
-            Err(format!(
+            // Permission problems are the common case; keep their kind so the
+            // remedy still applies, and list every failure in the message
+            let message = format!(
                 "Emptied {} items from Trash, but {} errors occurred:\n{}",
                 deleted_count,
                 errors.len(),
                 errors.join("\n")
-            ))
+            );
+            Err(match first_error {
+                Some(DevSweepError::PermissionDenied { .. }) => {
+                    DevSweepError::PermissionDenied { path: trash_path }
+                }
+                _ => DevSweepError::Io {
+                    path: trash_path,
+                    message,
+                },
+            })
         } else {
             Ok(format!(
                 "Successfully emptied Trash ({} items deleted)",
@@ -448,7 +812,7 @@ impl StorageBackend {
     }
 
     /// Clear all quarantine history and files
-    pub fn clear_all_quarantine(&mut self) -> Result<String, String> {
+    pub fn clear_all_quarantine(&mut self) -> Result<String, DevSweepError> {
         let stats = self.cleanup_history.stats();
 
         self.cleanup_history.clear_all()?;
@@ -464,7 +828,7 @@ impl StorageBackend {
         &mut self,
         record_id: &str,
         item_index: usize,
-    ) -> Result<String, String> {
+    ) -> Result<String, DevSweepError> {
         self.cleanup_history
             .delete_quarantine_item(record_id, item_index)
     }
@@ -480,3 +844,210 @@ impl Default for StorageBackend {
         Self::new()
     }
 }
+
+/// How long past its deadline a checker gets to notice the cancellation and
+/// return before it is abandoned
+const CHECKER_GRACE: Duration = Duration::from_secs(2);
+
+/// How often a thread waiting on a checker looks for other work
+const CHECKER_POLL: Duration = Duration::from_millis(5);
+
+/// Run `checker` on its own thread with a deadline `ctx.checker_timeout` from
+/// now. A checker that overruns it or panics yields a failed result instead
+/// of hanging or taking the rest of the scan down with it. Checkers stop
+/// walking at the deadline; one stuck in a system call (such as a `stat` on a
+/// hung network mount) is abandoned on its thread.
+fn run_isolated(checker: &'static dyn Checker, ctx: &ScanContext) -> CheckResult {
+    let ctx = ctx
+        .clone()
+        .with_cancel(ctx.cancel.with_timeout(ctx.checker_timeout));
+    let timed_out = || {
+        CheckResult::failed(
+            checker.name(),
+            CheckFailure::TimedOut {
+                after_secs: ctx.checker_timeout.as_secs(),
+            },
+        )
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let worker_ctx = ctx.clone();
+    let spawned = thread::Builder::new()
+        .name(format!("checker-{}", checker.id()))
+        .spawn(move || {
+            let check = || run_caught(checker, &worker_ctx);
+            // Keep a background scan's checkers on its low-priority threads
+            let outcome = match &worker_ctx.thread_pool {
+                Some(pool) => pool.install(check),
+                None => check(),
+            };
+            let _ = tx.send(outcome);
+        });
+    let outcome = match spawned {
+        Ok(_) => {
+            let deadline = Instant::now() + ctx.checker_timeout + CHECKER_GRACE;
+            loop {
+                if let Ok(outcome) = rx.try_recv() {
+                    break outcome;
+                }
+                if Instant::now() >= deadline {
+                    return timed_out();
+                }
+                // A pool thread waiting here runs queued work, such as the
+                // checker's own directory walks, rather than starving it
+                if rayon::yield_now() != Some(rayon::Yield::Executed) {
+                    if let Ok(outcome) = rx.recv_timeout(CHECKER_POLL) {
+                        break outcome;
+                    }
+                }
+            }
+        }
+        // Out of threads: run it here, with only the deadline to stop it
+        Err(_) => run_caught(checker, &ctx),
+    };
+    match outcome {
+        (Ok(_), true) => timed_out(),
+        (Ok(result), false) => result,
+        (Err(payload), _) => CheckResult::failed(
+            checker.name(),
+            CheckFailure::Crashed {
+                message: panic_message(payload.as_ref()),
+            },
+        ),
+    }
+}
+
+/// Run `checker`, catching a panic, and note whether it overran its deadline.
+/// The deadline is checked on return: the result may be collected later.
+fn run_caught(checker: &dyn Checker, ctx: &ScanContext) -> (thread::Result<CheckResult>, bool) {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| checker.check(ctx)));
+    (outcome, ctx.cancel.timed_out())
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SuperCategoryType;
+
+    struct PanickingChecker;
+
+    impl Checker for PanickingChecker {
+        fn id(&self) -> &'static str {
+            "panicking"
+        }
+
+        fn name(&self) -> &'static str {
+            "Panicking"
+        }
+
+        fn super_category(&self) -> SuperCategoryType {
+            SuperCategoryType::SystemAndBrowsers
+        }
+
+        fn default_ttl(&self) -> u64 {
+            0
+        }
+
+        fn check(&self, _ctx: &ScanContext) -> CheckResult {
+            panic!("unexpected output from tool")
+        }
+    }
+
+    struct BlockedChecker;
+
+    impl Checker for BlockedChecker {
+        fn id(&self) -> &'static str {
+            "blocked"
+        }
+
+        fn name(&self) -> &'static str {
+            "Blocked"
+        }
+
+        fn super_category(&self) -> SuperCategoryType {
+            SuperCategoryType::SystemAndBrowsers
+        }
+
+        fn default_ttl(&self) -> u64 {
+            0
+        }
+
+        fn check(&self, _ctx: &ScanContext) -> CheckResult {
+            // Like a stat on a hung mount: never looks at the cancellation token
+            thread::sleep(Duration::from_secs(600));
+            CheckResult::new(self.name())
+        }
+    }
+
+    /// Reports the name of the thread it ran on as its status
+    struct ThreadNameChecker;
+
+    impl Checker for ThreadNameChecker {
+        fn id(&self) -> &'static str {
+            "thread_name"
+        }
+
+        fn name(&self) -> &'static str {
+            "Thread Name"
+        }
+
+        fn super_category(&self) -> SuperCategoryType {
+            SuperCategoryType::SystemAndBrowsers
+        }
+
+        fn default_ttl(&self) -> u64 {
+            0
+        }
+
+        fn check(&self, _ctx: &ScanContext) -> CheckResult {
+            let mut result = CheckResult::new(self.name());
+            result.status = thread::current().name().map(String::from);
+            result
+        }
+    }
+
+    #[test]
+    fn test_checker_runs_in_the_scans_pool() {
+        let pool = Arc::new(ScanMode::Background.thread_pool().unwrap());
+        let ctx = ScanContext::for_home("/nonexistent").with_thread_pool(pool);
+        let result = run_isolated(&ThreadNameChecker, &ctx);
+        assert!(result
+            .status
+            .is_some_and(|name| name.starts_with("devsweep-background-")));
+    }
+
+    #[test]
+    fn test_panicking_checker_yields_a_failed_result() {
+        let result = run_isolated(&PanickingChecker, &ScanContext::for_home("/nonexistent"));
+        assert_eq!(
+            result.failure,
+            Some(CheckFailure::Crashed {
+                message: "unexpected output from tool".to_string()
+            })
+        );
+        assert_eq!(
+            result.status.as_deref(),
+            Some("Checker crashed: unexpected output from tool")
+        );
+    }
+
+    #[test]
+    fn test_blocked_checker_is_abandoned_at_its_deadline() {
+        let started = Instant::now();
+        let ctx = ScanContext::for_home("/nonexistent").with_checker_timeout(Duration::ZERO);
+        let result = run_isolated(&BlockedChecker, &ctx);
+        assert_eq!(
+            result.failure,
+            Some(CheckFailure::TimedOut { after_secs: 0 })
+        );
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use crate::backend::StorageBackend;
+use crate::checkers::registry;
 use std::collections::HashMap;
 
 /// Cache settings utility for managing TTL configuration
@@ -57,7 +58,7 @@ impl CacheSettings {
         println!("✓ Cache settings reset to defaults");
     }
 
-    /// Get recommended TTL presets
+    /// Get recommended TTL presets, keyed by checker id
     pub fn get_presets() -> HashMap<&'static str, Vec<(&'static str, u64)>> {
         let mut presets = HashMap::new();
 
@@ -65,17 +66,17 @@ impl CacheSettings {
         presets.insert(
             "conservative",
             vec![
-                ("Trash", 0),
-                ("General Caches", 30),
-                ("Docker", 60),
-                ("Homebrew", 600),
-                ("Node.js/npm/yarn", 300),
-                ("Python", 300),
-                ("Rust/Cargo", 120),
-                ("Xcode", 120),
-                ("Java (Gradle/Maven)", 300),
-                ("Go", 300),
-                ("node_modules in Projects", 120),
+                ("trash", 0),
+                ("general", 30),
+                ("docker", 60),
+                ("homebrew", 600),
+                ("nodejs", 300),
+                ("python", 300),
+                ("rust", 120),
+                ("xcode", 120),
+                ("java", 300),
+                ("go", 300),
+                ("node_modules", 120),
             ],
         );
 
@@ -83,17 +84,17 @@ impl CacheSettings {
         presets.insert(
             "balanced",
             vec![
-                ("Trash", 0),
-                ("General Caches", 30),
-                ("Docker", 300),
-                ("Homebrew", 3600),
-                ("Node.js/npm/yarn", 600),
-                ("Python", 600),
-                ("Rust/Cargo", 300),
-                ("Xcode", 300),
-                ("Java (Gradle/Maven)", 600),
-                ("Go", 600),
-                ("node_modules in Projects", 300),
+                ("trash", 0),
+                ("general", 30),
+                ("docker", 300),
+                ("homebrew", 3600),
+                ("nodejs", 600),
+                ("python", 600),
+                ("rust", 300),
+                ("xcode", 300),
+                ("java", 600),
+                ("go", 600),
+                ("node_modules", 300),
             ],
         );
 
@@ -101,17 +102,17 @@ impl CacheSettings {
         presets.insert(
             "aggressive",
             vec![
-                ("Trash", 0),
-                ("General Caches", 60),
-                ("Docker", 600),
-                ("Homebrew", 7200),
-                ("Node.js/npm/yarn", 1800),
-                ("Python", 1800),
-                ("Rust/Cargo", 600),
-                ("Xcode", 600),
-                ("Java (Gradle/Maven)", 1800),
-                ("Go", 1800),
-                ("node_modules in Projects", 600),
+                ("trash", 0),
+                ("general", 60),
+                ("docker", 600),
+                ("homebrew", 7200),
+                ("nodejs", 1800),
+                ("python", 1800),
+                ("rust", 600),
+                ("xcode", 600),
+                ("java", 1800),
+                ("go", 1800),
+                ("node_modules", 600),
             ],
         );
 
@@ -124,9 +125,10 @@ impl CacheSettings {
 
         if let Some(preset) = presets.get(preset_name) {
             println!("Applying '{}' preset...", preset_name);
-            for (category, _ttl) in preset {
+            for (id, _ttl) in preset {
                 // Note: set_cache_ttl method is not currently available in StorageBackend
-                println!("  Setting TTL for {}", category);
+                let name = registry::find(id).map_or(*id, |checker| checker.name());
+                println!("  Setting TTL for {}", name);
             }
             println!("✓ Applied '{}' cache preset", preset_name);
         } else {
@@ -238,7 +240,7 @@ mod tests {
         for (_, preset) in presets {
             let trash_ttl = preset
                 .iter()
-                .find(|(cat, _)| *cat == "Trash")
+                .find(|(id, _)| *id == "trash")
                 .map(|(_, ttl)| *ttl);
             assert_eq!(trash_ttl, Some(0), "Trash must always have TTL of 0");
         }
@@ -0,0 +1,89 @@
+//! Cooperative cancellation for scans and cleanups
+//!
+//! The UI keeps a clone of the token it hands to the backend and calls
+//! `cancel()` when the user presses Cancel. Long-running loops check
+//! `is_cancelled()` between units of work and stop early; nothing is
+//! interrupted halfway (a directory being deleted is deleted completely).
+//!
+//! A token can also carry a deadline (see `with_timeout`); the backend gives
+//! each checker one so a checker that runs too long stops like a cancelled one.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shared flag telling a running operation to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every holder of this token (and its clones) to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.timed_out()
+    }
+
+    /// A token that is cancelled along with this one, and also once `timeout`
+    /// has passed. Cancelling it cancels this one too.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let deadline = Instant::now() + timeout;
+        Self {
+            cancelled: self.cancelled.clone(),
+            deadline: Some(self.deadline.map_or(deadline, |d| d.min(deadline))),
+        }
+    }
+
+    /// This token without its deadline, for work shared with other checkers
+    pub fn without_timeout(&self) -> Self {
+        Self {
+            cancelled: self.cancelled.clone(),
+            deadline: None,
+        }
+    }
+
+    /// Whether the token's deadline has passed
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+
+    #[test]
+    fn test_timeout_only_affects_the_derived_token() {
+        let token = CancellationToken::new();
+        let timed = token.with_timeout(Duration::ZERO);
+        assert!(timed.is_cancelled());
+        assert!(timed.timed_out());
+        assert!(!token.is_cancelled());
+        assert!(!timed.without_timeout().is_cancelled());
+
+        let timed = token.with_timeout(Duration::from_secs(60));
+        assert!(!timed.is_cancelled());
+        token.cancel();
+        assert!(timed.is_cancelled());
+        assert!(!timed.timed_out());
+    }
+}
@@ -1,10 +1,36 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 
-pub fn check_browser_caches() -> CheckResult {
-    let mut result = CheckResult::new("Browser Caches");
+/// Registry entry for the browser cache checker
+pub struct BrowserChecker;
 
-    let home = match home_dir() {
+impl Checker for BrowserChecker {
+    fn id(&self) -> &'static str {
+        "browser"
+    }
+
+    fn name(&self) -> &'static str {
+        "Browser Caches"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::SystemAndBrowsers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_browser_caches(ctx)
+    }
+}
+
+pub fn check_browser_caches(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(BrowserChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
@@ -24,7 +50,7 @@ pub fn check_browser_caches() -> CheckResult {
 
     for (name, path) in safari_caches {
         if path.exists() {
-            let size = get_dir_size(&path);
+            let size = ctx.dir_size(&path);
             if size > 10 * 1024 * 1024 {
                 // > 10MB
                 let item = CleanupItem::new(&format!("Safari: {}", name), size, &format_size(size))
@@ -39,68 +65,68 @@ pub fn check_browser_caches() -> CheckResult {
     // Google Chrome
     let chrome_base = home.join("Library/Application Support/Google/Chrome");
     if chrome_base.exists() {
-        check_chromium_browser(&mut result, &chrome_base, "Chrome");
+        check_chromium_browser(&mut result, &chrome_base, "Chrome", ctx);
     }
 
     // Google Chrome Canary
     let chrome_canary_base = home.join("Library/Application Support/Google/Chrome Canary");
     if chrome_canary_base.exists() {
-        check_chromium_browser(&mut result, &chrome_canary_base, "Chrome Canary");
+        check_chromium_browser(&mut result, &chrome_canary_base, "Chrome Canary", ctx);
     }
 
     // Brave Browser
     let brave_base = home.join("Library/Application Support/BraveSoftware/Brave-Browser");
     if brave_base.exists() {
-        check_chromium_browser(&mut result, &brave_base, "Brave");
+        check_chromium_browser(&mut result, &brave_base, "Brave", ctx);
     }
 
     // Microsoft Edge
     let edge_base = home.join("Library/Application Support/Microsoft Edge");
     if edge_base.exists() {
-        check_chromium_browser(&mut result, &edge_base, "Edge");
+        check_chromium_browser(&mut result, &edge_base, "Edge", ctx);
     }
 
     // Vivaldi
     let vivaldi_base = home.join("Library/Application Support/Vivaldi");
     if vivaldi_base.exists() {
-        check_chromium_browser(&mut result, &vivaldi_base, "Vivaldi");
+        check_chromium_browser(&mut result, &vivaldi_base, "Vivaldi", ctx);
     }
 
     // Opera
     let opera_base = home.join("Library/Application Support/com.operasoftware.Opera");
     if opera_base.exists() {
-        check_chromium_browser(&mut result, &opera_base, "Opera");
+        check_chromium_browser(&mut result, &opera_base, "Opera", ctx);
     }
 
     // Arc Browser
     let arc_base = home.join("Library/Application Support/Arc");
     if arc_base.exists() {
-        check_chromium_browser(&mut result, &arc_base, "Arc");
+        check_chromium_browser(&mut result, &arc_base, "Arc", ctx);
     }
 
     // Firefox
     let firefox_base = home.join("Library/Application Support/Firefox/Profiles");
     if firefox_base.exists() {
-        check_firefox_browser(&mut result, &firefox_base, "Firefox");
+        check_firefox_browser(&mut result, &firefox_base, "Firefox", ctx);
     }
 
     // Firefox Developer Edition
     let firefox_dev_base =
         home.join("Library/Application Support/Firefox Developer Edition/Profiles");
     if firefox_dev_base.exists() {
-        check_firefox_browser(&mut result, &firefox_dev_base, "Firefox Dev");
+        check_firefox_browser(&mut result, &firefox_dev_base, "Firefox Dev", ctx);
     }
 
     // Firefox Nightly
     let firefox_nightly_base = home.join("Library/Application Support/Firefox Nightly/Profiles");
     if firefox_nightly_base.exists() {
-        check_firefox_browser(&mut result, &firefox_nightly_base, "Firefox Nightly");
+        check_firefox_browser(&mut result, &firefox_nightly_base, "Firefox Nightly", ctx);
     }
 
     // Chromium cache in Library/Caches
     let chrome_cache = home.join("Library/Caches/Google/Chrome");
     if chrome_cache.exists() {
-        let size = get_dir_size(&chrome_cache);
+        let size = ctx.dir_size(&chrome_cache);
         if size > 10 * 1024 * 1024 {
             let item = CleanupItem::new("Chrome: System Cache", size, &format_size(size))
                 .with_path(chrome_cache)
@@ -111,7 +137,7 @@ pub fn check_browser_caches() -> CheckResult {
 
     let brave_cache = home.join("Library/Caches/BraveSoftware/Brave-Browser");
     if brave_cache.exists() {
-        let size = get_dir_size(&brave_cache);
+        let size = ctx.dir_size(&brave_cache);
         if size > 10 * 1024 * 1024 {
             let item = CleanupItem::new("Brave: System Cache", size, &format_size(size))
                 .with_path(brave_cache)
@@ -122,7 +148,7 @@ pub fn check_browser_caches() -> CheckResult {
 
     let edge_cache = home.join("Library/Caches/Microsoft Edge");
     if edge_cache.exists() {
-        let size = get_dir_size(&edge_cache);
+        let size = ctx.dir_size(&edge_cache);
         if size > 10 * 1024 * 1024 {
             let item = CleanupItem::new("Edge: System Cache", size, &format_size(size))
                 .with_path(edge_cache)
@@ -133,7 +159,7 @@ pub fn check_browser_caches() -> CheckResult {
 
     let firefox_cache = home.join("Library/Caches/Firefox");
     if firefox_cache.exists() {
-        let size = get_dir_size(&firefox_cache);
+        let size = ctx.dir_size(&firefox_cache);
         if size > 10 * 1024 * 1024 {
             let item = CleanupItem::new("Firefox: System Cache", size, &format_size(size))
                 .with_path(firefox_cache)
@@ -149,6 +175,7 @@ fn check_chromium_browser(
     result: &mut CheckResult,
     base_path: &std::path::Path,
     browser_name: &str,
+    ctx: &ScanContext,
 ) {
     use std::fs;
 
@@ -171,7 +198,7 @@ fn check_chromium_browser(
         // Cache directory
         let cache_path = profile_path.join("Cache");
         if cache_path.exists() {
-            let size = get_dir_size(&cache_path);
+            let size = ctx.dir_size(&cache_path);
             if size > 10 * 1024 * 1024 {
                 let item = CleanupItem::new(
                     &format!("{}: {} Cache", browser_name, profile_name),
@@ -187,7 +214,7 @@ fn check_chromium_browser(
         // Code Cache (JavaScript compiled cache)
         let code_cache_path = profile_path.join("Code Cache");
         if code_cache_path.exists() {
-            let size = get_dir_size(&code_cache_path);
+            let size = ctx.dir_size(&code_cache_path);
             if size > 10 * 1024 * 1024 {
                 let item = CleanupItem::new(
                     &format!("{}: {} Code Cache", browser_name, profile_name),
@@ -203,7 +230,7 @@ fn check_chromium_browser(
         // GPUCache
         let gpu_cache_path = profile_path.join("GPUCache");
         if gpu_cache_path.exists() {
-            let size = get_dir_size(&gpu_cache_path);
+            let size = ctx.dir_size(&gpu_cache_path);
             if size > 5 * 1024 * 1024 {
                 let item = CleanupItem::new(
                     &format!("{}: {} GPU Cache", browser_name, profile_name),
@@ -219,7 +246,7 @@ fn check_chromium_browser(
         // Service Worker cache
         let sw_cache_path = profile_path.join("Service Worker/CacheStorage");
         if sw_cache_path.exists() {
-            let size = get_dir_size(&sw_cache_path);
+            let size = ctx.dir_size(&sw_cache_path);
             if size > 10 * 1024 * 1024 {
                 let item = CleanupItem::new(
                     &format!("{}: {} Service Worker Cache", browser_name, profile_name),
@@ -243,7 +270,7 @@ fn check_chromium_browser(
 
                 let cache_path = profile_path.join("Cache");
                 if cache_path.exists() {
-                    let size = get_dir_size(&cache_path);
+                    let size = ctx.dir_size(&cache_path);
                     if size > 10 * 1024 * 1024 {
                         let item = CleanupItem::new(
                             &format!("{}: {} Cache", browser_name, name),
@@ -264,6 +291,7 @@ fn check_firefox_browser(
     result: &mut CheckResult,
     profiles_path: &std::path::Path,
     browser_name: &str,
+    ctx: &ScanContext,
 ) {
     use std::fs;
 
@@ -285,7 +313,7 @@ fn check_firefox_browser(
             // cache2 directory (main cache)
             let cache_path = profile_path.join("cache2");
             if cache_path.exists() {
-                let size = get_dir_size(&cache_path);
+                let size = ctx.dir_size(&cache_path);
                 if size > 10 * 1024 * 1024 {
                     let item = CleanupItem::new(
                         &format!("{}: {} cache", browser_name, display_name),
@@ -301,7 +329,7 @@ fn check_firefox_browser(
             // OfflineCache
             let offline_cache_path = profile_path.join("OfflineCache");
             if offline_cache_path.exists() {
-                let size = get_dir_size(&offline_cache_path);
+                let size = ctx.dir_size(&offline_cache_path);
                 if size > 5 * 1024 * 1024 {
                     let item = CleanupItem::new(
                         &format!("{}: {} offline cache", browser_name, display_name),
@@ -317,7 +345,7 @@ fn check_firefox_browser(
             // storage/default (IndexedDB, localStorage for websites)
             let storage_path = profile_path.join("storage/default");
             if storage_path.exists() {
-                let size = get_dir_size(&storage_path);
+                let size = ctx.dir_size(&storage_path);
                 if size > 50 * 1024 * 1024 {
                     let item = CleanupItem::new(
                         &format!("{}: {} site storage", browser_name, display_name),
@@ -333,7 +361,7 @@ fn check_firefox_browser(
             // startupCache
             let startup_cache_path = profile_path.join("startupCache");
             if startup_cache_path.exists() {
-                let size = get_dir_size(&startup_cache_path);
+                let size = ctx.dir_size(&startup_cache_path);
                 if size > 5 * 1024 * 1024 {
                     let item = CleanupItem::new(
                         &format!("{}: {} startup cache", browser_name, display_name),
@@ -0,0 +1,430 @@
+//! Scan context shared by all checkers
+//!
+//! Checkers never look up the home directory or environment themselves; they
+//! receive a `ScanContext` instead. The default context describes the current
+//! user, while `ScanContext::for_home` builds an isolated one around an
+//! arbitrary directory (another user's home, a mounted backup, a test fixture).
+//! Clones of a context share its project walk, so projects are discovered
+//! once however many checkers ask for them. Sizes are never taken of paths on
+//! network or FUSE mounts, and walks don't cross into other filesystems.
+//! External commands go through `runner` and are killed once they run longer
+//! than `command_timeout`.
+
+use super::projects::{ProjectIndex, ProjectWalk};
+use crate::cancel::CancellationToken;
+use crate::command_runner::{CommandOutput, CommandRunner, Invocation, ProcessRunner};
+use crate::disk_usage::{disk_usage_within, estimate_usage, DiskUsage, ESTIMATE_DIR_BUDGET};
+use crate::mounts::{Mount, MountTable};
+use crate::scan_cache::PathTracker;
+use crate::scan_mode::ScanMode;
+use crate::scan_progress::ProgressReporter;
+use crate::size_index::SizeIndex;
+use crate::utils::DEFAULT_COMMAND_TIMEOUT;
+use crate::walk::IoBudget;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Directories under the home directory that are searched for projects
+pub const DEFAULT_PROJECT_DIRS: &[&str] = &[
+    "Projects",
+    "Developer",
+    "Code",
+    "Documents",
+    "Desktop",
+    "src",
+    "repos",
+    "workspace",
+    "git",
+];
+
+/// Default maximum depth (below a search root) at which projects are discovered
+pub const DEFAULT_MAX_DEPTH: usize = 5;
+
+/// Default time a checker may run before it is stopped and reported as timed out
+pub const DEFAULT_CHECKER_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Everything a checker needs to know about the machine it is scanning
+#[derive(Debug, Clone)]
+pub struct ScanContext {
+    /// Home directory to scan; checkers that need it report nothing when unset
+    pub home: Option<PathBuf>,
+    /// Filesystem root that system-wide paths such as `/usr/local` are resolved against
+    pub root: PathBuf,
+    /// Directories searched for projects (target/, node_modules, git repos)
+    pub project_roots: Vec<PathBuf>,
+    /// Maximum depth below a project root at which projects are discovered
+    pub max_depth: usize,
+    /// Environment passed to external commands and consulted for tool locations
+    pub env: HashMap<String, String>,
+    /// Where the running checker reports progress; set by the backend per checker
+    pub progress: Option<ProgressReporter>,
+    /// Set when the user cancels the scan; long walks should stop early
+    pub cancel: CancellationToken,
+    /// Collects the directories the running checker listed; set by the backend per checker
+    pub tracker: Option<Arc<Mutex<PathTracker>>>,
+    /// Sizes of directories seen in earlier scans; `dir_size` walks everything when unset
+    pub size_index: Option<Arc<SizeIndex>>,
+    /// Projects found under `project_roots`; replaced whenever the roots or depth change
+    pub projects: Arc<ProjectWalk>,
+    /// Estimate directory sizes from a sample instead of walking them; set by
+    /// the backend for the quick first pass of a scan
+    pub estimate: bool,
+    /// How hard the scan may work the machine; limits directory reads
+    pub scan_mode: ScanMode,
+    /// The background scan's pool, built once per scan by the backend; each
+    /// checker runs in it. Rayon's global pool when unset.
+    pub thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// Filesystems mounted when the scan started; paths on network and FUSE
+    /// mounts are not walked. The backend loads the current table for each
+    /// scan when unset.
+    pub mounts: Option<Arc<MountTable>>,
+    /// Collects the mounts the running checker left out; set by the backend per checker
+    pub skipped_mounts: Option<Arc<Mutex<Vec<Mount>>>>,
+    /// How long an external command may run before it is killed
+    pub command_timeout: Duration,
+    /// How long each checker may run; the backend gives it a deadline on `cancel`
+    pub checker_timeout: Duration,
+    /// Runs external tools; real processes unless replaced (e.g. by a
+    /// `ScriptedRunner` in tests)
+    pub runner: Arc<dyn CommandRunner>,
+}
+
+impl ScanContext {
+    /// Context for the current user and process environment
+    pub fn new() -> Self {
+        let home = dirs::home_dir();
+        let project_roots = home
+            .as_deref()
+            .map(default_project_roots)
+            .unwrap_or_default();
+
+        Self {
+            home,
+            root: PathBuf::from("/"),
+            project_roots,
+            max_depth: DEFAULT_MAX_DEPTH,
+            env: std::env::vars().collect(),
+            progress: None,
+            cancel: CancellationToken::new(),
+            tracker: None,
+            size_index: None,
+            projects: Arc::new(ProjectWalk::new()),
+            estimate: false,
+            scan_mode: ScanMode::Normal,
+            thread_pool: None,
+            mounts: None,
+            skipped_mounts: None,
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            checker_timeout: DEFAULT_CHECKER_TIMEOUT,
+            runner: Arc::new(ProcessRunner),
+        }
+    }
+
+    /// Context isolated to `home`: system-wide paths are resolved inside it, no
+    /// environment is inherited, and external tools are only found in `home/bin`
+    pub fn for_home(home: impl Into<PathBuf>) -> Self {
+        let home = home.into();
+        let mut env = HashMap::new();
+        env.insert("HOME".to_string(), home.to_string_lossy().to_string());
+        env.insert(
+            "PATH".to_string(),
+            home.join("bin").to_string_lossy().to_string(),
+        );
+
+        Self {
+            project_roots: default_project_roots(&home),
+            root: home.clone(),
+            home: Some(home),
+            max_depth: DEFAULT_MAX_DEPTH,
+            env,
+            progress: None,
+            cancel: CancellationToken::new(),
+            tracker: None,
+            size_index: None,
+            projects: Arc::new(ProjectWalk::new()),
+            estimate: false,
+            scan_mode: ScanMode::Normal,
+            thread_pool: None,
+            mounts: None,
+            skipped_mounts: None,
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            checker_timeout: DEFAULT_CHECKER_TIMEOUT,
+            runner: Arc::new(ProcessRunner),
+        }
+    }
+
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    pub fn with_project_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        self.project_roots = roots;
+        self.projects = Arc::new(ProjectWalk::new());
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self.projects = Arc::new(ProjectWalk::new());
+        self
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_tracker(mut self, tracker: Arc<Mutex<PathTracker>>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    pub fn with_size_index(mut self, size_index: Arc<SizeIndex>) -> Self {
+        self.size_index = Some(size_index);
+        self
+    }
+
+    pub fn with_projects(mut self, projects: Arc<ProjectWalk>) -> Self {
+        self.projects = projects;
+        self
+    }
+
+    pub fn with_estimate(mut self, estimate: bool) -> Self {
+        self.estimate = estimate;
+        self
+    }
+
+    pub fn with_scan_mode(mut self, scan_mode: ScanMode) -> Self {
+        self.scan_mode = scan_mode;
+        self
+    }
+
+    pub fn with_thread_pool(mut self, thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    pub fn with_mounts(mut self, mounts: Arc<MountTable>) -> Self {
+        self.mounts = Some(mounts);
+        self
+    }
+
+    /// The mount table, empty when none was loaded
+    pub fn mount_table(&self) -> &MountTable {
+        static EMPTY: MountTable = MountTable::empty();
+        self.mounts.as_deref().unwrap_or(&EMPTY)
+    }
+
+    pub fn with_skipped_mounts(mut self, skipped_mounts: Arc<Mutex<Vec<Mount>>>) -> Self {
+        self.skipped_mounts = Some(skipped_mounts);
+        self
+    }
+
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    pub fn with_checker_timeout(mut self, timeout: Duration) -> Self {
+        self.checker_timeout = timeout;
+        self
+    }
+
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// The budget directory reads are taken from in this context's scan mode
+    pub fn io_budget(&self) -> &'static IoBudget {
+        self.scan_mode.io_budget()
+    }
+
+    /// Whether the scan was cancelled; checkers stop walking and return what they have
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Total size of the files under `path`, counted towards the checker's progress
+    pub fn dir_size(&self, path: impl AsRef<Path>) -> u64 {
+        self.disk_usage(path).apparent
+    }
+
+    /// Like `dir_size`, but also reports the space allocated on disk and how
+    /// much of it deleting `path` would free. Only an estimate when `estimate`
+    /// is set.
+    pub fn disk_usage(&self, path: impl AsRef<Path>) -> DiskUsage {
+        let usage = self.uncounted_usage(path);
+        if let Some(progress) = &self.progress {
+            progress.add_bytes(usage.apparent);
+        }
+        usage
+    }
+
+    /// Like `disk_usage`, but not counted towards the checker's progress, for
+    /// directories that are reported but never deleted
+    pub fn uncounted_usage(&self, path: impl AsRef<Path>) -> DiskUsage {
+        let path = path.as_ref();
+        if self.is_on_remote_mount(path) {
+            return DiskUsage::default();
+        }
+        for mount in self.mount_table().mounts_below(path) {
+            self.skip_mount(mount);
+        }
+        self.report_path(path);
+        let budget = self.io_budget();
+        match &self.size_index {
+            _ if self.estimate => estimate_usage(path, ESTIMATE_DIR_BUDGET, budget, &self.cancel),
+            Some(index) => index.disk_usage_within(path, budget, &self.cancel),
+            None => disk_usage_within(path, budget, &self.cancel),
+        }
+    }
+
+    /// Projects under the search roots, discovered by the first checker that
+    /// asks. The walked directories are tracked for every checker, since a new
+    /// project anywhere in them can change its result.
+    pub fn projects(&self) -> &ProjectIndex {
+        let index = self.projects.get_or_discover(self);
+        for dir in &index.walked_dirs {
+            self.track_dir(dir);
+        }
+        for mount in &index.skipped_mounts {
+            self.skip_mount(mount);
+        }
+        index
+    }
+
+    /// Whether `path` is on a network or FUSE mount. Such paths are not
+    /// walked; the mount is recorded as skipped.
+    pub fn is_on_remote_mount(&self, path: &Path) -> bool {
+        match self.mount_table().remote_mount_of(path) {
+            Some(mount) => {
+                self.skip_mount(mount);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record a mount the running checker left out, for its category status
+    pub fn skip_mount(&self, mount: &Mount) {
+        if let Some(skipped) = &self.skipped_mounts {
+            if let Ok(mut skipped) = skipped.lock() {
+                if !skipped.contains(mount) {
+                    skipped.push(mount.clone());
+                }
+            }
+        }
+    }
+
+    /// Record a directory listed to discover items, so the scan cache is
+    /// invalidated when an entry is added to or removed from it
+    pub fn track_dir(&self, path: &Path) {
+        if let Some(tracker) = &self.tracker {
+            if let Ok(mut tracker) = tracker.lock() {
+                tracker.track_dir(path);
+            }
+        }
+    }
+
+    /// Tell the UI which directory the checker is looking at
+    pub fn report_path(&self, path: &Path) {
+        if let Some(progress) = &self.progress {
+            progress.report_path(path);
+        }
+    }
+
+    /// Look up an environment variable, ignoring empty values
+    pub fn env_var(&self, key: &str) -> Option<&str> {
+        self.env
+            .get(key)
+            .map(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Resolve an absolute system path (e.g. `/usr/local/lib`) against `root`
+    pub fn system_path(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /// Project search roots that exist on disk
+    pub fn existing_project_roots(&self) -> Vec<&Path> {
+        self.project_roots
+            .iter()
+            .map(|p| p.as_path())
+            .filter(|p| p.exists())
+            .collect()
+    }
+
+    /// Build a command that runs with this context's environment only
+    pub fn command(&self, program: &str) -> Invocation {
+        let mut invocation = Invocation::new(program);
+        invocation
+            .env(self.env.clone())
+            .timeout(self.command_timeout);
+        invocation
+    }
+
+    /// Run a command built with `command` through `runner`. `None` if it
+    /// couldn't start, ran longer than `command_timeout`, or the scan stopped.
+    pub fn output(&self, invocation: &Invocation) -> Option<CommandOutput> {
+        self.runner.run(invocation, &self.cancel).ok()
+    }
+
+    /// Run a command and return stdout as String
+    pub fn run_command(&self, cmd: &str, args: &[&str]) -> Option<String> {
+        self.output(self.command(cmd).args(args))
+            .filter(|o| o.success())
+            .map(|o| o.stdout.trim().to_string())
+    }
+}
+
+impl Default for ScanContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_project_roots(home: &Path) -> Vec<PathBuf> {
+    DEFAULT_PROJECT_DIRS.iter().map(|d| home.join(d)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_home_is_isolated() {
+        let ctx = ScanContext::for_home("/fixture/home");
+        assert_eq!(ctx.home, Some(PathBuf::from("/fixture/home")));
+        assert_eq!(
+            ctx.system_path("/usr/local/lib"),
+            PathBuf::from("/fixture/home/usr/local/lib")
+        );
+        assert_eq!(ctx.env_var("HOME"), Some("/fixture/home"));
+        assert_eq!(ctx.env_var("PATH"), Some("/fixture/home/bin"));
+        assert!(ctx
+            .project_roots
+            .contains(&PathBuf::from("/fixture/home/Projects")));
+    }
+
+    #[test]
+    fn test_isolated_context_does_not_find_host_tools() {
+        let home = tempfile::tempdir().unwrap();
+        let ctx = ScanContext::for_home(home.path());
+        assert!(ctx.run_command("git", &["--version"]).is_none());
+    }
+}
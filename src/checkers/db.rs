@@ -1,44 +1,69 @@
-use crate::types::{CheckResult, CleanupItem, ItemDetail};
-use crate::utils::{format_size, get_dir_size};
-use dirs::home_dir;
-use std::path::{Path, PathBuf};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, ItemDetail, SuperCategoryType};
+use crate::utils::format_size;
+use std::path::Path;
 
-pub fn check_db_caches() -> CheckResult {
-    let mut result = CheckResult::new("Database Caches");
+/// Registry entry for the database cache checker
+pub struct DatabaseChecker;
 
-    if let Some(home) = home_dir() {
+impl Checker for DatabaseChecker {
+    fn id(&self) -> &'static str {
+        "database"
+    }
+
+    fn name(&self) -> &'static str {
+        "Database Caches"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::SystemAndBrowsers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_db_caches(ctx)
+    }
+}
+
+pub fn check_db_caches(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(DatabaseChecker.name());
+
+    if let Some(home) = &ctx.home {
         // PostgreSQL
-        check_postgres_caches(&mut result, &home);
+        check_postgres_caches(&mut result, home, ctx);
 
         // MySQL
-        check_mysql_caches(&mut result, &home);
+        check_mysql_caches(&mut result, home, ctx);
 
         // MongoDB
-        check_mongodb_caches(&mut result, &home);
+        check_mongodb_caches(&mut result, home, ctx);
 
         // Redis
-        check_redis_caches(&mut result, &home);
+        check_redis_caches(&mut result, home, ctx);
 
         // SQLite
-        check_sqlite_caches(&mut result, &home);
+        check_sqlite_caches(&mut result, home, ctx);
     }
 
     // Homebrew database logs and data
     let brew_paths = vec![
-        PathBuf::from("/opt/homebrew/var"), // Apple Silicon
-        PathBuf::from("/usr/local/var"),    // Intel
+        ctx.system_path("/opt/homebrew/var"), // Apple Silicon
+        ctx.system_path("/usr/local/var"),    // Intel
     ];
 
     for brew_var in brew_paths {
         if brew_var.exists() {
-            check_brew_db_data(&mut result, &brew_var);
+            check_brew_db_data(&mut result, &brew_var, ctx);
         }
     }
 
     result
 }
 
-fn check_postgres_caches(result: &mut CheckResult, home: &Path) {
+fn check_postgres_caches(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut postgres_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
@@ -74,7 +99,7 @@ fn check_postgres_caches(result: &mut CheckResult, home: &Path) {
                         // pg_log directory (older versions)
                         let pg_log_dir = path.join("pg_log");
                         if pg_log_dir.exists() {
-                            let size = get_dir_size(&pg_log_dir);
+                            let size = ctx.dir_size(&pg_log_dir);
                             if size > 1024 * 1024 {
                                 // > 1MB
                                 postgres_items.push(
@@ -92,7 +117,7 @@ fn check_postgres_caches(result: &mut CheckResult, home: &Path) {
                         // pg_wal directory (Write-Ahead Logs) - can be large but be careful
                         let pg_wal_dir = path.join("pg_wal");
                         if pg_wal_dir.exists() {
-                            let size = get_dir_size(&pg_wal_dir);
+                            let size = ctx.dir_size(&pg_wal_dir);
                             if size > 100 * 1024 * 1024 {
                                 // > 100MB
                                 postgres_items.push(
@@ -116,7 +141,7 @@ fn check_postgres_caches(result: &mut CheckResult, home: &Path) {
     // PostgreSQL cache in Library/Caches
     let pg_cache = home.join("Library/Caches/com.postgresapp.Postgres2");
     if pg_cache.exists() {
-        let size = get_dir_size(&pg_cache);
+        let size = ctx.dir_size(&pg_cache);
         if size > 0 {
             postgres_items.push(
                 ItemDetail::new("Postgres.app Cache", size, &format_size(size)).with_path(pg_cache),
@@ -139,7 +164,7 @@ fn check_postgres_caches(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
+fn check_mysql_caches(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut mysql_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
@@ -149,7 +174,7 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
         // Log directory
         let log_dir = mysql_workbench.join("log");
         if log_dir.exists() {
-            let size = get_dir_size(&log_dir);
+            let size = ctx.dir_size(&log_dir);
             if size > 1024 * 1024 {
                 // > 1MB
                 mysql_items.push(
@@ -163,7 +188,7 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
         // SQL history
         let sql_history = mysql_workbench.join("sql_history");
         if sql_history.exists() {
-            let size = get_dir_size(&sql_history);
+            let size = ctx.dir_size(&sql_history);
             if size > 5 * 1024 * 1024 {
                 // > 5MB
                 mysql_items.push(
@@ -178,7 +203,7 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
     // MySQL cache
     let mysql_cache = home.join("Library/Caches/com.oracle.workbench.MySQLWorkbench");
     if mysql_cache.exists() {
-        let size = get_dir_size(&mysql_cache);
+        let size = ctx.dir_size(&mysql_cache);
         if size > 0 {
             mysql_items.push(
                 ItemDetail::new("MySQL Workbench Cache", size, &format_size(size))
@@ -191,7 +216,7 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
     // Sequel Pro / Sequel Ace
     let sequel_support = home.join("Library/Application Support/Sequel Pro");
     if sequel_support.exists() {
-        let size = get_dir_size(&sequel_support);
+        let size = ctx.dir_size(&sequel_support);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             mysql_items.push(
@@ -204,7 +229,7 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
 
     let sequel_ace_support = home.join("Library/Application Support/Sequel Ace");
     if sequel_ace_support.exists() {
-        let size = get_dir_size(&sequel_ace_support);
+        let size = ctx.dir_size(&sequel_ace_support);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             mysql_items.push(
@@ -224,14 +249,14 @@ fn check_mysql_caches(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_mongodb_caches(result: &mut CheckResult, home: &Path) {
+fn check_mongodb_caches(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut mongo_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // MongoDB Compass cache
     let compass_cache = home.join("Library/Caches/mongodb-compass");
     if compass_cache.exists() {
-        let size = get_dir_size(&compass_cache);
+        let size = ctx.dir_size(&compass_cache);
         if size > 0 {
             mongo_items.push(
                 ItemDetail::new("MongoDB Compass Cache", size, &format_size(size))
@@ -246,7 +271,7 @@ fn check_mongodb_caches(result: &mut CheckResult, home: &Path) {
     if compass_support.exists() {
         let logs_dir = compass_support.join("Logs");
         if logs_dir.exists() {
-            let size = get_dir_size(&logs_dir);
+            let size = ctx.dir_size(&logs_dir);
             if size > 1024 * 1024 {
                 // > 1MB
                 mongo_items.push(
@@ -277,7 +302,7 @@ fn check_mongodb_caches(result: &mut CheckResult, home: &Path) {
         // Only check for journal and diagnostic.data which can be cleaned
         let journal_dir = mongo_data.join("journal");
         if journal_dir.exists() {
-            let size = get_dir_size(&journal_dir);
+            let size = ctx.dir_size(&journal_dir);
             if size > 100 * 1024 * 1024 {
                 // > 100MB
                 mongo_items.push(
@@ -291,7 +316,7 @@ fn check_mongodb_caches(result: &mut CheckResult, home: &Path) {
 
         let diag_dir = mongo_data.join("diagnostic.data");
         if diag_dir.exists() {
-            let size = get_dir_size(&diag_dir);
+            let size = ctx.dir_size(&diag_dir);
             if size > 50 * 1024 * 1024 {
                 // > 50MB
                 mongo_items.push(
@@ -313,7 +338,7 @@ fn check_mongodb_caches(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_redis_caches(result: &mut CheckResult, home: &PathBuf) {
+fn check_redis_caches(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut redis_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
@@ -321,8 +346,8 @@ fn check_redis_caches(result: &mut CheckResult, home: &PathBuf) {
     let redis_locations = vec![
         home.join("dump.rdb"),
         home.join(".redis/dump.rdb"),
-        PathBuf::from("/opt/homebrew/var/db/redis/dump.rdb"),
-        PathBuf::from("/usr/local/var/db/redis/dump.rdb"),
+        ctx.system_path("/opt/homebrew/var/db/redis/dump.rdb"),
+        ctx.system_path("/usr/local/var/db/redis/dump.rdb"),
     ];
 
     for dump_path in redis_locations {
@@ -352,7 +377,7 @@ fn check_redis_caches(result: &mut CheckResult, home: &PathBuf) {
     // RedisInsight cache
     let redis_insight_cache = home.join("Library/Caches/RedisInsight");
     if redis_insight_cache.exists() {
-        let size = get_dir_size(&redis_insight_cache);
+        let size = ctx.dir_size(&redis_insight_cache);
         if size > 0 {
             redis_items.push(
                 ItemDetail::new("RedisInsight Cache", size, &format_size(size))
@@ -365,7 +390,7 @@ fn check_redis_caches(result: &mut CheckResult, home: &PathBuf) {
     // Another Redis Desktop Manager
     let ardm_cache = home.join("Library/Caches/Another Redis Desktop Manager");
     if ardm_cache.exists() {
-        let size = get_dir_size(&ardm_cache);
+        let size = ctx.dir_size(&ardm_cache);
         if size > 0 {
             redis_items.push(
                 ItemDetail::new(
@@ -388,14 +413,14 @@ fn check_redis_caches(result: &mut CheckResult, home: &PathBuf) {
     }
 }
 
-fn check_sqlite_caches(result: &mut CheckResult, home: &Path) {
+fn check_sqlite_caches(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut sqlite_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // DB Browser for SQLite
     let db_browser_cache = home.join("Library/Caches/com.sqlitebrowser.sqlitebrowser");
     if db_browser_cache.exists() {
-        let size = get_dir_size(&db_browser_cache);
+        let size = ctx.dir_size(&db_browser_cache);
         if size > 0 {
             sqlite_items.push(
                 ItemDetail::new("DB Browser for SQLite Cache", size, &format_size(size))
@@ -408,7 +433,7 @@ fn check_sqlite_caches(result: &mut CheckResult, home: &Path) {
     // TablePlus cache
     let tableplus_cache = home.join("Library/Caches/com.tableplus.TablePlus");
     if tableplus_cache.exists() {
-        let size = get_dir_size(&tableplus_cache);
+        let size = ctx.dir_size(&tableplus_cache);
         if size > 0 {
             sqlite_items.push(
                 ItemDetail::new("TablePlus Cache", size, &format_size(size))
@@ -421,7 +446,7 @@ fn check_sqlite_caches(result: &mut CheckResult, home: &Path) {
     // DBeaver
     let dbeaver_cache = home.join("Library/Caches/DBeaverData");
     if dbeaver_cache.exists() {
-        let size = get_dir_size(&dbeaver_cache);
+        let size = ctx.dir_size(&dbeaver_cache);
         if size > 0 {
             sqlite_items.push(
                 ItemDetail::new("DBeaver Cache", size, &format_size(size)).with_path(dbeaver_cache),
@@ -435,7 +460,7 @@ fn check_sqlite_caches(result: &mut CheckResult, home: &Path) {
         // Check workspace/.metadata which can grow large
         let metadata_dir = dbeaver_support.join("workspace6/.metadata");
         if metadata_dir.exists() {
-            let size = get_dir_size(&metadata_dir);
+            let size = ctx.dir_size(&metadata_dir);
             if size > 50 * 1024 * 1024 {
                 // > 50MB
                 sqlite_items.push(
@@ -456,7 +481,7 @@ fn check_sqlite_caches(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_brew_db_data(result: &mut CheckResult, brew_var: &Path) {
+fn check_brew_db_data(result: &mut CheckResult, brew_var: &Path, ctx: &ScanContext) {
     let mut brew_db_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
@@ -477,7 +502,7 @@ fn check_brew_db_data(result: &mut CheckResult, brew_var: &Path) {
     // PostgreSQL log directory
     let pg_log_dir = brew_var.join("log/postgresql@14");
     if pg_log_dir.exists() {
-        let size = get_dir_size(&pg_log_dir);
+        let size = ctx.dir_size(&pg_log_dir);
         if size > 1024 * 1024 {
             // > 1MB
             brew_db_items.push(
@@ -1,11 +1,37 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, run_command};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupAction, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 
-pub fn check_docker() -> CheckResult {
-    let mut result = CheckResult::new("Docker");
+/// Registry entry for the Docker checker
+pub struct DockerChecker;
+
+impl Checker for DockerChecker {
+    fn id(&self) -> &'static str {
+        "docker"
+    }
+
+    fn name(&self) -> &'static str {
+        "Docker"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::DevelopmentTools
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_docker(ctx)
+    }
+}
+
+pub fn check_docker(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(DockerChecker.name());
 
     // Check if Docker is installed and running
-    let docker_info = run_command("docker", &["info"]);
+    let docker_info = ctx.run_command("docker", &["info"]);
     if docker_info.is_none() {
         result.status = Some("Docker not installed or not running".to_string());
         return result;
@@ -14,12 +40,12 @@ pub fn check_docker() -> CheckResult {
     result.status = Some("installed".to_string());
 
     // Get Docker disk usage summary
-    if let Some(df_output) = run_command("docker", &["system", "df"]) {
+    if let Some(df_output) = ctx.run_command("docker", &["system", "df"]) {
         result.extra_data.docker_summary = Some(df_output);
     }
 
     // Count dangling images
-    if let Some(dangling) = run_command("docker", &["images", "-f", "dangling=true", "-q"]) {
+    if let Some(dangling) = ctx.run_command("docker", &["images", "-f", "dangling=true", "-q"]) {
         let count = dangling.lines().filter(|l| !l.is_empty()).count();
         if count > 0 {
             result.extra_data.dangling_images = Some(count);
@@ -27,7 +53,7 @@ pub fn check_docker() -> CheckResult {
     }
 
     // Count stopped containers
-    if let Some(stopped) = run_command("docker", &["ps", "-a", "-f", "status=exited", "-q"]) {
+    if let Some(stopped) = ctx.run_command("docker", &["ps", "-a", "-f", "status=exited", "-q"]) {
         let count = stopped.lines().filter(|l| !l.is_empty()).count();
         if count > 0 {
             result.extra_data.stopped_containers = Some(count);
@@ -35,7 +61,7 @@ pub fn check_docker() -> CheckResult {
     }
 
     // Try to get reclaimable space from docker system df
-    if let Some(df_output) = run_command(
+    if let Some(df_output) = ctx.run_command(
         "docker",
         &[
             "system",
@@ -63,7 +89,7 @@ pub fn check_docker() -> CheckResult {
                 &format_size(total_reclaimable),
             )
             .with_safe_to_delete(true)
-            .with_cleanup_command("docker system prune");
+            .with_cleanup_action(CleanupAction::command("docker", ["system", "prune"]));
             result.add_item(item);
         }
     }
@@ -1,14 +1,41 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ProjectKind, ScanContext};
+use crate::types::{CheckResult, CleanupItem, ItemDetail, SuperCategoryType};
+use crate::utils::format_size;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use walkdir::WalkDir;
+use std::path::Path;
 
-pub fn check_general_caches() -> CheckResult {
+/// Registry entry for the general cache checker
+pub struct GeneralCachesChecker;
+
+impl Checker for GeneralCachesChecker {
+    fn id(&self) -> &'static str {
+        "general"
+    }
+
+    fn name(&self) -> &'static str {
+        "General Caches"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::SystemAndBrowsers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        30
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_general_caches(ctx)
+    }
+}
+
+pub fn check_general_caches(ctx: &ScanContext) -> CheckResult {
     use rayon::prelude::*;
 
-    let mut result = CheckResult::new("General Caches");
+    let mut result = CheckResult::new(GeneralCachesChecker.name());
 
-    let home = match home_dir() {
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
@@ -16,6 +43,7 @@ pub fn check_general_caches() -> CheckResult {
     // User caches
     let cache_dir = home.join("Library/Caches");
     if cache_dir.exists() {
+        ctx.track_dir(&cache_dir);
         if let Ok(entries) = fs::read_dir(&cache_dir) {
             let cache_entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
 
@@ -25,7 +53,7 @@ pub fn check_general_caches() -> CheckResult {
                 .filter_map(|entry| {
                     let path = entry.path();
                     if path.is_dir() {
-                        let size = get_dir_size(&path);
+                        let size = ctx.dir_size(&path);
                         if size > 100 * 1024 * 1024 {
                             // > 100MB
                             let name = entry.file_name().to_string_lossy().to_string();
@@ -53,7 +81,7 @@ pub fn check_general_caches() -> CheckResult {
     // User logs
     let logs_dir = home.join("Library/Logs");
     if logs_dir.exists() {
-        let size = get_dir_size(&logs_dir);
+        let size = ctx.dir_size(&logs_dir);
         if size > 50 * 1024 * 1024 {
             // > 50MB
             let item = CleanupItem::new("User Logs", size, &format_size(size))
@@ -66,80 +94,66 @@ pub fn check_general_caches() -> CheckResult {
     result
 }
 
-pub fn check_trash() -> CheckResult {
-    let mut result = CheckResult::new("Trash");
+/// Registry entry for the Trash checker
+pub struct TrashChecker;
+
+impl Checker for TrashChecker {
+    fn id(&self) -> &'static str {
+        "trash"
+    }
+
+    fn name(&self) -> &'static str {
+        "Trash"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::Trash
+    }
+
+    fn default_ttl(&self) -> u64 {
+        0
+    }
 
-    let home = match home_dir() {
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_trash(ctx)
+    }
+}
+
+pub fn check_trash(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(TrashChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
 
     let trash_path = home.join(".Trash");
-
-    println!("DEBUG: Checking trash at: {:?}", trash_path);
-    println!("DEBUG: Trash exists: {}", trash_path.exists());
-
     if !trash_path.exists() {
-        println!("DEBUG: Trash path does not exist");
         return result;
     }
 
-    // Try to read directory entries and count items
-    let (item_count, total_size) = match fs::read_dir(&trash_path) {
-        Ok(entries) => {
-            let mut count = 0;
-            let mut size = 0u64;
-
-            for entry in entries {
-                match entry {
-                    Ok(e) => {
-                        count += 1;
-                        let path = e.path();
-                        println!("DEBUG: Found trash item: {:?}", path.file_name());
-
-                        // Calculate size for this entry
-                        if let Ok(metadata) = e.metadata() {
-                            if metadata.is_file() {
-                                size += metadata.len();
-                            } else if metadata.is_dir() {
-                                // For directories, walk recursively
-                                size += get_dir_size(&path);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!("DEBUG: Error reading entry: {}", e);
-                    }
-                }
-            }
-
-            println!(
-                "DEBUG: Trash contains {} items, total size: {} bytes ({})",
-                count,
-                size,
-                format_size(size)
-            );
-            (count, size)
-        }
+    let entries = match fs::read_dir(&trash_path) {
+        Ok(entries) => entries,
         Err(e) => {
-            println!("DEBUG: Error reading trash directory: {}", e);
-            println!(
-                "DEBUG: This may be a permission issue. Try running the app with appropriate permissions."
-            );
-
-            // Fallback: try to get metadata of the trash directory itself
-            if let Ok(metadata) = fs::metadata(&trash_path) {
-                println!(
-                    "DEBUG: Trash directory metadata - is_dir: {}, readonly: {}",
-                    metadata.is_dir(),
-                    metadata.permissions().readonly()
-                );
-            }
-
+            // Usually missing Full Disk Access on macOS
+            result.status = Some(format!("Cannot read the Trash: {}", e));
             return result;
         }
     };
 
+    let mut item_count = 0;
+    let mut total_size = 0u64;
+    for entry in entries.flatten() {
+        item_count += 1;
+        if let Ok(metadata) = entry.metadata() {
+            if metadata.is_file() {
+                total_size += metadata.len();
+            } else if metadata.is_dir() {
+                total_size += ctx.dir_size(entry.path());
+            }
+        }
+    }
+
     if item_count > 0 && total_size > 0 {
         let item = CleanupItem::new(
             &format!("Empty Trash ({} items)", item_count),
@@ -149,123 +163,138 @@ pub fn check_trash() -> CheckResult {
         .with_path(trash_path)
         .with_safe_to_delete(true);
         result.add_item(item);
-        println!("DEBUG: Added trash item to results");
-    } else if item_count > 0 {
-        println!(
-            "DEBUG: Trash has {} items but size calculated as 0 - may be permission issue",
-            item_count
-        );
-    } else {
-        println!("DEBUG: Trash is empty");
     }
 
     result
 }
 
-pub fn check_node_modules() -> CheckResult {
-    use rayon::prelude::*;
-    use std::sync::{Arc, Mutex};
+/// Registry entry for the project node_modules checker
+pub struct NodeModulesChecker;
 
-    let mut result = CheckResult::new("node_modules in Projects");
+impl Checker for NodeModulesChecker {
+    fn id(&self) -> &'static str {
+        "node_modules"
+    }
 
-    let home = match home_dir() {
-        Some(h) => h,
-        None => return result,
-    };
+    fn name(&self) -> &'static str {
+        "node_modules in Projects"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::ProjectFiles
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
 
-    let search_paths = [
-        home.join("Projects"),
-        home.join("Developer"),
-        home.join("Code"),
-        home.join("Documents"),
-        home.join("Desktop"),
-    ];
-
-    let node_modules_found: Arc<Mutex<Vec<(String, std::path::PathBuf, u64)>>> =
-        Arc::new(Mutex::new(Vec::new()));
-
-    let skip_dirs = [
-        ".git",
-        "venv",
-        ".venv",
-        "__pycache__",
-        "target",
-        "build",
-        "dist",
-        "node_modules",
-    ];
-
-    // Process search paths in parallel
-    search_paths
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_node_modules(ctx)
+    }
+}
+
+/// Files that mark the root of a pnpm workspace or a Lerna, Nx or Turborepo monorepo
+const WORKSPACE_MARKERS: &[&str] = &["pnpm-workspace.yaml", "lerna.json", "nx.json", "turbo.json"];
+
+/// Whether `dir` is the root of a JavaScript workspace whose packages share
+/// dependencies (npm/yarn `workspaces` in package.json, or a monorepo marker)
+fn is_workspace_root(dir: &Path) -> bool {
+    WORKSPACE_MARKERS.iter().any(|m| dir.join(m).is_file())
+        || fs::read_to_string(dir.join("package.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .is_some_and(|package| package.get("workspaces").is_some())
+}
+
+pub fn check_node_modules(ctx: &ScanContext) -> CheckResult {
+    use rayon::prelude::*;
+
+    let mut result = CheckResult::new(NodeModulesChecker.name());
+
+    // Project discovery never descends into node_modules, so nested
+    // node_modules/foo/node_modules are part of the outer one's size
+    let projects: Vec<&Path> = ctx
+        .projects()
+        .of_kind(ProjectKind::Npm)
+        .map(|project| project.path.as_path())
+        .collect();
+    let workspace_roots: Vec<&Path> = projects
+        .iter()
+        .copied()
+        .filter(|p| is_workspace_root(p))
+        .collect();
+
+    // Group packages under the outermost workspace containing them
+    let mut groups: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+    for &project in &projects {
+        let root = workspace_roots
+            .iter()
+            .copied()
+            .filter(|root| project.starts_with(root))
+            .min_by_key(|root| root.components().count())
+            .unwrap_or(project);
+        groups.entry(root).or_default().push(project);
+    }
+
+    // Calculate sizes in parallel
+    let sizes: HashMap<&Path, u64> = projects
         .par_iter()
-        .filter(|p| p.exists())
-        .for_each(|search_path| {
-            let candidates: Vec<_> = WalkDir::new(search_path)
-                .max_depth(6)
-                .into_iter()
-                .filter_entry(|e| {
-                    let name = e.file_name().to_string_lossy();
-                    !skip_dirs.contains(&name.as_ref()) || name == "node_modules"
-                })
-                .filter_map(|e| e.ok())
-                .filter(|entry| {
-                    let path = entry.path();
-                    // Only collect node_modules directories with package.json parent
-                    path.is_dir()
-                        && path
-                            .file_name()
-                            .map(|n| n == "node_modules")
-                            .unwrap_or(false)
-                        && path
-                            .parent()
-                            .map(|p| p.join("package.json").exists())
-                            .unwrap_or(false)
-                })
-                .collect();
+        .filter(|_| !ctx.is_cancelled())
+        .filter_map(|&project| {
+            let node_modules = project.join("node_modules");
+            node_modules
+                .is_dir()
+                .then(|| (project, ctx.dir_size(&node_modules)))
+        })
+        .collect();
+
+    let mut items = Vec::new();
+    for (root, members) in groups {
+        let mut packages: Vec<(&Path, u64)> = members
+            .into_iter()
+            .filter_map(|member| sizes.get(member).map(|&size| (member, size)))
+            .collect();
+        let total: u64 = packages.iter().map(|(_, size)| size).sum();
+
+        // Only include if > 50MB
+        if total <= 50 * 1024 * 1024 {
+            continue;
+        }
 
-            // Calculate sizes in parallel
-            let found: Vec<_> = candidates
-                .par_iter()
-                .filter_map(|entry| {
-                    let path = entry.path();
-                    let size = get_dir_size(path);
-
-                    // Only include if > 50MB
-                    if size > 50 * 1024 * 1024 {
-                        let parent = path.parent()?;
-                        Some((
-                            parent.to_string_lossy().to_string(),
-                            path.to_path_buf(),
-                            size,
-                        ))
-                    } else {
-                        None
-                    }
+        packages.sort_by(|a, b| b.1.cmp(&a.1));
+        let item = if let [(project, size)] = packages[..] {
+            CleanupItem::new(
+                &format!("node_modules: {}", project.display()),
+                size,
+                &format_size(size),
+            )
+            .with_path(project.join("node_modules"))
+        } else {
+            let details = packages
+                .iter()
+                .map(|&(member, size)| {
+                    let name = match member.strip_prefix(root) {
+                        Ok(relative) if relative.as_os_str().is_empty() => "(root)".to_string(),
+                        Ok(relative) => relative.display().to_string(),
+                        Err(_) => member.display().to_string(),
+                    };
+                    ItemDetail::new(&name, size, &format_size(size))
+                        .with_path(member.join("node_modules"))
                 })
                 .collect();
-
-            // Update shared state
-            if !found.is_empty() {
-                node_modules_found.lock().unwrap().extend(found);
-            }
-        });
-
-    // Extract results from Arc<Mutex>
-    let mut node_modules_found = match Arc::try_unwrap(node_modules_found) {
-        Ok(mutex) => mutex.into_inner().unwrap(),
-        Err(arc) => arc.lock().unwrap().clone(),
-    };
+            CleanupItem::new(
+                &format!("node_modules (workspace): {}", root.display()),
+                total,
+                &format_size(total),
+            )
+            .with_group(details)
+        };
+        items.push(item.with_warning("Run 'npm install' to restore"));
+    }
 
     // Sort by size descending and add each as a separate item
-    node_modules_found.sort_by(|a, b| b.2.cmp(&a.2));
-    for (project_path, node_modules_path, size) in node_modules_found {
-        let item = CleanupItem::new(
-            &format!("node_modules: {}", project_path),
-            size,
-            &format_size(size),
-        )
-        .with_path(node_modules_path)
-        .with_warning("Run 'npm install' to restore");
+    items.sort_by(|a, b| b.size.cmp(&a.size));
+    for item in items {
         result.add_item(item);
     }
 
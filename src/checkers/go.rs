@@ -1,38 +1,69 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir, run_command};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupAction, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 use std::path::PathBuf;
 
-pub fn check_go() -> CheckResult {
-    let mut result = CheckResult::new("Go");
+/// Registry entry for the Go checker
+pub struct GoChecker;
 
-    let home = match home_dir() {
+impl Checker for GoChecker {
+    fn id(&self) -> &'static str {
+        "go"
+    }
+
+    fn name(&self) -> &'static str {
+        "Go"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::PackageManagers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_go(ctx)
+    }
+}
+
+pub fn check_go(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(GoChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
 
-    // Go module cache
-    let go_mod_cache = home.join("go/pkg/mod");
+    // Go module cache (first GOPATH entry, defaulting to ~/go)
+    let go_path = ctx
+        .env_var("GOPATH")
+        .and_then(|p| p.split(':').next())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join("go"));
+    let go_mod_cache = go_path.join("pkg/mod");
     if go_mod_cache.exists() {
-        let size = get_dir_size(&go_mod_cache);
+        let size = ctx.dir_size(&go_mod_cache);
         if size > 0 {
             let item = CleanupItem::new("Go module cache", size, &format_size(size))
                 .with_path(go_mod_cache)
                 .with_warning("Projects will need to re-download modules")
-                .with_cleanup_command("go clean -modcache");
+                .with_cleanup_action(CleanupAction::command("go", ["clean", "-modcache"]));
             result.add_item(item);
         }
     }
 
     // Go build cache
-    if let Some(go_cache) = run_command("go", &["env", "GOCACHE"]) {
+    if let Some(go_cache) = ctx.run_command("go", &["env", "GOCACHE"]) {
         let cache_path = PathBuf::from(&go_cache);
         if cache_path.exists() {
-            let size = get_dir_size(&cache_path);
+            let size = ctx.dir_size(&cache_path);
             if size > 0 {
                 let item = CleanupItem::new("Go build cache", size, &format_size(size))
                     .with_path(cache_path)
                     .with_safe_to_delete(true)
-                    .with_cleanup_command("go clean -cache");
+                    .with_cleanup_action(CleanupAction::command("go", ["clean", "-cache"]));
                 result.add_item(item);
             }
         }
@@ -1,17 +1,44 @@
+use super::{Checker, ScanContext};
 use crate::types::{
-    CheckResult, CleanupItem, ItemDetail, OldVersionInfo, PackageInfo, UnneededPackage,
+    CheckResult, CleanupAction, CleanupItem, ItemDetail, OldVersionInfo, PackageInfo,
+    SuperCategoryType, UnneededPackage,
 };
-use crate::utils::{format_size, get_dir_size, home_dir, run_command, sort_versions};
+use crate::utils::{format_size, sort_versions};
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-pub fn check_homebrew() -> CheckResult {
-    let mut result = CheckResult::new("Homebrew");
+/// Registry entry for the Homebrew checker
+pub struct HomebrewChecker;
+
+impl Checker for HomebrewChecker {
+    fn id(&self) -> &'static str {
+        "homebrew"
+    }
+
+    fn name(&self) -> &'static str {
+        "Homebrew"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::DevelopmentTools
+    }
+
+    fn default_ttl(&self) -> u64 {
+        3600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_homebrew(ctx)
+    }
+}
+
+pub fn check_homebrew(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(HomebrewChecker.name());
 
     // Check if Homebrew is installed
-    let brew_version = run_command("brew", &["--version"]);
+    let brew_version = ctx.run_command("brew", &["--version"]);
     if brew_version.is_none() {
         result.status = Some("Homebrew not installed".to_string());
         return result;
@@ -20,22 +47,24 @@ pub fn check_homebrew() -> CheckResult {
     result.status = Some("installed".to_string());
 
     // Get Homebrew prefix
-    let brew_prefix = run_command("brew", &["--prefix"])
+    let brew_prefix = ctx
+        .run_command("brew", &["--prefix"])
         .map(PathBuf::from)
         .unwrap_or_else(|| {
-            if PathBuf::from("/opt/homebrew").exists() {
-                PathBuf::from("/opt/homebrew")
+            let apple_silicon = ctx.system_path("/opt/homebrew");
+            if apple_silicon.exists() {
+                apple_silicon
             } else {
-                PathBuf::from("/usr/local")
+                ctx.system_path("/usr/local")
             }
         });
     result.extra_data.brew_prefix = Some(brew_prefix.clone());
 
     // Check Homebrew cache
-    if let Some(home) = home_dir() {
+    if let Some(home) = &ctx.home {
         let cache_path = home.join("Library/Caches/Homebrew");
         if cache_path.exists() {
-            let cache_size = get_dir_size(&cache_path);
+            let cache_size = ctx.dir_size(&cache_path);
             if cache_size > 0 {
                 let item = CleanupItem::new("Homebrew Cache", cache_size, &format_size(cache_size))
                     .with_path(cache_path)
@@ -48,6 +77,7 @@ pub fn check_homebrew() -> CheckResult {
     // Check for old versions in Cellar
     let cellar_path = brew_prefix.join("Cellar");
     if cellar_path.exists() {
+        ctx.track_dir(&cellar_path);
         if let Ok(packages) = fs::read_dir(&cellar_path) {
             let package_entries: Vec<_> = packages.filter_map(|e| e.ok()).collect();
 
@@ -77,7 +107,7 @@ pub fn check_homebrew() -> CheckResult {
                         // Calculate size of old versions in parallel
                         let old_size: u64 = old_versions
                             .par_iter()
-                            .map(|v| get_dir_size(pkg_path.join(v)))
+                            .map(|v| ctx.dir_size(pkg_path.join(v)))
                             .sum();
 
                         if old_size > 1024 * 1024 {
@@ -117,7 +147,7 @@ pub fn check_homebrew() -> CheckResult {
                     &format_size(total_old_size),
                 )
                 .with_safe_to_delete(true)
-                .with_cleanup_command("brew cleanup")
+                .with_cleanup_action(CleanupAction::command("brew", ["cleanup"]))
                 .with_details(details);
                 result.add_item(item);
                 result.extra_data.old_versions = Some(old_versions_info);
@@ -130,7 +160,7 @@ pub fn check_homebrew() -> CheckResult {
             for pkg_entry in packages.filter_map(|e| e.ok()) {
                 let pkg_path = pkg_entry.path();
                 if pkg_path.is_dir() {
-                    let size = get_dir_size(&pkg_path);
+                    let size = ctx.dir_size(&pkg_path);
                     if size > 100 * 1024 * 1024 {
                         // > 100MB
                         large_packages.push(PackageInfo {
@@ -159,7 +189,7 @@ pub fn check_homebrew() -> CheckResult {
             for pkg_entry in packages.filter_map(|e| e.ok()) {
                 let pkg_path = pkg_entry.path();
                 if pkg_path.is_dir() {
-                    let size = get_dir_size(&pkg_path);
+                    let size = ctx.dir_size(&pkg_path);
                     if size > 10 * 1024 * 1024 {
                         // > 10MB
                         global_npm_packages.push(PackageInfo {
@@ -197,7 +227,7 @@ pub fn check_homebrew() -> CheckResult {
     }
 
     // Check for potentially unneeded packages
-    if let Some(leaves) = run_command("brew", &["leaves"]) {
+    if let Some(leaves) = ctx.run_command("brew", &["leaves"]) {
         let leaf_list: Vec<String> = leaves.lines().map(|s| s.to_string()).collect();
         result.extra_data.leaf_packages = Some(leaf_list.clone());
 
@@ -228,7 +258,7 @@ pub fn check_homebrew() -> CheckResult {
             if let Some(reason) = deprecated_or_replaced.get(pkg.as_str()) {
                 let pkg_path = cellar_path.join(pkg);
                 let size = if pkg_path.exists() {
-                    get_dir_size(&pkg_path)
+                    ctx.dir_size(&pkg_path)
                 } else {
                     0
                 };
@@ -1,10 +1,36 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 
-pub fn check_ide_caches() -> CheckResult {
-    let mut result = CheckResult::new("IDE Caches");
+/// Registry entry for the IDE cache checker
+pub struct IdeChecker;
 
-    if let Some(home) = home_dir() {
+impl Checker for IdeChecker {
+    fn id(&self) -> &'static str {
+        "ide"
+    }
+
+    fn name(&self) -> &'static str {
+        "IDE Caches"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::DevelopmentTools
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_ide_caches(ctx)
+    }
+}
+
+pub fn check_ide_caches(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(IdeChecker.name());
+
+    if let Some(home) = &ctx.home {
         // VSCode Caches
         let vscode_caches = [
             ("Library/Caches/com.microsoft.VSCode", "VSCode Cache"),
@@ -26,7 +52,7 @@ pub fn check_ide_caches() -> CheckResult {
         for (path_str, label) in vscode_caches {
             let path = home.join(path_str);
             if path.exists() {
-                let size = get_dir_size(&path);
+                let size = ctx.dir_size(&path);
                 if size > 0 {
                     result.add_item(
                         CleanupItem::new(label, size, &format_size(size))
@@ -41,12 +67,13 @@ pub fn check_ide_caches() -> CheckResult {
         // Modern layout: ~/Library/Caches/JetBrains/<Product><Version>
         let jetbrains_root = home.join("Library/Caches/JetBrains");
         if jetbrains_root.exists() {
+            ctx.track_dir(&jetbrains_root);
             if let Ok(entries) = std::fs::read_dir(&jetbrains_root) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.is_dir() {
                         let name = path.file_name().unwrap_or_default().to_string_lossy();
-                        let size = get_dir_size(&path);
+                        let size = ctx.dir_size(&path);
                         if size > 0 {
                             result.add_item(
                                 CleanupItem::new(
@@ -66,12 +93,13 @@ pub fn check_ide_caches() -> CheckResult {
         // Android Studio (often under ~/Library/Caches/Google/AndroidStudio*)
         let google_caches = home.join("Library/Caches/Google");
         if google_caches.exists() {
+            ctx.track_dir(&google_caches);
             if let Ok(entries) = std::fs::read_dir(&google_caches) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     let name = path.file_name().unwrap_or_default().to_string_lossy();
                     if name.starts_with("AndroidStudio") {
-                        let size = get_dir_size(&path);
+                        let size = ctx.dir_size(&path);
                         if size > 0 {
                             result.add_item(
                                 CleanupItem::new(
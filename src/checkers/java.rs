@@ -1,18 +1,50 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
+use std::path::PathBuf;
 
-pub fn check_gradle_maven() -> CheckResult {
-    let mut result = CheckResult::new("Java Build Tools");
+/// Registry entry for the Gradle and Maven checker
+pub struct JavaChecker;
 
-    let home = match home_dir() {
+impl Checker for JavaChecker {
+    fn id(&self) -> &'static str {
+        "java"
+    }
+
+    fn name(&self) -> &'static str {
+        "Java Build Tools"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::PackageManagers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_gradle_maven(ctx)
+    }
+}
+
+pub fn check_gradle_maven(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(JavaChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
 
+    let gradle_home = ctx
+        .env_var("GRADLE_USER_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".gradle"));
+
     // Gradle caches
-    let gradle_cache = home.join(".gradle/caches");
+    let gradle_cache = gradle_home.join("caches");
     if gradle_cache.exists() {
-        let size = get_dir_size(&gradle_cache);
+        let size = ctx.dir_size(&gradle_cache);
         if size > 0 {
             let item = CleanupItem::new("Gradle caches", size, &format_size(size))
                 .with_path(gradle_cache)
@@ -22,9 +54,9 @@ pub fn check_gradle_maven() -> CheckResult {
     }
 
     // Gradle wrapper distributions
-    let gradle_wrapper = home.join(".gradle/wrapper/dists");
+    let gradle_wrapper = gradle_home.join("wrapper/dists");
     if gradle_wrapper.exists() {
-        let size = get_dir_size(&gradle_wrapper);
+        let size = ctx.dir_size(&gradle_wrapper);
         if size > 0 {
             let item = CleanupItem::new("Gradle wrapper distributions", size, &format_size(size))
                 .with_path(gradle_wrapper)
@@ -36,7 +68,7 @@ pub fn check_gradle_maven() -> CheckResult {
     // Maven repository
     let maven_repo = home.join(".m2/repository");
     if maven_repo.exists() {
-        let size = get_dir_size(&maven_repo);
+        let size = ctx.dir_size(&maven_repo);
         if size > 0 {
             let item = CleanupItem::new("Maven repository", size, &format_size(size))
                 .with_path(maven_repo)
@@ -1,32 +1,57 @@
-use crate::types::{CheckResult, CleanupItem, ItemDetail};
-use crate::utils::{format_size, get_dir_size};
-use dirs::home_dir;
-use std::path::{Path, PathBuf};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, ItemDetail, SuperCategoryType};
+use crate::utils::format_size;
+use std::path::Path;
 
-pub fn check_system_logs() -> CheckResult {
-    let mut result = CheckResult::new("System Logs & Crash Reports");
+/// Registry entry for the system log checker
+pub struct LogsChecker;
 
-    if let Some(home) = home_dir() {
+impl Checker for LogsChecker {
+    fn id(&self) -> &'static str {
+        "logs"
+    }
+
+    fn name(&self) -> &'static str {
+        "System Logs & Crash Reports"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::SystemAndBrowsers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_system_logs(ctx)
+    }
+}
+
+pub fn check_system_logs(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(LogsChecker.name());
+
+    if let Some(home) = &ctx.home {
         // User Application Logs
-        check_user_logs(&mut result, &home);
+        check_user_logs(&mut result, home, ctx);
 
         // Crash Reports and Diagnostic Reports
-        check_crash_reports(&mut result, &home);
+        check_crash_reports(&mut result, home, ctx);
 
         // System diagnostic data
-        check_system_diagnostics(&mut result, &home);
+        check_system_diagnostics(&mut result, home, ctx);
 
         // Application-specific logs
-        check_app_logs(&mut result, &home);
+        check_app_logs(&mut result, home, ctx);
     }
 
     // System-level logs (readable without root)
-    check_system_level_logs(&mut result);
+    check_system_level_logs(&mut result, ctx);
 
     result
 }
 
-fn check_user_logs(result: &mut CheckResult, home: &Path) {
+fn check_user_logs(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let user_logs = home.join("Library/Logs");
     if !user_logs.exists() {
         return;
@@ -47,7 +72,7 @@ fn check_user_logs(result: &mut CheckResult, home: &Path) {
             }
 
             let size = if path.is_dir() {
-                get_dir_size(&path)
+                ctx.dir_size(&path)
             } else {
                 std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
             };
@@ -77,14 +102,14 @@ fn check_user_logs(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_crash_reports(result: &mut CheckResult, home: &Path) {
+fn check_crash_reports(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut crash_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // User Diagnostic Reports (crash reports)
     let diag_reports = home.join("Library/Logs/DiagnosticReports");
     if diag_reports.exists() {
-        let size = get_dir_size(&diag_reports);
+        let size = ctx.dir_size(&diag_reports);
         if size > 1024 * 1024 {
             // > 1MB
             crash_items.push(
@@ -98,7 +123,7 @@ fn check_crash_reports(result: &mut CheckResult, home: &Path) {
     // Retired Diagnostic Reports
     let retired_reports = home.join("Library/Logs/DiagnosticReports/Retired");
     if retired_reports.exists() {
-        let size = get_dir_size(&retired_reports);
+        let size = ctx.dir_size(&retired_reports);
         if size > 0 {
             // Already counted in DiagnosticReports, but note it exists
         }
@@ -107,7 +132,7 @@ fn check_crash_reports(result: &mut CheckResult, home: &Path) {
     // CoreAnalytics (system telemetry)
     let core_analytics = home.join("Library/Logs/CoreAnalytics");
     if core_analytics.exists() {
-        let size = get_dir_size(&core_analytics);
+        let size = ctx.dir_size(&core_analytics);
         if size > 1024 * 1024 {
             // > 1MB
             crash_items.push(
@@ -121,7 +146,7 @@ fn check_crash_reports(result: &mut CheckResult, home: &Path) {
     // Spotlight diagnostic logs
     let spotlight_diag = home.join("Library/Logs/Spotlight");
     if spotlight_diag.exists() {
-        let size = get_dir_size(&spotlight_diag);
+        let size = ctx.dir_size(&spotlight_diag);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             crash_items.push(
@@ -145,14 +170,14 @@ fn check_crash_reports(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_system_diagnostics(result: &mut CheckResult, home: &Path) {
+fn check_system_diagnostics(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut diag_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // Apple System Logs aggregated data
     let asl_logs = home.join("Library/Logs/asl");
     if asl_logs.exists() {
-        let size = get_dir_size(&asl_logs);
+        let size = ctx.dir_size(&asl_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             diag_items.push(
@@ -166,7 +191,7 @@ fn check_system_diagnostics(result: &mut CheckResult, home: &Path) {
     // Console saved logs and reports
     let console_reports = home.join("Library/Logs/Console");
     if console_reports.exists() {
-        let size = get_dir_size(&console_reports);
+        let size = ctx.dir_size(&console_reports);
         if size > 1024 * 1024 {
             // > 1MB
             diag_items.push(
@@ -180,7 +205,7 @@ fn check_system_diagnostics(result: &mut CheckResult, home: &Path) {
     // Install logs
     let install_logs = home.join("Library/Logs/Install Application");
     if install_logs.exists() {
-        let size = get_dir_size(&install_logs);
+        let size = ctx.dir_size(&install_logs);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             diag_items.push(
@@ -193,7 +218,7 @@ fn check_system_diagnostics(result: &mut CheckResult, home: &Path) {
     // JetBrains logs (can get very large)
     let jetbrains_logs = home.join("Library/Logs/JetBrains");
     if jetbrains_logs.exists() {
-        let size = get_dir_size(&jetbrains_logs);
+        let size = ctx.dir_size(&jetbrains_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             diag_items.push(
@@ -217,14 +242,14 @@ fn check_system_diagnostics(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_app_logs(result: &mut CheckResult, home: &Path) {
+fn check_app_logs(result: &mut CheckResult, home: &Path, ctx: &ScanContext) {
     let mut app_log_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // Adobe logs
     let adobe_logs = home.join("Library/Logs/Adobe");
     if adobe_logs.exists() {
-        let size = get_dir_size(&adobe_logs);
+        let size = ctx.dir_size(&adobe_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             app_log_items.push(
@@ -237,7 +262,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Microsoft logs
     let microsoft_logs = home.join("Library/Logs/Microsoft");
     if microsoft_logs.exists() {
-        let size = get_dir_size(&microsoft_logs);
+        let size = ctx.dir_size(&microsoft_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             app_log_items.push(
@@ -251,7 +276,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Google Chrome crash reports
     let chrome_crash = home.join("Library/Application Support/Google/Chrome/Crash Reports");
     if chrome_crash.exists() {
-        let size = get_dir_size(&chrome_crash);
+        let size = ctx.dir_size(&chrome_crash);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             app_log_items.push(
@@ -265,7 +290,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Firefox crash reports
     let firefox_crash = home.join("Library/Application Support/Firefox/Crash Reports");
     if firefox_crash.exists() {
-        let size = get_dir_size(&firefox_crash);
+        let size = ctx.dir_size(&firefox_crash);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             app_log_items.push(
@@ -279,7 +304,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Slack logs
     let slack_logs = home.join("Library/Application Support/Slack/logs");
     if slack_logs.exists() {
-        let size = get_dir_size(&slack_logs);
+        let size = ctx.dir_size(&slack_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             app_log_items.push(
@@ -292,7 +317,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Discord logs
     let discord_logs = home.join("Library/Application Support/discord/logs");
     if discord_logs.exists() {
-        let size = get_dir_size(&discord_logs);
+        let size = ctx.dir_size(&discord_logs);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             app_log_items.push(
@@ -305,7 +330,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Zoom logs
     let zoom_logs = home.join("Library/Logs/zoom.us");
     if zoom_logs.exists() {
-        let size = get_dir_size(&zoom_logs);
+        let size = ctx.dir_size(&zoom_logs);
         if size > 10 * 1024 * 1024 {
             // > 10MB
             app_log_items
@@ -317,7 +342,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Figma logs
     let figma_logs = home.join("Library/Application Support/Figma/logs");
     if figma_logs.exists() {
-        let size = get_dir_size(&figma_logs);
+        let size = ctx.dir_size(&figma_logs);
         if size > 5 * 1024 * 1024 {
             // > 5MB
             app_log_items.push(
@@ -330,7 +355,7 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     // Spotify logs and cache (can be huge)
     let spotify_prefs = home.join("Library/Application Support/Spotify/PersistentCache");
     if spotify_prefs.exists() {
-        let size = get_dir_size(&spotify_prefs);
+        let size = ctx.dir_size(&spotify_prefs);
         if size > 100 * 1024 * 1024 {
             // > 100MB
             app_log_items.push(
@@ -354,13 +379,13 @@ fn check_app_logs(result: &mut CheckResult, home: &Path) {
     }
 }
 
-fn check_system_level_logs(result: &mut CheckResult) {
+fn check_system_level_logs(result: &mut CheckResult, ctx: &ScanContext) {
     let mut sys_log_items: Vec<ItemDetail> = Vec::new();
     let mut total_size: u64 = 0;
 
     // /var/log is usually readable but not writable without root
     // We can still show the sizes for awareness
-    let var_log = PathBuf::from("/var/log");
+    let var_log = ctx.system_path("/var/log");
     if var_log.exists() {
         // Check specific log files that might be readable
         let log_files = vec![
@@ -422,12 +447,12 @@ fn check_system_level_logs(result: &mut CheckResult) {
     }
 
     // Private var logs
-    let private_var_log = PathBuf::from("/private/var/log");
+    let private_var_log = ctx.system_path("/private/var/log");
     if private_var_log.exists() {
         // DiagnosticMessages (can grow very large)
         let diag_messages = private_var_log.join("DiagnosticMessages");
         if diag_messages.exists() {
-            let size = get_dir_size(&diag_messages);
+            let size = ctx.dir_size(&diag_messages);
             if size > 100 * 1024 * 1024 {
                 // > 100MB
                 sys_log_items.push(
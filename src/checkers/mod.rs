@@ -1,4 +1,5 @@
 mod browser;
+mod context;
 mod db;
 mod docker;
 mod general;
@@ -9,12 +10,15 @@ mod ide;
 mod java;
 mod logs;
 mod nodejs;
+pub mod projects;
 mod python;
+pub mod registry;
 mod rust_cargo;
 mod shell;
 mod xcode;
 
 pub use browser::check_browser_caches;
+pub use context::{ScanContext, DEFAULT_CHECKER_TIMEOUT};
 pub use db::check_db_caches;
 pub use docker::check_docker;
 pub use general::{check_general_caches, check_node_modules, check_trash};
@@ -25,7 +29,9 @@ pub use ide::check_ide_caches;
 pub use java::check_gradle_maven;
 pub use logs::check_system_logs;
 pub use nodejs::check_npm_yarn;
+pub use projects::{Project, ProjectIndex, ProjectKind, ProjectWalk};
 pub use python::check_python;
+pub use registry::Checker;
 pub use rust_cargo::check_rust;
 pub use shell::check_shell_caches;
 pub use xcode::check_xcode;
@@ -1,17 +1,43 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir, run_command};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 use std::path::PathBuf;
 
-pub fn check_npm_yarn() -> CheckResult {
-    let mut result = CheckResult::new("Node.js Package Managers");
+/// Registry entry for the npm, yarn and pnpm checker
+pub struct NodePackageManagersChecker;
+
+impl Checker for NodePackageManagersChecker {
+    fn id(&self) -> &'static str {
+        "nodejs"
+    }
+
+    fn name(&self) -> &'static str {
+        "Node.js Package Managers"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::PackageManagers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_npm_yarn(ctx)
+    }
+}
+
+pub fn check_npm_yarn(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(NodePackageManagersChecker.name());
 
     // npm cache
-    if let Some(npm_cache) = run_command("npm", &["config", "get", "cache"]) {
+    if let Some(npm_cache) = ctx.run_command("npm", &["config", "get", "cache"]) {
         let cache_path = PathBuf::from(&npm_cache);
         if cache_path.exists() {
-            let size = get_dir_size(&cache_path);
-            if size > 0 {
-                let item = CleanupItem::new("npm cache", size, &format_size(size))
+            let usage = ctx.disk_usage(&cache_path);
+            if usage.apparent > 0 {
+                let item = CleanupItem::from_usage("npm cache", usage)
                     .with_path(cache_path)
                     .with_safe_to_delete(true);
                 result.add_item(item);
@@ -20,12 +46,12 @@ pub fn check_npm_yarn() -> CheckResult {
     }
 
     // yarn cache
-    if let Some(yarn_cache) = run_command("yarn", &["cache", "dir"]) {
+    if let Some(yarn_cache) = ctx.run_command("yarn", &["cache", "dir"]) {
         let cache_path = PathBuf::from(&yarn_cache);
         if cache_path.exists() {
-            let size = get_dir_size(&cache_path);
-            if size > 0 {
-                let item = CleanupItem::new("yarn cache", size, &format_size(size))
+            let usage = ctx.disk_usage(&cache_path);
+            if usage.apparent > 0 {
+                let item = CleanupItem::from_usage("yarn cache", usage)
                     .with_path(cache_path)
                     .with_safe_to_delete(true);
                 result.add_item(item);
@@ -34,12 +60,12 @@ pub fn check_npm_yarn() -> CheckResult {
     }
 
     // pnpm cache
-    if let Some(home) = home_dir() {
+    if let Some(home) = &ctx.home {
         let pnpm_cache = home.join("Library/pnpm");
         if pnpm_cache.exists() {
-            let size = get_dir_size(&pnpm_cache);
-            if size > 0 {
-                let item = CleanupItem::new("pnpm cache", size, &format_size(size))
+            let usage = ctx.disk_usage(&pnpm_cache);
+            if usage.apparent > 0 {
+                let item = CleanupItem::from_usage("pnpm cache", usage)
                     .with_path(pnpm_cache)
                     .with_safe_to_delete(true);
                 result.add_item(item);
@@ -48,9 +74,9 @@ pub fn check_npm_yarn() -> CheckResult {
     }
 
     // Global node_modules (Intel Mac location)
-    let global_nm = PathBuf::from("/usr/local/lib/node_modules");
+    let global_nm = ctx.system_path("/usr/local/lib/node_modules");
     if global_nm.exists() {
-        let size = get_dir_size(&global_nm);
+        let size = ctx.dir_size(&global_nm);
         if size > 0 {
             let item =
                 CleanupItem::new("Global node_modules (/usr/local)", size, &format_size(size))
@@ -68,7 +94,8 @@ mod tests {
     use super::*;
     #[test]
     fn test_check_npm_yarn_returns_result() {
-        let result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let result = check_npm_yarn(&ctx);
         assert_eq!(result.name, "Node.js Package Managers");
         // Result may or may not have items depending on system state
         // total_size is u64, always non-negative by type
@@ -78,10 +105,11 @@ mod tests {
     fn test_npm_cache_detection() {
         // This test verifies the function doesn't crash
         // Actual cache presence depends on system configuration
-        let _result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let _result = check_npm_yarn(&ctx);
 
         // If npm is installed, we should get a valid result
-        if let Some(_npm_cache) = run_command("npm", &["config", "get", "cache"]) {
+        if let Some(_npm_cache) = ctx.run_command("npm", &["config", "get", "cache"]) {
             // Test passed - npm is available
             // items.len() is usize, always non-negative by type
         }
@@ -90,10 +118,11 @@ mod tests {
     #[test]
     fn test_yarn_cache_detection() {
         // This test verifies the function doesn't crash
-        let _result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let _result = check_npm_yarn(&ctx);
 
         // If yarn is installed, we should get a valid result
-        if let Some(_yarn_cache) = run_command("yarn", &["cache", "dir"]) {
+        if let Some(_yarn_cache) = ctx.run_command("yarn", &["cache", "dir"]) {
             // Test passed - yarn is available
             // items.len() is usize, always non-negative by type
         }
@@ -102,7 +131,8 @@ mod tests {
     #[test]
     fn test_pnpm_cache_path_construction() {
         // Test that pnpm path is correctly constructed
-        if let Some(home) = home_dir() {
+        let ctx = ScanContext::new();
+        if let Some(home) = &ctx.home {
             let pnpm_cache = home.join("Library/pnpm");
             // Path should be valid even if it doesn't exist
             assert!(pnpm_cache.to_str().is_some());
@@ -120,7 +150,8 @@ mod tests {
 
     #[test]
     fn test_check_result_structure() {
-        let result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let result = check_npm_yarn(&ctx);
 
         // Verify result structure
         assert!(!result.name.is_empty());
@@ -136,7 +167,8 @@ mod tests {
 
     #[test]
     fn test_cleanup_items_have_paths() {
-        let result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let result = check_npm_yarn(&ctx);
 
         // All items should have paths if they were detected
         for item in result.items {
@@ -150,7 +182,8 @@ mod tests {
 
     #[test]
     fn test_safe_to_delete_flags() {
-        let result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let result = check_npm_yarn(&ctx);
 
         // Check that npm, yarn, and pnpm caches are marked as safe to delete
         for item in result.items {
@@ -169,7 +202,8 @@ mod tests {
 
     #[test]
     fn test_global_node_modules_has_warning() {
-        let result = check_npm_yarn();
+        let ctx = ScanContext::new();
+        let result = check_npm_yarn(&ctx);
 
         // Global node_modules should have a warning
         for item in result.items {
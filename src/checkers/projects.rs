@@ -0,0 +1,167 @@
+//! Project discovery shared by the project-scanning checkers
+//!
+//! The Rust, node_modules and Git checkers all look for projects under the
+//! same search roots. Instead of each walking them with its own depth and skip
+//! list, the first checker to ask runs one parallel walk that classifies every
+//! directory from its listing, without probing for marker files; the others
+//! wait for and reuse its result. A
+//! `ProjectWalk` lives for one scan: the backend hands every checker a fresh
+//! one, so projects created between scans are found.
+
+use super::ScanContext;
+use crate::mounts::Mount;
+use crate::walk::{DirListing, ParallelWalk};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directories that are never descended into while looking for projects:
+/// build output, dependencies and VCS internals hold no projects of their own
+pub const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "venv",
+    ".venv",
+    "__pycache__",
+    "target",
+    "build",
+    "dist",
+    "node_modules",
+];
+
+/// What a project directory was recognised as; one directory can be several
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProjectKind {
+    /// Has a `Cargo.toml`
+    Cargo,
+    /// Has a `package.json`
+    Npm,
+    /// Has a `.git` directory
+    Git,
+    /// Has a `pyproject.toml`, `setup.py` or `requirements.txt`
+    Python,
+}
+
+impl ProjectKind {
+    fn all() -> [Self; 4] {
+        [Self::Cargo, Self::Npm, Self::Git, Self::Python]
+    }
+
+    fn matches(&self, listing: &DirListing) -> bool {
+        match self {
+            Self::Cargo => listing.has_file("Cargo.toml"),
+            Self::Npm => listing.has_file("package.json"),
+            Self::Git => listing.has_dir(".git"),
+            Self::Python => ["pyproject.toml", "setup.py", "requirements.txt"]
+                .iter()
+                .any(|marker| listing.has_file(marker)),
+        }
+    }
+}
+
+/// A directory recognised as one or more kinds of project
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub path: PathBuf,
+    pub kinds: Vec<ProjectKind>,
+}
+
+impl Project {
+    pub fn is(&self, kind: ProjectKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+}
+
+/// Result of walking the project search roots
+#[derive(Debug, Clone, Default)]
+pub struct ProjectIndex {
+    /// Projects in walk order, search root by search root
+    pub projects: Vec<Project>,
+    /// Every directory listed during the walk
+    pub walked_dirs: Vec<PathBuf>,
+    /// Network and FUSE mounts that were not walked, and mounts the walk
+    /// stopped at
+    pub skipped_mounts: Vec<Mount>,
+}
+
+impl ProjectIndex {
+    /// Walk `ctx.project_roots` down to `ctx.max_depth`, classifying each
+    /// directory from its listing
+    pub fn discover(ctx: &ScanContext) -> Self {
+        let mut index = Self::default();
+        for root in ctx.existing_project_roots() {
+            let root = Self::discover_root(ctx, root);
+            index.projects.extend(root.projects);
+            index.walked_dirs.extend(root.walked_dirs);
+            index.skipped_mounts.extend(root.skipped_mounts);
+        }
+        index
+    }
+
+    fn discover_root(ctx: &ScanContext, root: &Path) -> Self {
+        let mut index = Self::default();
+        if let Some(mount) = ctx.mount_table().remote_mount_of(root) {
+            index.skipped_mounts.push(mount.clone());
+            return index;
+        }
+        index
+            .skipped_mounts
+            .extend(ctx.mount_table().mounts_below(root).cloned());
+
+        let listed = ParallelWalk::new(&ctx.cancel)
+            .with_budget(ctx.io_budget())
+            .map_dirs(
+                root,
+                ctx.max_depth,
+                |name| !SKIPPED_DIRS.iter().any(|skipped| name == *skipped),
+                |listing| {
+                    ctx.report_path(&listing.dir);
+                    let kinds: Vec<ProjectKind> = ProjectKind::all()
+                        .into_iter()
+                        .filter(|kind| kind.matches(listing))
+                        .collect();
+                    (listing.dir.clone(), kinds)
+                },
+            );
+        for (dir, kinds) in listed {
+            if !kinds.is_empty() {
+                index.projects.push(Project {
+                    path: dir.clone(),
+                    kinds,
+                });
+            }
+            index.walked_dirs.push(dir);
+        }
+        index
+    }
+
+    /// Projects of the given kind
+    pub fn of_kind(&self, kind: ProjectKind) -> impl Iterator<Item = &Project> {
+        self.projects.iter().filter(move |p| p.is(kind))
+    }
+}
+
+/// One scan's project discovery, run on first use and shared by every
+/// checker holding a clone of the same context
+#[derive(Debug, Default)]
+pub struct ProjectWalk {
+    index: OnceLock<ProjectIndex>,
+}
+
+impl ProjectWalk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The discovered projects, walking the search roots if no checker has yet
+    pub fn get_or_discover(&self, ctx: &ScanContext) -> &ProjectIndex {
+        // Other checkers share the walk, so it isn't cut short by the deadline
+        // of whichever checker happened to start it
+        self.index.get_or_init(|| {
+            ProjectIndex::discover(&ctx.clone().with_cancel(ctx.cancel.without_timeout()))
+        })
+    }
+
+    /// Whether the walk has already run
+    pub fn is_done(&self) -> bool {
+        self.index.get().is_some()
+    }
+}
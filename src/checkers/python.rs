@@ -1,11 +1,37 @@
-use crate::types::{CheckResult, CleanupItem, ItemDetail};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, ItemDetail, SuperCategoryType};
+use crate::utils::format_size;
 use std::fs;
 
-pub fn check_python() -> CheckResult {
-    let mut result = CheckResult::new("Python");
+/// Registry entry for the Python checker
+pub struct PythonChecker;
 
-    let home = match home_dir() {
+impl Checker for PythonChecker {
+    fn id(&self) -> &'static str {
+        "python"
+    }
+
+    fn name(&self) -> &'static str {
+        "Python"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::PackageManagers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        600
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_python(ctx)
+    }
+}
+
+pub fn check_python(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(PythonChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
@@ -13,7 +39,7 @@ pub fn check_python() -> CheckResult {
     // pip cache
     let pip_cache = home.join("Library/Caches/pip");
     if pip_cache.exists() {
-        let size = get_dir_size(&pip_cache);
+        let size = ctx.dir_size(&pip_cache);
         if size > 0 {
             let item = CleanupItem::new("pip cache", size, &format_size(size))
                 .with_path(pip_cache)
@@ -25,7 +51,7 @@ pub fn check_python() -> CheckResult {
     // pyenv versions
     let pyenv_path = home.join(".pyenv/versions");
     if pyenv_path.exists() {
-        let size = get_dir_size(&pyenv_path);
+        let size = ctx.dir_size(&pyenv_path);
         if size > 0 {
             let versions: Vec<String> = fs::read_dir(&pyenv_path)
                 .ok()
@@ -43,7 +69,7 @@ pub fn check_python() -> CheckResult {
                 .take(10)
                 .map(|v| {
                     let v_path = pyenv_path.join(v);
-                    let v_size = get_dir_size(&v_path);
+                    let v_size = ctx.dir_size(&v_path);
                     ItemDetail::new(v, v_size, &format_size(v_size)).with_path(v_path)
                 })
                 .collect();
@@ -60,7 +86,7 @@ pub fn check_python() -> CheckResult {
     for conda_name in &["miniconda3", "anaconda3"] {
         let conda_path = home.join(conda_name);
         if conda_path.exists() {
-            let size = get_dir_size(&conda_path);
+            let size = ctx.dir_size(&conda_path);
             if size > 0 {
                 let item = CleanupItem::new(
                     &format!("Conda installation ({})", conda_name),
@@ -78,7 +104,7 @@ pub fn check_python() -> CheckResult {
     // virtualenvs
     let venv_path = home.join(".virtualenvs");
     if venv_path.exists() {
-        let size = get_dir_size(&venv_path);
+        let size = ctx.dir_size(&venv_path);
         if size > 0 {
             let envs: Vec<String> = fs::read_dir(&venv_path)
                 .ok()
@@ -96,7 +122,7 @@ pub fn check_python() -> CheckResult {
                 .take(10)
                 .map(|v| {
                     let v_path = venv_path.join(v);
-                    let v_size = get_dir_size(&v_path);
+                    let v_size = ctx.dir_size(&v_path);
                     ItemDetail::new(v, v_size, &format_size(v_size)).with_path(v_path)
                 })
                 .collect();
@@ -0,0 +1,121 @@
+//! Checker registry
+//!
+//! Every category DevSweep scans is registered here exactly once. The backend,
+//! scan cache and UI look categories up through this registry so that a
+//! category's id, display name, grouping and default TTL cannot drift apart.
+
+use super::ScanContext;
+use super::{
+    browser::BrowserChecker, db::DatabaseChecker, docker::DockerChecker,
+    general::GeneralCachesChecker, general::NodeModulesChecker, general::TrashChecker,
+    git::GitChecker, go::GoChecker, homebrew::HomebrewChecker, ide::IdeChecker, java::JavaChecker,
+    logs::LogsChecker, nodejs::NodePackageManagersChecker, python::PythonChecker,
+    rust_cargo::RustChecker, shell::ShellChecker, xcode::XcodeChecker,
+};
+use crate::custom_paths::CustomPathsChecker;
+use crate::types::{CheckResult, SuperCategoryType};
+
+/// A single scannable category
+pub trait Checker: Send + Sync {
+    /// Stable identifier used in config files and on the command line
+    fn id(&self) -> &'static str;
+
+    /// Human-readable category name shown in the UI
+    fn name(&self) -> &'static str;
+
+    /// Super category this checker is grouped under in the UI
+    fn super_category(&self) -> SuperCategoryType;
+
+    /// Default cache TTL in seconds (0 = never cache)
+    fn default_ttl(&self) -> u64;
+
+    /// Run the check against the given machine description
+    fn check(&self, ctx: &ScanContext) -> CheckResult;
+}
+
+/// All registered checkers, in display order
+static CHECKERS: &[&dyn Checker] = &[
+    &DockerChecker,
+    &HomebrewChecker,
+    &NodePackageManagersChecker,
+    &PythonChecker,
+    &RustChecker,
+    &XcodeChecker,
+    &JavaChecker,
+    &GoChecker,
+    &IdeChecker,
+    &ShellChecker,
+    &DatabaseChecker,
+    &LogsChecker,
+    &BrowserChecker,
+    &NodeModulesChecker,
+    &GitChecker,
+    &GeneralCachesChecker,
+    &CustomPathsChecker,
+    &TrashChecker,
+];
+
+/// Category names used by earlier releases, mapped to their current names
+const LEGACY_NAMES: &[(&str, &str)] = &[
+    ("Node.js/npm/yarn", "Node.js Package Managers"),
+    ("Java (Gradle/Maven)", "Java Build Tools"),
+    ("System Logs", "System Logs & Crash Reports"),
+];
+
+/// Get all registered checkers in display order
+pub fn all() -> &'static [&'static dyn Checker] {
+    CHECKERS
+}
+
+/// Find a checker by its stable id
+pub fn find(id: &str) -> Option<&'static dyn Checker> {
+    CHECKERS.iter().copied().find(|c| c.id() == id)
+}
+
+/// Find a checker by its display name
+pub fn find_by_name(name: &str) -> Option<&'static dyn Checker> {
+    CHECKERS.iter().copied().find(|c| c.name() == name)
+}
+
+/// Map a category name from an older release to its current name
+pub fn migrate_legacy_name(name: &str) -> &str {
+    LEGACY_NAMES
+        .iter()
+        .find(|(old, _)| *old == name)
+        .map(|(_, new)| *new)
+        .unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_ids_and_names_are_unique() {
+        let ids: HashSet<_> = all().iter().map(|c| c.id()).collect();
+        let names: HashSet<_> = all().iter().map(|c| c.name()).collect();
+        assert_eq!(ids.len(), all().len());
+        assert_eq!(names.len(), all().len());
+    }
+
+    #[test]
+    fn test_find_by_id_and_name() {
+        let docker = find("docker").unwrap();
+        assert_eq!(docker.name(), "Docker");
+        assert_eq!(find_by_name("Docker").unwrap().id(), "docker");
+        assert!(find("does-not-exist").is_none());
+    }
+
+    #[test]
+    fn test_migrate_legacy_name() {
+        assert_eq!(
+            migrate_legacy_name("Node.js/npm/yarn"),
+            "Node.js Package Managers"
+        );
+        assert_eq!(migrate_legacy_name("Docker"), "Docker");
+        for (_, new) in LEGACY_NAMES {
+            assert!(find_by_name(new).is_some());
+        }
+    }
+}
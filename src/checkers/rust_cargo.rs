@@ -1,16 +1,45 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
-use walkdir::WalkDir;
+use super::{Checker, ProjectKind, ScanContext};
+use crate::types::{CheckResult, CleanupAction, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
+use std::path::PathBuf;
 
-pub fn check_rust() -> CheckResult {
-    let mut result = CheckResult::new("Rust/Cargo");
+/// Registry entry for the Rust/Cargo checker
+pub struct RustChecker;
 
-    let home = match home_dir() {
+impl Checker for RustChecker {
+    fn id(&self) -> &'static str {
+        "rust"
+    }
+
+    fn name(&self) -> &'static str {
+        "Rust/Cargo"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::PackageManagers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_rust(ctx)
+    }
+}
+
+pub fn check_rust(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(RustChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
 
-    let cargo_home = home.join(".cargo");
+    let cargo_home = ctx
+        .env_var("CARGO_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".cargo"));
     if !cargo_home.exists() {
         result.status = Some("Cargo not installed".to_string());
         return result;
@@ -19,9 +48,9 @@ pub fn check_rust() -> CheckResult {
     // Cargo registry cache
     let registry_cache = cargo_home.join("registry/cache");
     if registry_cache.exists() {
-        let size = get_dir_size(&registry_cache);
-        if size > 0 {
-            let item = CleanupItem::new("Cargo registry cache", size, &format_size(size))
+        let usage = ctx.disk_usage(&registry_cache);
+        if usage.apparent > 0 {
+            let item = CleanupItem::from_usage("Cargo registry cache", usage)
                 .with_path(registry_cache)
                 .with_safe_to_delete(true);
             result.add_item(item);
@@ -31,43 +60,32 @@ pub fn check_rust() -> CheckResult {
     // Cargo git checkouts
     let git_db = cargo_home.join("git");
     if git_db.exists() {
-        let size = get_dir_size(&git_db);
-        if size > 0 {
-            let item = CleanupItem::new("Cargo git checkouts", size, &format_size(size))
+        let usage = ctx.disk_usage(&git_db);
+        if usage.apparent > 0 {
+            let item = CleanupItem::from_usage("Cargo git checkouts", usage)
                 .with_path(git_db)
                 .with_safe_to_delete(true);
             result.add_item(item);
         }
     }
 
-    // Find target directories in common project locations
-    let search_paths = [
-        home.join("Projects"),
-        home.join("Developer"),
-        home.join("Code"),
-        home.join("Documents"),
-    ];
-
-    let mut target_dirs: Vec<(String, std::path::PathBuf, u64)> = Vec::new();
-
-    for search_path in search_paths.iter().filter(|p| p.exists()) {
-        for entry in WalkDir::new(search_path)
-            .max_depth(5)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-
-            // Check if this is a Rust project with a target directory
-            if path.is_dir() && path.join("Cargo.toml").exists() {
-                let target_path = path.join("target");
-                if target_path.exists() {
-                    let size = get_dir_size(&target_path);
-                    if size > 100 * 1024 * 1024 {
-                        // > 100MB
-                        target_dirs.push((path.to_string_lossy().to_string(), target_path, size));
-                    }
-                }
+    // Find target directories in the discovered Cargo projects
+    let mut target_dirs: Vec<(String, PathBuf, u64)> = Vec::new();
+
+    for project in ctx.projects().of_kind(ProjectKind::Cargo) {
+        if ctx.is_cancelled() {
+            break;
+        }
+        let target_path = project.path.join("target");
+        if target_path.is_dir() {
+            let size = ctx.dir_size(&target_path);
+            if size > 100 * 1024 * 1024 {
+                // > 100MB
+                target_dirs.push((
+                    project.path.to_string_lossy().to_string(),
+                    target_path,
+                    size,
+                ));
             }
         }
     }
@@ -95,7 +113,10 @@ pub fn check_rust() -> CheckResult {
         )
         .with_safe_to_delete(true)
         .with_warning("Cleans artifacts older than 30 days, keeps recent builds")
-        .with_cleanup_command(&format!("cargo sweep --time 30 {}", project_path));
+        .with_cleanup_action(CleanupAction::command(
+            "cargo",
+            ["sweep", "--time", "30", &project_path],
+        ));
         result.add_item(sweep_item);
     }
 
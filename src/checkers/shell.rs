@@ -1,15 +1,41 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 
-pub fn check_shell_caches() -> CheckResult {
-    let mut result = CheckResult::new("Shell Caches");
+/// Registry entry for the shell cache checker
+pub struct ShellChecker;
 
-    if let Some(home) = home_dir() {
+impl Checker for ShellChecker {
+    fn id(&self) -> &'static str {
+        "shell"
+    }
+
+    fn name(&self) -> &'static str {
+        "Shell Caches"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::SystemAndBrowsers
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_shell_caches(ctx)
+    }
+}
+
+pub fn check_shell_caches(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(ShellChecker.name());
+
+    if let Some(home) = &ctx.home {
         // Zsh
         // Oh My Zsh Cache
         let omz_cache = home.join(".oh-my-zsh/cache");
         if omz_cache.exists() {
-            let size = get_dir_size(&omz_cache);
+            let size = ctx.dir_size(&omz_cache);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Oh My Zsh Cache", size, &format_size(size))
@@ -20,12 +46,12 @@ pub fn check_shell_caches() -> CheckResult {
         }
 
         // Zsh completion dumps (safe to regenerate)
-        if let Ok(entries) = std::fs::read_dir(&home) {
+        if let Ok(entries) = std::fs::read_dir(home) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     if name.starts_with(".zcompdump") {
-                        let size = get_dir_size(&path);
+                        let size = ctx.dir_size(&path);
                         if size > 0 {
                             result.add_item(
                                 CleanupItem::new(
@@ -45,7 +71,7 @@ pub fn check_shell_caches() -> CheckResult {
         // Zsh sessions/history (optional, might be sensitive)
         let zsh_sessions = home.join(".zsh_sessions");
         if zsh_sessions.exists() {
-            let size = get_dir_size(&zsh_sessions);
+            let size = ctx.dir_size(&zsh_sessions);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Zsh Sessions", size, &format_size(size))
@@ -59,7 +85,7 @@ pub fn check_shell_caches() -> CheckResult {
         // Bash sessions/history often in .bash_sessions on macOS
         let bash_sessions = home.join(".bash_sessions");
         if bash_sessions.exists() {
-            let size = get_dir_size(&bash_sessions);
+            let size = ctx.dir_size(&bash_sessions);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Bash Sessions", size, &format_size(size))
@@ -73,7 +99,7 @@ pub fn check_shell_caches() -> CheckResult {
         // Fish cache is typically in ~/.config/fish/cache or ~/.cache/fish
         let fish_cache_config = home.join(".config/fish/cache");
         if fish_cache_config.exists() {
-            let size = get_dir_size(&fish_cache_config);
+            let size = ctx.dir_size(&fish_cache_config);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Fish Shell Cache (.config)", size, &format_size(size))
@@ -85,7 +111,7 @@ pub fn check_shell_caches() -> CheckResult {
 
         let fish_cache_local = home.join(".cache/fish");
         if fish_cache_local.exists() {
-            let size = get_dir_size(&fish_cache_local);
+            let size = ctx.dir_size(&fish_cache_local);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Fish Shell Cache (.cache)", size, &format_size(size))
@@ -98,7 +124,7 @@ pub fn check_shell_caches() -> CheckResult {
         // Starship Prompt Cache (common cross-shell prompt)
         let starship_cache = home.join(".cache/starship");
         if starship_cache.exists() {
-            let size = get_dir_size(&starship_cache);
+            let size = ctx.dir_size(&starship_cache);
             if size > 0 {
                 result.add_item(
                     CleanupItem::new("Starship Prompt Cache", size, &format_size(size))
@@ -1,10 +1,36 @@
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::{format_size, get_dir_size, home_dir};
+use super::{Checker, ScanContext};
+use crate::types::{CheckResult, CleanupAction, CleanupItem, SuperCategoryType};
+use crate::utils::format_size;
 
-pub fn check_xcode() -> CheckResult {
-    let mut result = CheckResult::new("Xcode");
+/// Registry entry for the Xcode checker
+pub struct XcodeChecker;
 
-    let home = match home_dir() {
+impl Checker for XcodeChecker {
+    fn id(&self) -> &'static str {
+        "xcode"
+    }
+
+    fn name(&self) -> &'static str {
+        "Xcode"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::DevelopmentTools
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_xcode(ctx)
+    }
+}
+
+pub fn check_xcode(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(XcodeChecker.name());
+
+    let home = match &ctx.home {
         Some(h) => h,
         None => return result,
     };
@@ -12,7 +38,7 @@ pub fn check_xcode() -> CheckResult {
     // Derived Data
     let derived_data = home.join("Library/Developer/Xcode/DerivedData");
     if derived_data.exists() {
-        let size = get_dir_size(&derived_data);
+        let size = ctx.dir_size(&derived_data);
         if size > 0 {
             let item = CleanupItem::new("Derived Data", size, &format_size(size))
                 .with_path(derived_data)
@@ -24,7 +50,7 @@ pub fn check_xcode() -> CheckResult {
     // Archives
     let archives = home.join("Library/Developer/Xcode/Archives");
     if archives.exists() {
-        let size = get_dir_size(&archives);
+        let size = ctx.dir_size(&archives);
         if size > 0 {
             let item = CleanupItem::new("Archives", size, &format_size(size))
                 .with_path(archives)
@@ -36,7 +62,7 @@ pub fn check_xcode() -> CheckResult {
     // iOS Device Support
     let device_support = home.join("Library/Developer/Xcode/iOS DeviceSupport");
     if device_support.exists() {
-        let size = get_dir_size(&device_support);
+        let size = ctx.dir_size(&device_support);
         if size > 0 {
             let item = CleanupItem::new("iOS Device Support", size, &format_size(size))
                 .with_path(device_support)
@@ -48,7 +74,7 @@ pub fn check_xcode() -> CheckResult {
     // watchOS Device Support
     let watch_support = home.join("Library/Developer/Xcode/watchOS DeviceSupport");
     if watch_support.exists() {
-        let size = get_dir_size(&watch_support);
+        let size = ctx.dir_size(&watch_support);
         if size > 0 {
             let item = CleanupItem::new("watchOS Device Support", size, &format_size(size))
                 .with_path(watch_support)
@@ -60,12 +86,15 @@ pub fn check_xcode() -> CheckResult {
     // Simulator devices
     let simulators = home.join("Library/Developer/CoreSimulator/Devices");
     if simulators.exists() {
-        let size = get_dir_size(&simulators);
+        let size = ctx.dir_size(&simulators);
         if size > 0 {
             let item = CleanupItem::new("Simulator Devices", size, &format_size(size))
                 .with_path(simulators)
                 .with_warning("Use 'xcrun simctl delete unavailable' to clean old simulators")
-                .with_cleanup_command("xcrun simctl delete unavailable");
+                .with_cleanup_action(CleanupAction::command(
+                    "xcrun",
+                    ["simctl", "delete", "unavailable"],
+                ));
             result.add_item(item);
         }
     }
@@ -73,7 +102,7 @@ pub fn check_xcode() -> CheckResult {
     // Caches
     let xcode_caches = home.join("Library/Developer/Xcode/Caches");
     if xcode_caches.exists() {
-        let size = get_dir_size(&xcode_caches);
+        let size = ctx.dir_size(&xcode_caches);
         if size > 0 {
             let item = CleanupItem::new("Xcode Caches", size, &format_size(size))
                 .with_path(xcode_caches)
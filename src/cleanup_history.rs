@@ -1,8 +1,11 @@
+use crate::deletion_policy::DeletionPolicy;
+use crate::disk_usage::disk_usage;
+use crate::error::DevSweepError;
 use crate::types::CleanupItem;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 /// Maximum number of cleanup operations to keep in history
@@ -21,6 +24,12 @@ pub struct CleanupRecord {
     pub success_count: usize,
     pub error_count: usize,
     pub can_undo: bool,
+    /// The cleanup was cancelled before every selected item was processed
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Selected items left untouched because of the cancellation
+    #[serde(default)]
+    pub skipped_count: usize,
 }
 
 impl CleanupRecord {
@@ -33,9 +42,17 @@ impl CleanupRecord {
             success_count: 0,
             error_count: 0,
             can_undo: true,
+            cancelled: false,
+            skipped_count: 0,
         }
     }
 
+    /// Record that the cleanup stopped with `skipped` items left untouched
+    pub fn mark_cancelled(&mut self, skipped: usize) {
+        self.cancelled = true;
+        self.skipped_count = skipped;
+    }
+
     pub fn add_item(&mut self, item: CleanupItemRecord) {
         self.total_size += item.size;
         if item.success {
@@ -49,6 +66,45 @@ impl CleanupRecord {
     pub fn is_undoable(&self) -> bool {
         self.can_undo && self.success_count > 0
     }
+
+    /// One-line outcome, e.g. "Cleaned 3 items with 1 error"
+    pub fn summary(&self) -> String {
+        if self.cancelled {
+            format!(
+                "Cancelled after cleaning {} items ({} errors, {} not processed)",
+                self.success_count, self.error_count, self.skipped_count
+            )
+        } else if self.error_count == 0 {
+            format!("Successfully cleaned {} items", self.success_count)
+        } else {
+            format!(
+                "Cleaned {} items with {} errors",
+                self.success_count, self.error_count
+            )
+        }
+    }
+
+    /// Items that failed, with their errors
+    pub fn failures(&self) -> impl Iterator<Item = (&CleanupItemRecord, &DevSweepError)> {
+        self.items
+            .iter()
+            .filter_map(|item| item.error.as_ref().map(|e| (item, e)))
+    }
+
+    /// Of the `items` this cleanup was run with, those that failed in a way
+    /// that deleting them permanently may fix
+    pub fn permanent_retry_items(&self, items: &[CleanupItem]) -> Vec<CleanupItem> {
+        items
+            .iter()
+            .filter(|item| {
+                self.failures().any(|(record, error)| {
+                    error.can_retry_permanently()
+                        && item.path.as_ref() == Some(&record.original_path)
+                })
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 /// Record of a single cleaned item
@@ -59,7 +115,10 @@ pub struct CleanupItemRecord {
     pub quarantine_path: Option<PathBuf>,
     pub size: u64,
     pub success: bool,
+    /// Human-readable error, kept for history files written by older releases
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub error: Option<DevSweepError>,
     pub deleted_permanently: bool,
 }
 
@@ -72,18 +131,21 @@ impl CleanupItemRecord {
             size: item.size,
             success: true,
             error_message: None,
+            error: None,
             deleted_permanently: quarantine_path.is_none(),
         }
     }
 
-    pub fn error(item: &CleanupItem, error: String) -> Self {
+    pub fn error(item: &CleanupItem, error: impl Into<DevSweepError>) -> Self {
+        let error = error.into();
         Self {
             item_type: item.item_type.clone(),
             original_path: item.path.clone().unwrap_or_default(),
             quarantine_path: None,
             size: item.size,
             success: false,
-            error_message: Some(error),
+            error_message: Some(error.to_string()),
+            error: Some(error),
             deleted_permanently: false,
         }
     }
@@ -128,20 +190,19 @@ impl CleanupHistory {
     }
 
     /// Save history to disk
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<(), DevSweepError> {
         let history_file = Self::history_file_path();
 
         // Ensure parent directory exists
         if let Some(parent) = history_file.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create history directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
         }
 
-        let json = serde_json::to_string_pretty(&self.records)
-            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+        let json = serde_json::to_string_pretty(&self.records).map_err(|e| {
+            DevSweepError::serialization(format!("Failed to serialize history: {}", e))
+        })?;
 
-        fs::write(&history_file, json)
-            .map_err(|e| format!("Failed to write history file: {}", e))?;
+        fs::write(&history_file, json).map_err(|e| DevSweepError::from_io(&e, &history_file))?;
 
         Ok(())
     }
@@ -154,6 +215,11 @@ impl CleanupHistory {
             .join("cleanup_history.json")
     }
 
+    /// Where quarantined items are moved to
+    pub fn quarantine_dir(&self) -> &Path {
+        &self.quarantine_dir
+    }
+
     /// Get the quarantine directory path
     fn get_quarantine_dir() -> PathBuf {
         let cache_dir = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
@@ -186,16 +252,21 @@ impl CleanupHistory {
         self.records.iter().find(|r| r.id == id)
     }
 
-    /// Move item to quarantine instead of deleting permanently
-    pub fn quarantine_item(&self, item: &CleanupItem) -> Result<PathBuf, String> {
-        let original_path = item
-            .path
-            .as_ref()
-            .ok_or_else(|| "Item has no path".to_string())?;
+    /// Move item to quarantine instead of deleting permanently, if `policy`
+    /// allows deleting it
+    pub fn quarantine_item(
+        &self,
+        item: &CleanupItem,
+        policy: &DeletionPolicy,
+    ) -> Result<PathBuf, DevSweepError> {
+        let original_path = item.path.as_ref().ok_or(DevSweepError::NoCleanupAction)?;
 
         if !original_path.exists() {
-            return Err(format!("Path does not exist: {}", original_path.display()));
+            return Err(DevSweepError::NotFound {
+                path: original_path.clone(),
+            });
         }
+        policy.check(original_path)?;
 
         // Create unique quarantine path using timestamp and original filename
         let timestamp = SystemTime::now()
@@ -214,60 +285,64 @@ impl CleanupHistory {
 
         // Ensure quarantine directory exists
         fs::create_dir_all(&self.quarantine_dir)
-            .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+            .map_err(|e| DevSweepError::from_io(&e, &self.quarantine_dir))?;
 
         // Move to quarantine
         fs::rename(original_path, &quarantine_path)
-            .map_err(|e| format!("Failed to move to quarantine: {}", e))?;
+            .map_err(|e| DevSweepError::from_io(&e, original_path))?;
 
         Ok(quarantine_path)
     }
 
     /// Restore an item from quarantine
-    pub fn restore_item(&self, record: &CleanupItemRecord) -> Result<String, String> {
+    pub fn restore_item(&self, record: &CleanupItemRecord) -> Result<String, DevSweepError> {
         if !record.can_restore() {
-            return Err("Item cannot be restored".to_string());
+            return Err(DevSweepError::NotInQuarantine);
         }
 
         let quarantine_path = record
             .quarantine_path
             .as_ref()
-            .ok_or_else(|| "No quarantine path".to_string())?;
+            .ok_or(DevSweepError::NotInQuarantine)?;
 
         if !quarantine_path.exists() {
-            return Err("Quarantined file no longer exists".to_string());
+            return Err(DevSweepError::QuarantineMissing {
+                path: quarantine_path.clone(),
+            });
         }
 
         // Check if original location is available
         if record.original_path.exists() {
-            return Err(format!(
-                "Original location already exists: {}",
-                record.original_path.display()
-            ));
+            return Err(DevSweepError::AlreadyExists {
+                path: record.original_path.clone(),
+            });
         }
 
         // Ensure parent directory exists
         if let Some(parent) = record.original_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
         }
 
         // Move back from quarantine
         fs::rename(quarantine_path, &record.original_path)
-            .map_err(|e| format!("Failed to restore from quarantine: {}", e))?;
+            .map_err(|e| DevSweepError::from_io(&e, &record.original_path))?;
 
         Ok(format!("Restored: {}", record.original_path.display()))
     }
 
     /// Undo a cleanup operation
-    pub fn undo(&mut self, record_id: &str) -> Result<UndoResult, String> {
+    pub fn undo(&mut self, record_id: &str) -> Result<UndoResult, DevSweepError> {
         let record = self
             .get_record(record_id)
-            .ok_or_else(|| "Record not found".to_string())?
+            .ok_or_else(|| DevSweepError::RecordNotFound {
+                record_id: record_id.to_string(),
+            })?
             .clone();
 
         if !record.is_undoable() {
-            return Err("This cleanup cannot be undone".to_string());
+            return Err(DevSweepError::NotUndoable {
+                record_id: record_id.to_string(),
+            });
         }
 
         let mut success_count = 0;
@@ -279,12 +354,12 @@ impl CleanupHistory {
                 match self.restore_item(item) {
                     Ok(msg) => {
                         success_count += 1;
-                        println!("✓ {}", msg);
+                        eprintln!("✓ {}", msg);
                     }
                     Err(e) => {
                         error_count += 1;
-                        errors.push(format!("{}: {}", item.item_type, e));
-                        eprintln!("✗ {}", e);
+                        eprintln!("✗ {}: {}", item.item_type, e);
+                        errors.push(e);
                     }
                 }
             }
@@ -305,16 +380,16 @@ impl CleanupHistory {
     }
 
     /// Clear all history and quarantine
-    pub fn clear_all(&mut self) -> Result<(), String> {
+    pub fn clear_all(&mut self) -> Result<(), DevSweepError> {
         // Remove all quarantined files
         if self.quarantine_dir.exists() {
             fs::remove_dir_all(&self.quarantine_dir)
-                .map_err(|e| format!("Failed to remove quarantine directory: {}", e))?;
+                .map_err(|e| DevSweepError::from_io(&e, &self.quarantine_dir))?;
         }
 
         // Recreate empty quarantine directory
         fs::create_dir_all(&self.quarantine_dir)
-            .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+            .map_err(|e| DevSweepError::from_io(&e, &self.quarantine_dir))?;
 
         // Clear records
         self.records.clear();
@@ -339,23 +414,13 @@ impl CleanupHistory {
         }
     }
 
-    /// Get total size of quarantine directory
+    /// Get the space the quarantine directory takes up on disk
     fn get_quarantine_size(&self) -> u64 {
-        if !self.quarantine_dir.exists() {
-            return 0;
-        }
-
-        walkdir::WalkDir::new(&self.quarantine_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter_map(|e| e.metadata().ok())
-            .filter(|m| m.is_file())
-            .map(|m| m.len())
-            .sum()
+        disk_usage(&self.quarantine_dir).allocated
     }
 
     /// Clean up old quarantine files if size exceeds limit
-    fn cleanup_old_quarantine_if_needed(&mut self) -> Result<(), String> {
+    fn cleanup_old_quarantine_if_needed(&mut self) -> Result<(), DevSweepError> {
         let current_size = self.get_quarantine_size();
 
         if current_size > MAX_QUARANTINE_SIZE {
@@ -378,17 +443,22 @@ impl CleanupHistory {
         &mut self,
         record_id: &str,
         item_index: usize,
-    ) -> Result<String, String> {
+    ) -> Result<String, DevSweepError> {
         // Find the record
         let record = self
             .records
             .iter_mut()
             .find(|r| r.id == record_id)
-            .ok_or_else(|| "Record not found".to_string())?;
+            .ok_or_else(|| DevSweepError::RecordNotFound {
+                record_id: record_id.to_string(),
+            })?;
 
         // Check if item index is valid
         if item_index >= record.items.len() {
-            return Err("Item index out of bounds".to_string());
+            return Err(DevSweepError::ItemNotFound {
+                record_id: record_id.to_string(),
+                index: item_index,
+            });
         }
 
         let item = &record.items[item_index];
@@ -407,7 +477,7 @@ impl CleanupHistory {
                     fs::remove_file(&qpath)
                 };
 
-                result.map_err(|e| format!("Failed to delete quarantined item: {}", e))?;
+                result.map_err(|e| DevSweepError::from_io(&e, &qpath))?;
 
                 // Mark the item as deleted permanently
                 let item_mut = &mut record.items[item_index];
@@ -418,10 +488,10 @@ impl CleanupHistory {
 
                 Ok(format!("Deleted: {}", item_type))
             } else {
-                Err("Quarantined file no longer exists".to_string())
+                Err(DevSweepError::QuarantineMissing { path: qpath })
             }
         } else {
-            Err("Item is not in quarantine".to_string())
+            Err(DevSweepError::NotInQuarantine)
         }
     }
 
@@ -451,8 +521,21 @@ impl Default for CleanupHistory {
 pub struct UndoResult {
     pub success_count: usize,
     pub error_count: usize,
-    #[allow(dead_code)]
-    pub errors: Vec<String>,
+    pub errors: Vec<DevSweepError>,
+}
+
+impl UndoResult {
+    /// One-line outcome, e.g. "Restored 2 items with 1 error"
+    pub fn summary(&self) -> String {
+        if self.error_count == 0 {
+            format!("Successfully restored {} items", self.success_count)
+        } else {
+            format!(
+                "Restored {} items with {} errors",
+                self.success_count, self.error_count
+            )
+        }
+    }
 }
 
 /// Statistics about cleanup history
@@ -0,0 +1,335 @@
+//! Cleanup plans
+//!
+//! A plan resolves a selection of `CleanupItem`s into the operations a cleanup
+//! would perform: which paths are quarantined, which are deleted permanently
+//! and which commands are run. It can be printed as a dry run, saved to a file
+//! for someone else to review, and applied later with
+//! `StorageBackend::apply_plan`, which first checks that every path still
+//! looks the way it did when the plan was made.
+//! Bump `PLAN_VERSION` whenever a field is renamed or removed.
+
+use crate::error::DevSweepError;
+use crate::overlap;
+use crate::report::timestamp;
+use crate::types::CleanupItem;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the plan file layout
+pub const PLAN_VERSION: u32 = 2;
+
+/// How an item will be cleaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupMode {
+    /// Moved to quarantine; can be undone
+    Quarantine,
+    /// Deleted without going through quarantine; cannot be undone
+    Permanent,
+    /// Its `cleanup_action` is run; cannot be undone
+    Command,
+}
+
+impl CleanupMode {
+    /// The mode a cleanup uses for `item`
+    ///
+    /// Items with a cleanup action always run it, and the Trash is emptied in
+    /// place rather than moved into quarantine.
+    pub fn for_item(item: &CleanupItem, use_quarantine: bool) -> Self {
+        if item.cleanup_action.is_some() {
+            Self::Command
+        } else if use_quarantine && item.path.as_ref().is_some_and(|p| !p.ends_with(".Trash")) {
+            Self::Quarantine
+        } else {
+            Self::Permanent
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Quarantine => "quarantine",
+            Self::Permanent => "permanent",
+            Self::Command => "command",
+        }
+    }
+
+    pub fn is_undoable(&self) -> bool {
+        matches!(self, Self::Quarantine)
+    }
+}
+
+/// What a path looked like when the plan was made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathFingerprint {
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// File length in bytes (not the size of a directory's contents)
+    pub len: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+}
+
+impl PathFingerprint {
+    /// Fingerprint `path` without following symlinks; `None` if it doesn't exist
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        Some(Self {
+            is_dir: metadata.is_dir(),
+            is_symlink: metadata.file_type().is_symlink(),
+            len: metadata.len(),
+            modified_ms: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64),
+        })
+    }
+
+    /// How `current` differs from this fingerprint, if it does
+    ///
+    /// A directory's modification time changes when entries are added to or
+    /// removed from it directly; changes deeper in the tree are not detected.
+    fn difference(&self, current: &Self) -> Option<&'static str> {
+        if self.is_symlink != current.is_symlink {
+            Some("it was replaced by or with a symlink")
+        } else if self.is_dir != current.is_dir {
+            Some("it changed between file and directory")
+        } else if !self.is_dir && self.len != current.len {
+            Some("its size changed")
+        } else if self.modified_ms != current.modified_ms {
+            Some("it was modified")
+        } else {
+            None
+        }
+    }
+}
+
+/// One planned cleanup operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedOperation {
+    pub mode: CleanupMode,
+    /// Everything the reviewer should know before approving the operation
+    pub warnings: Vec<String>,
+    /// The path as it was when the plan was made, if the item has one
+    pub fingerprint: Option<PathFingerprint>,
+    #[serde(flatten)]
+    pub item: CleanupItem,
+}
+
+impl PlannedOperation {
+    pub fn new(item: &CleanupItem, use_quarantine: bool) -> Self {
+        let mode = CleanupMode::for_item(item, use_quarantine);
+
+        let mut warnings = Vec::new();
+        match mode {
+            CleanupMode::Command => warnings.push("Runs a command; cannot be undone".into()),
+            CleanupMode::Permanent if use_quarantine => {
+                warnings.push("Bypasses quarantine; cannot be undone".into())
+            }
+            CleanupMode::Permanent => warnings.push("Deleted permanently".into()),
+            CleanupMode::Quarantine => {}
+        }
+        if !item.safe_to_delete {
+            warnings.push("Not marked as safe to delete".into());
+        }
+        if let Some(warning) = &item.warning {
+            warnings.push(warning.clone());
+        }
+        if let Some(in_use) = &item.in_use {
+            warnings.push(in_use.to_string());
+        }
+
+        Self {
+            mode,
+            warnings,
+            fingerprint: item.path.as_deref().and_then(PathFingerprint::of),
+            item: item.clone(),
+        }
+    }
+
+    /// Bytes this operation is expected to free
+    pub fn expected_bytes(&self) -> u64 {
+        self.item.size
+    }
+
+    /// The command that will run, or the path that will be removed
+    pub fn target(&self) -> String {
+        match (&self.item.cleanup_action, &self.item.path) {
+            (Some(action), _) => format!("$ {}", action),
+            (None, Some(path)) => path.display().to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Check that the item's path still matches the plan
+    pub fn verify(&self) -> Result<(), DevSweepError> {
+        let (Some(path), Some(expected)) = (&self.item.path, &self.fingerprint) else {
+            return Ok(());
+        };
+        let current = PathFingerprint::of(path)
+            .ok_or_else(|| DevSweepError::NotFound { path: path.clone() })?;
+        match expected.difference(&current) {
+            None => Ok(()),
+            Some(reason) => Err(DevSweepError::PlanMismatch {
+                path: path.clone(),
+                reason: reason.to_string(),
+            }),
+        }
+    }
+}
+
+/// The items cleaning `item` consists of: its details, when it is a group of
+/// paths reported as one item (e.g. a workspace's node_modules), else itself
+fn expand_group(item: &CleanupItem) -> Vec<CleanupItem> {
+    let details = match (item.is_group, &item.details) {
+        (true, Some(details)) => details,
+        _ => return vec![item.clone()],
+    };
+    details
+        .iter()
+        .filter_map(|detail| {
+            let mut part = CleanupItem::new(
+                &format!("{}: {}", item.item_type, detail.name),
+                detail.size,
+                &detail.size_str,
+            )
+            .with_path(detail.path.clone()?)
+            .with_safe_to_delete(item.safe_to_delete);
+            part.warning = item.warning.clone();
+            part.is_estimate = item.is_estimate;
+            Some(part)
+        })
+        .collect()
+}
+
+/// A selection resolved into cleanup operations, ready to review or apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupPlan {
+    pub plan_version: u32,
+    pub devsweep_version: String,
+    #[serde(with = "timestamp")]
+    pub created_at: SystemTime,
+    pub use_quarantine: bool,
+    pub operations: Vec<PlannedOperation>,
+}
+
+impl CleanupPlan {
+    /// Plan the cleanup of `items`, in order
+    ///
+    /// A group of paths (`CleanupItem::is_group`) is planned as one operation
+    /// per detail. Items inside another selected
+    /// item's path are left out; cleaning the outer item already removes them.
+    pub fn new(items: &[CleanupItem], use_quarantine: bool) -> Self {
+        let mut items: Vec<CleanupItem> = items.iter().flat_map(expand_group).collect();
+        overlap::drop_covered(&mut items);
+        Self {
+            plan_version: PLAN_VERSION,
+            devsweep_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: SystemTime::now(),
+            use_quarantine,
+            operations: items
+                .iter()
+                .map(|item| PlannedOperation::new(item, use_quarantine))
+                .collect(),
+        }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.operations.iter().map(|op| op.expected_bytes()).sum()
+    }
+
+    /// Number of operations using `mode`
+    pub fn count(&self, mode: CleanupMode) -> usize {
+        self.operations.iter().filter(|op| op.mode == mode).count()
+    }
+
+    /// Operations whose path no longer matches the plan, with the reason
+    pub fn verify(&self) -> Vec<(&PlannedOperation, DevSweepError)> {
+        self.operations
+            .iter()
+            .filter_map(|op| op.verify().err().map(|e| (op, e)))
+            .collect()
+    }
+
+    /// One-line description, e.g. "3 operations (1.2 GB): 2 quarantined, 1 command"
+    pub fn summary(&self) -> String {
+        let counts: Vec<String> = [
+            (CleanupMode::Quarantine, "quarantined"),
+            (CleanupMode::Permanent, "deleted permanently"),
+            (CleanupMode::Command, "commands"),
+        ]
+        .iter()
+        .filter_map(|(mode, label)| match self.count(*mode) {
+            0 => None,
+            n => Some(format!("{} {}", n, label)),
+        })
+        .collect();
+
+        format!(
+            "{} operations ({}): {}",
+            self.operations.len(),
+            crate::utils::format_size(self.total_bytes()),
+            if counts.is_empty() {
+                "nothing to do".to_string()
+            } else {
+                counts.join(", ")
+            }
+        )
+    }
+
+    /// Write the plan as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<(), DevSweepError> {
+        let json = serde_json::to_string_pretty(self).map_err(DevSweepError::serialization)?;
+        fs::write(path, json).map_err(|e| DevSweepError::from_io(&e, path))
+    }
+
+    /// Read a plan written by `save`
+    pub fn load(path: &Path) -> Result<Self, DevSweepError> {
+        let json = fs::read_to_string(path).map_err(|e| DevSweepError::from_io(&e, path))?;
+        let plan: Self = serde_json::from_str(&json).map_err(|e| {
+            DevSweepError::serialization(format!("Invalid plan {}: {}", path.display(), e))
+        })?;
+        if plan.plan_version > PLAN_VERSION {
+            return Err(DevSweepError::InvalidInput {
+                message: format!(
+                    "Plan version {} is newer than this DevSweep supports ({})",
+                    plan.plan_version, PLAN_VERSION
+                ),
+            });
+        }
+        // Older plans describe commands in a form this version no longer
+        // reads; applying one could delete a path instead of running its command
+        if plan.plan_version < PLAN_VERSION {
+            return Err(DevSweepError::InvalidInput {
+                message: format!(
+                    "Plan version {} is older than this DevSweep supports ({}); make the plan again",
+                    plan.plan_version, PLAN_VERSION
+                ),
+            });
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CleanupAction;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_mode_for_item() {
+        let path = CleanupItem::new("cache", 1, "1 B").with_path(PathBuf::from("/tmp/cache"));
+        let trash =
+            CleanupItem::new("Trash", 1, "1 B").with_path(PathBuf::from("/Users/me/.Trash"));
+        let command = CleanupItem::new("images", 1, "1 B")
+            .with_cleanup_action(CleanupAction::command("docker", ["image", "prune"]));
+
+        assert_eq!(CleanupMode::for_item(&path, true), CleanupMode::Quarantine);
+        assert_eq!(CleanupMode::for_item(&path, false), CleanupMode::Permanent);
+        assert_eq!(CleanupMode::for_item(&trash, true), CleanupMode::Permanent);
+        assert_eq!(CleanupMode::for_item(&command, true), CleanupMode::Command);
+    }
+}
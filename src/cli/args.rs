@@ -0,0 +1,336 @@
+//! Command-line argument parsing
+
+use crate::report::ReportFormat;
+use std::path::PathBuf;
+
+/// A parsed command-line invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Scan and list reclaimable items, optionally filtered by selectors
+    Scan {
+        selectors: Vec<String>,
+        use_cache: bool,
+        /// Machine-readable report format; a table when unset
+        format: Option<ReportFormat>,
+        /// Scan in low-impact mode regardless of the saved setting
+        background: bool,
+    },
+    /// Clean the items matched by the selectors
+    Clean {
+        selectors: Vec<String>,
+        use_cache: bool,
+        permanent: bool,
+        yes: bool,
+        /// Print the cleanup plan without changing anything
+        dry_run: bool,
+        /// Write the cleanup plan to this file instead of cleaning
+        save_plan: Option<PathBuf>,
+        /// Scan in low-impact mode regardless of the saved setting
+        background: bool,
+    },
+    /// Apply a cleanup plan saved with `clean --save-plan`
+    Apply {
+        plan: PathBuf,
+        dry_run: bool,
+        yes: bool,
+    },
+    /// Restore a cleanup from quarantine (latest undoable one if no id is given)
+    Undo {
+        record_id: Option<String>,
+    },
+    /// List quarantine records and their items
+    QuarantineList,
+    /// Permanently delete quarantined items matched by the selectors
+    QuarantineDelete {
+        selectors: Vec<String>,
+        yes: bool,
+    },
+    /// Permanently delete everything in quarantine
+    QuarantineClear {
+        yes: bool,
+    },
+    Help,
+    Version,
+}
+
+pub const USAGE: &str = "\
+Usage: devsweep [COMMAND]
+
+Run without a command to open the DevSweep window.
+
+Commands:
+  scan [SELECTOR...]               List reclaimable items
+  clean SELECTOR...                Clean the selected items
+  apply PLAN_FILE                  Check a saved cleanup plan and apply it
+  undo [RECORD_ID]                 Restore a cleanup from quarantine (default: latest)
+  quarantine list                  List quarantined cleanups and their items
+  quarantine delete SELECTOR...    Permanently delete quarantined items
+  quarantine clear                 Permanently delete everything in quarantine
+  help                             Show this message
+  version                          Show the version
+
+Selectors:
+  A category id (e.g. docker, node_modules), an item id as shown by `scan`
+  (e.g. rust/2), or a glob over either (e.g. 'node*', 'xcode/*', '*').
+  Quarantined items are selected by RECORD_ID/N, or a glob such as 'cleanup_*/1'.
+
+Options:
+  --no-cache     Ignore cached scan results (scan, clean)
+  --background   Scan with a few low-priority threads and throttled disk reads (scan, clean)
+  --format FMT   Print a report instead of a table: json, ndjson or csv (scan)
+  --permanent    Delete instead of moving to quarantine (clean)
+  --dry-run      Show what would be done without changing anything (clean, apply)
+  --save-plan F  Save the cleanup plan to file F for review instead of cleaning (clean)
+  -y, --yes      Do not ask for confirmation (clean, apply, quarantine delete/clear)
+  -h, --help     Show this message
+  -V, --version  Show the version
+";
+
+/// Parse the arguments that follow the program name
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let mut positional = Vec::new();
+    let mut use_cache = true;
+    let mut permanent = false;
+    let mut yes = false;
+    let mut format = None;
+    let mut dry_run = false;
+    let mut save_plan = None;
+    let mut background = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--no-cache" => use_cache = false,
+            "--background" => background = true,
+            "--permanent" => permanent = true,
+            "-y" | "--yes" => yes = true,
+            "--dry-run" => dry_run = true,
+            "--save-plan" => {
+                let file = args
+                    .next()
+                    .ok_or_else(|| "--save-plan needs a file name".to_string())?;
+                save_plan = Some(PathBuf::from(file));
+            }
+            flag if flag.starts_with("--save-plan=") => {
+                save_plan = Some(PathBuf::from(&flag["--save-plan=".len()..]));
+            }
+            "--format" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| "--format needs a value: json, ndjson or csv".to_string())?;
+                format = Some(parse_format(name)?);
+            }
+            flag if flag.starts_with("--format=") => {
+                format = Some(parse_format(&flag["--format=".len()..])?);
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("Unknown option '{}'", flag));
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = positional.next().unwrap_or_else(|| "help".to_string());
+    let rest: Vec<String> = positional.collect();
+
+    let reject_flag = |name: &str, set: bool, command: &str| {
+        if set {
+            Err(format!("'{}' does not accept {}", command, name))
+        } else {
+            Ok(())
+        }
+    };
+
+    if command != "scan" {
+        reject_flag("--format", format.is_some(), &command)?;
+    }
+    if command != "clean" {
+        reject_flag("--save-plan", save_plan.is_some(), &command)?;
+    }
+    if command != "clean" && command != "apply" {
+        reject_flag("--dry-run", dry_run, &command)?;
+    }
+    if command != "scan" && command != "clean" {
+        reject_flag("--background", background, &command)?;
+    }
+
+    match command.as_str() {
+        "scan" => {
+            reject_flag("--permanent", permanent, "scan")?;
+            reject_flag("--yes", yes, "scan")?;
+            Ok(Command::Scan {
+                selectors: rest,
+                use_cache,
+                format,
+                background,
+            })
+        }
+        "clean" => {
+            if rest.is_empty() {
+                return Err("'clean' needs at least one selector (use '*' for everything)".into());
+            }
+            Ok(Command::Clean {
+                selectors: rest,
+                use_cache,
+                permanent,
+                yes,
+                dry_run,
+                save_plan,
+                background,
+            })
+        }
+        "apply" => {
+            reject_flag("--no-cache", !use_cache, "apply")?;
+            reject_flag("--permanent", permanent, "apply")?;
+            match rest.as_slice() {
+                [plan] => Ok(Command::Apply {
+                    plan: PathBuf::from(plan),
+                    dry_run,
+                    yes,
+                }),
+                _ => Err("'apply' needs exactly one plan file".into()),
+            }
+        }
+        "undo" => {
+            reject_flag("--no-cache", !use_cache, "undo")?;
+            reject_flag("--permanent", permanent, "undo")?;
+            if rest.len() > 1 {
+                return Err("'undo' takes at most one record id".into());
+            }
+            Ok(Command::Undo {
+                record_id: rest.into_iter().next(),
+            })
+        }
+        "quarantine" => {
+            reject_flag("--no-cache", !use_cache, "quarantine")?;
+            reject_flag("--permanent", permanent, "quarantine")?;
+            let (action, selectors) = rest
+                .split_first()
+                .ok_or_else(|| "'quarantine' needs one of: list, delete, clear".to_string())?;
+            match action.as_str() {
+                "list" if selectors.is_empty() => Ok(Command::QuarantineList),
+                "delete" if !selectors.is_empty() => Ok(Command::QuarantineDelete {
+                    selectors: selectors.to_vec(),
+                    yes,
+                }),
+                "delete" => Err("'quarantine delete' needs at least one selector".into()),
+                "clear" if selectors.is_empty() => Ok(Command::QuarantineClear { yes }),
+                "list" | "clear" => Err(format!("'quarantine {}' takes no arguments", action)),
+                other => Err(format!("Unknown quarantine action '{}'", other)),
+            }
+        }
+        "help" => Ok(Command::Help),
+        "version" => Ok(Command::Version),
+        other => Err(format!("Unknown command '{}'", other)),
+    }
+}
+
+fn parse_format(name: &str) -> Result<ReportFormat, String> {
+    ReportFormat::from_name(name)
+        .ok_or_else(|| format!("Unknown format '{}' (expected json, ndjson or csv)", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_scan_and_clean() {
+        assert_eq!(
+            parse(&args("scan --no-cache docker")).unwrap(),
+            Command::Scan {
+                selectors: vec!["docker".into()],
+                use_cache: false,
+                format: None,
+                background: false
+            }
+        );
+        assert_eq!(
+            parse(&args("scan --format ndjson")).unwrap(),
+            Command::Scan {
+                selectors: vec![],
+                use_cache: true,
+                format: Some(ReportFormat::Ndjson),
+                background: false
+            }
+        );
+        assert!(parse(&args("scan --format=xml")).is_err());
+        assert!(parse(&args("clean --format json docker")).is_err());
+        assert_eq!(
+            parse(&args("clean -y rust/1 node*")).unwrap(),
+            Command::Clean {
+                selectors: vec!["rust/1".into(), "node*".into()],
+                use_cache: true,
+                permanent: false,
+                yes: true,
+                dry_run: false,
+                save_plan: None,
+                background: false
+            }
+        );
+        assert_eq!(
+            parse(&args("clean --save-plan plan.json docker")).unwrap(),
+            Command::Clean {
+                selectors: vec!["docker".into()],
+                use_cache: true,
+                permanent: false,
+                yes: false,
+                dry_run: false,
+                save_plan: Some(PathBuf::from("plan.json")),
+                background: false
+            }
+        );
+        assert_eq!(
+            parse(&args("apply --dry-run plan.json")).unwrap(),
+            Command::Apply {
+                plan: PathBuf::from("plan.json"),
+                dry_run: true,
+                yes: false
+            }
+        );
+        assert_eq!(
+            parse(&args("scan --background")).unwrap(),
+            Command::Scan {
+                selectors: vec![],
+                use_cache: true,
+                format: None,
+                background: true
+            }
+        );
+        assert!(parse(&args("undo --background")).is_err());
+        assert!(parse(&args("apply")).is_err());
+        assert!(parse(&args("scan --dry-run")).is_err());
+        assert!(parse(&args("clean")).is_err());
+        assert!(parse(&args("scan --permanent")).is_err());
+    }
+
+    #[test]
+    fn test_parse_quarantine() {
+        assert_eq!(
+            parse(&args("quarantine list")).unwrap(),
+            Command::QuarantineList
+        );
+        assert_eq!(
+            parse(&args("quarantine clear --yes")).unwrap(),
+            Command::QuarantineClear { yes: true }
+        );
+        assert!(parse(&args("quarantine delete")).is_err());
+        assert!(parse(&args("quarantine purge")).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_and_help() {
+        assert_eq!(parse(&[]).unwrap(), Command::Help);
+        assert_eq!(parse(&args("scan --help")).unwrap(), Command::Help);
+        assert!(parse(&args("scan --bogus")).is_err());
+        assert!(parse(&args("frobnicate")).is_err());
+        assert!(parse(&args("undo a b")).is_err());
+    }
+}
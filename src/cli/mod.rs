@@ -0,0 +1,506 @@
+//! Headless command-line interface
+//!
+//! `devsweep scan`, `clean`, `apply`, `undo` and `quarantine` drive the same
+//! `StorageBackend` as the window, so DevSweep can be used over SSH, from
+//! scripts and on machines without a display.
+
+pub mod args;
+pub mod select;
+pub mod table;
+
+pub use args::{parse, Command, USAGE};
+
+use crate::backend::StorageBackend;
+use crate::cancel::CancellationToken;
+use crate::cleanup_history::{CleanupItemRecord, CleanupRecord};
+use crate::cleanup_plan::CleanupPlan;
+use crate::preflight::ItemChange;
+use crate::report::ScanReport;
+use crate::scan_mode::ScanMode;
+use crate::types::CleanupItem;
+use crate::utils::format_size;
+use select::ScannedItem;
+use std::io::{self, BufRead, Write};
+use std::time::SystemTime;
+use table::Table;
+
+/// Whether the process arguments ask for the CLI rather than the window.
+///
+/// macOS may pass a `-psn_*` process serial number when launching the app
+/// bundle from Finder; that still opens the window.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.first().is_some_and(|arg| !arg.starts_with("-psn_"))
+}
+
+/// Run the CLI with the arguments that follow the program name and return
+/// the process exit code (0 success, 1 failure, 2 usage error)
+pub fn run(args: &[String]) -> i32 {
+    let command = match parse(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("devsweep: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    // Not locked for the whole run: checkers print diagnostics from worker threads
+    let mut out = io::stdout();
+
+    let result = match command {
+        Command::Help => write(&mut out, USAGE),
+        Command::Version => write(
+            &mut out,
+            &format!("devsweep {}\n", env!("CARGO_PKG_VERSION")),
+        ),
+        command => {
+            let mut backend = StorageBackend::new();
+            let stdin = io::stdin();
+            let mut input = stdin.lock();
+            execute(command, &mut backend, &mut out, &mut input)
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("devsweep: {}", e);
+            1
+        }
+    }
+}
+
+/// Execute a parsed command against `backend`, writing tables to `out` and
+/// reading confirmations from `input`
+pub fn execute(
+    command: Command,
+    backend: &mut StorageBackend,
+    out: &mut dyn Write,
+    input: &mut dyn BufRead,
+) -> Result<(), String> {
+    match command {
+        Command::Scan {
+            selectors,
+            use_cache,
+            format: Some(format),
+            background,
+        } => {
+            use_background(backend, background);
+            let categories = backend.scan_with_cache(use_cache);
+            let categories = if selectors.is_empty() {
+                categories
+            } else {
+                select::select_categories(categories, &selectors)?
+            };
+            ScanReport::new(&categories).write(format, out)
+        }
+        Command::Scan {
+            selectors,
+            use_cache,
+            format: None,
+            background,
+        } => {
+            use_background(backend, background);
+            let items = scan(backend, &selectors, use_cache)?;
+            if items.is_empty() {
+                return write(out, "Nothing to clean.\n");
+            }
+            write(out, &items_table(&items).render())?;
+            write(
+                out,
+                &format!(
+                    "\n{} items, {} reclaimable\n",
+                    items.len(),
+                    format_size(total_size(&items))
+                ),
+            )
+        }
+        Command::Clean {
+            selectors,
+            use_cache,
+            permanent,
+            yes,
+            dry_run,
+            save_plan,
+            background,
+        } => {
+            use_background(backend, background);
+            let items = scan(backend, &selectors, use_cache)?;
+            if items.is_empty() {
+                return write(out, "Nothing to clean.\n");
+            }
+            // Cached results can be hours old; clean what is on disk now
+            let items = recheck(backend, items, out)?;
+            if items.is_empty() {
+                return write(out, "Nothing left to clean.\n");
+            }
+
+            if dry_run || save_plan.is_some() {
+                let items: Vec<CleanupItem> = items.into_iter().map(|i| i.item).collect();
+                let plan = CleanupPlan::new(&items, !permanent);
+                write(out, &plan_table(&plan).render())?;
+                write(out, &format!("\n{}\n", plan.summary()))?;
+                if let Some(path) = save_plan {
+                    plan.save(&path).map_err(|e| e.explain())?;
+                    write(
+                        out,
+                        &format!(
+                            "Plan saved to {}; apply it with `devsweep apply {}`\n",
+                            path.display(),
+                            path.display()
+                        ),
+                    )?;
+                }
+                return Ok(());
+            }
+
+            write(out, &items_table(&items).render())?;
+
+            let prompt = format!(
+                "\nClean {} items ({})? {}",
+                items.len(),
+                format_size(total_size(&items)),
+                if permanent {
+                    "They will be deleted permanently."
+                } else {
+                    "They will be moved to quarantine (restore with `devsweep undo`)."
+                }
+            );
+            if !yes && !confirm(out, input, &prompt)? {
+                return Err("Aborted".to_string());
+            }
+
+            let items: Vec<CleanupItem> = items.into_iter().map(|i| i.item).collect();
+            let record = backend.execute_cleanup_with_history(&items, !permanent);
+            if record.error_count == 0 {
+                return write(out, &format!("{}\n", record.summary()));
+            }
+            write(out, &format!("\n{}", failures_table(&record).render()))?;
+
+            // Quarantine is a rename, which can't cross filesystems
+            let retry = record.permanent_retry_items(&items);
+            if !retry.is_empty() {
+                let prompt = format!(
+                    "\n{} items could not be quarantined. Delete them permanently instead?",
+                    retry.len()
+                );
+                if !yes && confirm(out, input, &prompt)? {
+                    let retried = backend.execute_cleanup_with_history(&retry, false);
+                    write(out, &format!("{}\n", retried.summary()))?;
+                    if retried.error_count == 0 && retried.success_count == record.error_count {
+                        return Ok(());
+                    }
+                } else if yes {
+                    write(
+                        out,
+                        &format!(
+                            "\n{} items could not be quarantined; run again with --permanent to delete them.\n",
+                            retry.len()
+                        ),
+                    )?;
+                }
+            }
+            Err(record.summary())
+        }
+        Command::Apply { plan, dry_run, yes } => {
+            let plan = CleanupPlan::load(&plan).map_err(|e| e.explain())?;
+            if plan.operations.is_empty() {
+                return write(out, "Nothing to clean.\n");
+            }
+            write(out, &plan_table(&plan).render())?;
+            write(out, &format!("\n{}\n", plan.summary()))?;
+
+            let changed = plan.verify();
+            if !changed.is_empty() {
+                let mut table = Table::new(&["ITEM", "CHANGE"]);
+                for (op, error) in &changed {
+                    table.add_row(vec![op.item.item_type.clone(), error.to_string()]);
+                }
+                write(out, &format!("\n{}", table.render()))?;
+                return Err(format!(
+                    "{} of {} planned items changed since the plan was made; create a new plan",
+                    changed.len(),
+                    plan.operations.len()
+                ));
+            }
+            if dry_run {
+                return write(out, "The plan still matches the filesystem.\n");
+            }
+
+            if !yes && !confirm(out, input, "\nApply this plan?")? {
+                return Err("Aborted".to_string());
+            }
+
+            let record = backend.apply_plan(&plan, &CancellationToken::new());
+            if record.error_count == 0 {
+                return write(out, &format!("{}\n", record.summary()));
+            }
+            write(out, &format!("\n{}", failures_table(&record).render()))?;
+            Err(record.summary())
+        }
+        Command::Undo { record_id } => {
+            let record_id = match record_id {
+                Some(id) => id,
+                None => backend
+                    .get_quarantine_records()
+                    .into_iter()
+                    .find(|r| r.is_undoable())
+                    .map(|r| r.id.clone())
+                    .ok_or_else(|| "No cleanup to undo".to_string())?,
+            };
+            let result = backend.undo_cleanup(&record_id).map_err(|e| e.explain())?;
+            for error in &result.errors {
+                write(out, &format!("✗ {}\n", error.explain()))?;
+            }
+            if result.error_count == 0 {
+                write(out, &format!("{}\n", result.summary()))
+            } else {
+                Err(result.summary())
+            }
+        }
+        Command::QuarantineList => {
+            let records = backend.get_quarantine_records();
+            if records.is_empty() {
+                return write(out, "Quarantine is empty.\n");
+            }
+            write(out, &quarantine_table(&records).render())?;
+            let stats = backend.get_quarantine_stats();
+            write(
+                out,
+                &format!(
+                    "\n{} cleanups, {} in quarantine\n",
+                    stats.total_records,
+                    format_size(stats.quarantine_size)
+                ),
+            )
+        }
+        Command::QuarantineDelete { selectors, yes } => {
+            let selected =
+                select::select_quarantine_items(&backend.get_quarantine_records(), &selectors)?;
+
+            let prompt = format!(
+                "Permanently delete {} quarantined items? This cannot be undone.",
+                selected.len()
+            );
+            if !yes && !confirm(out, input, &prompt)? {
+                return Err("Aborted".to_string());
+            }
+
+            let mut errors = Vec::new();
+            for (record_id, item_index) in &selected {
+                match backend.delete_quarantine_item(record_id, *item_index) {
+                    Ok(message) => write(out, &format!("{}\n", message))?,
+                    Err(e) => {
+                        errors.push(format!("{}/{}: {}", record_id, item_index + 1, e.explain()))
+                    }
+                }
+            }
+
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} of {} items could not be deleted:\n  {}",
+                    errors.len(),
+                    selected.len(),
+                    errors.join("\n  ")
+                ))
+            }
+        }
+        Command::QuarantineClear { yes } => {
+            let stats = backend.get_quarantine_stats();
+            if stats.total_records == 0 {
+                return write(out, "Quarantine is empty.\n");
+            }
+
+            let prompt = format!(
+                "Permanently delete all {} cleanups ({}) from quarantine? This cannot be undone.",
+                stats.total_records,
+                format_size(stats.quarantine_size)
+            );
+            if !yes && !confirm(out, input, &prompt)? {
+                return Err("Aborted".to_string());
+            }
+
+            let message = backend.clear_all_quarantine().map_err(|e| e.explain())?;
+            write(out, &format!("{}\n", message))
+        }
+        Command::Help => write(out, USAGE),
+        Command::Version => write(out, &format!("devsweep {}\n", env!("CARGO_PKG_VERSION"))),
+    }
+}
+
+/// Switch to low-impact scanning when asked; otherwise keep the saved mode
+fn use_background(backend: &mut StorageBackend, background: bool) {
+    if background {
+        backend.set_scan_mode(ScanMode::Background);
+    }
+}
+
+/// Scan and keep the items matched by `selectors` (all items if none)
+fn scan(
+    backend: &mut StorageBackend,
+    selectors: &[String],
+    use_cache: bool,
+) -> Result<Vec<ScannedItem>, String> {
+    let categories = backend.scan_with_cache(use_cache);
+    let items = select::scanned_items(&categories);
+    if selectors.is_empty() {
+        Ok(items)
+    } else {
+        select::select_items(items, selectors)
+    }
+}
+
+/// Re-check `items` against the disk, print how they changed since the scan,
+/// and keep those that still exist, with their current sizes
+fn recheck(
+    backend: &StorageBackend,
+    items: Vec<ScannedItem>,
+    out: &mut dyn Write,
+) -> Result<Vec<ScannedItem>, String> {
+    let selected: Vec<CleanupItem> = items.iter().map(|i| i.item.clone()).collect();
+    let preflight = backend.preflight(&selected);
+    if preflight.has_changes() {
+        let mut table = Table::new(&["ID", "ITEM", "CHANGE"]);
+        for (scanned, checked) in items.iter().zip(&preflight.checked) {
+            if let Some(change) = checked.change {
+                table.add_row(vec![
+                    scanned.id.clone(),
+                    scanned.item.item_type.clone(),
+                    change.describe(),
+                ]);
+            }
+        }
+        write(out, &table.render())?;
+        write(out, &format!("{}\n\n", preflight.summary()))?;
+    }
+
+    Ok(items
+        .into_iter()
+        .zip(preflight.checked)
+        .filter(|(_, checked)| checked.change != Some(ItemChange::Vanished))
+        .map(|(scanned, checked)| ScannedItem {
+            item: checked.item,
+            ..scanned
+        })
+        .collect())
+}
+
+/// Size of the items, leaving out those another item already includes
+fn total_size(items: &[ScannedItem]) -> u64 {
+    items
+        .iter()
+        .filter(|i| !i.item.is_covered())
+        .map(|i| i.item.size)
+        .sum()
+}
+
+fn items_table(items: &[ScannedItem]) -> Table {
+    let mut table = Table::new(&["ID", "ITEM", "SIZE", "SAFE", "PATH"]).with_right_aligned(2);
+    for scanned in items {
+        let item = &scanned.item;
+        let mut location = match (&item.cleanup_action, &item.path, &item.covered_by) {
+            (Some(action), _, _) => format!("$ {}", action),
+            (None, Some(path), Some(outer)) => {
+                format!("{} (inside {})", path.display(), outer.display())
+            }
+            (None, Some(path), None) => path.display().to_string(),
+            (None, None, _) => String::new(),
+        };
+        if let Some(in_use) = &item.in_use {
+            location = format!("{} [{}]", location, in_use);
+        }
+        table.add_row(vec![
+            scanned.id.clone(),
+            item.item_type.clone(),
+            format_size(item.size),
+            if item.safe_to_delete { "yes" } else { "no" }.to_string(),
+            location,
+        ]);
+    }
+    table
+}
+
+fn plan_table(plan: &CleanupPlan) -> Table {
+    let mut table =
+        Table::new(&["#", "ITEM", "MODE", "SIZE", "TARGET", "WARNINGS"]).with_right_aligned(3);
+    for (idx, op) in plan.operations.iter().enumerate() {
+        table.add_row(vec![
+            (idx + 1).to_string(),
+            op.item.item_type.clone(),
+            op.mode.label().to_string(),
+            format_size(op.expected_bytes()),
+            op.target(),
+            op.warnings.join("; "),
+        ]);
+    }
+    table
+}
+
+fn quarantine_table(records: &[&CleanupRecord]) -> Table {
+    let mut table = Table::new(&["ID", "CLEANED", "ITEM", "SIZE", "STATUS", "ORIGINAL PATH"])
+        .with_right_aligned(3);
+    for record in records {
+        let cleaned = format_timestamp(record.timestamp);
+        for (idx, item) in record.items.iter().enumerate() {
+            table.add_row(vec![
+                format!("{}/{}", record.id, idx + 1),
+                cleaned.clone(),
+                item.item_type.clone(),
+                format_size(item.size),
+                item_status(record, item),
+                item.original_path.display().to_string(),
+            ]);
+        }
+    }
+    table
+}
+
+fn failures_table(record: &CleanupRecord) -> Table {
+    let mut table = Table::new(&["ITEM", "ERROR", "REMEDY"]);
+    for (item, error) in record.failures() {
+        table.add_row(vec![
+            item.item_type.clone(),
+            error.to_string(),
+            error.remedy().unwrap_or_default().to_string(),
+        ]);
+    }
+    table
+}
+
+fn item_status(record: &CleanupRecord, item: &CleanupItemRecord) -> String {
+    if !item.success {
+        format!(
+            "failed: {}",
+            item.error_message.as_deref().unwrap_or("unknown error")
+        )
+    } else if item.deleted_permanently {
+        "deleted".to_string()
+    } else if !record.can_undo {
+        "restored".to_string()
+    } else {
+        "quarantined".to_string()
+    }
+}
+
+fn format_timestamp(timestamp: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(timestamp)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Ask a yes/no question; anything but "y"/"yes" (including EOF) is a no
+fn confirm(out: &mut dyn Write, input: &mut dyn BufRead, prompt: &str) -> Result<bool, String> {
+    write(out, &format!("{} [y/N] ", prompt))?;
+    out.flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    input
+        .read_line(&mut answer)
+        .map_err(|e| format!("Failed to read answer: {}", e))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn write(out: &mut dyn Write, text: &str) -> Result<(), String> {
+    out.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
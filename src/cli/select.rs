@@ -0,0 +1,190 @@
+//! Item ids and selector matching
+
+use crate::backend::CategoryData;
+use crate::checkers::registry;
+use crate::cleanup_history::CleanupRecord;
+use crate::report::{category_id, item_id};
+use crate::types::CleanupItem;
+use crate::utils::format_size;
+
+/// A scanned item together with the id the CLI shows for it
+#[derive(Debug, Clone)]
+pub struct ScannedItem {
+    /// `<category id>/<n>`, numbered from 1 in scan order
+    pub id: String,
+    pub category_id: String,
+    pub item: CleanupItem,
+}
+
+/// Flatten scan results into items with CLI ids
+pub fn scanned_items(categories: &[CategoryData]) -> Vec<ScannedItem> {
+    categories
+        .iter()
+        .flat_map(|category| {
+            let category_id = category_id(&category.name);
+            category
+                .items
+                .iter()
+                .enumerate()
+                .map(move |(idx, item)| ScannedItem {
+                    id: item_id(&category_id, idx),
+                    category_id: category_id.clone(),
+                    item: item.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Keep the items matched by at least one selector, in scan order.
+///
+/// A selector matches an item when it equals or globs its item id or its
+/// category id. Every selector must match something (or name a registered
+/// category) so that typos are reported instead of silently ignored.
+pub fn select_items(
+    items: Vec<ScannedItem>,
+    selectors: &[String],
+) -> Result<Vec<ScannedItem>, String> {
+    for selector in selectors {
+        let known = items
+            .iter()
+            .any(|i| glob_match(selector, &i.id) || glob_match(selector, &i.category_id))
+            || registry::all().iter().any(|c| glob_match(selector, c.id()));
+        if !known {
+            return Err(format!("Nothing matches '{}'", selector));
+        }
+    }
+
+    Ok(items
+        .into_iter()
+        .filter(|i| {
+            selectors
+                .iter()
+                .any(|s| glob_match(s, &i.id) || glob_match(s, &i.category_id))
+        })
+        .collect())
+}
+
+/// Narrow scan results to the items matched by the selectors, keeping each
+/// category's metadata and dropping categories with nothing selected
+pub fn select_categories(
+    categories: Vec<CategoryData>,
+    selectors: &[String],
+) -> Result<Vec<CategoryData>, String> {
+    let selected: Vec<String> = select_items(scanned_items(&categories), selectors)?
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+
+    Ok(categories
+        .into_iter()
+        .filter_map(|mut category| {
+            let id = category_id(&category.name);
+            let whole = selectors.iter().any(|s| glob_match(s, &id));
+            if !whole {
+                let mut idx = 0;
+                category.items.retain(|_| {
+                    idx += 1;
+                    selected.contains(&item_id(&id, idx - 1))
+                });
+                if category.items.is_empty() {
+                    return None;
+                }
+                category.total_size = category
+                    .items
+                    .iter()
+                    .filter(|i| !i.is_covered())
+                    .map(|i| i.size)
+                    .sum();
+                category.size = format_size(category.total_size);
+                category.item_count = category.items.len() as i32;
+            }
+            Some(category)
+        })
+        .collect())
+}
+
+/// Resolve quarantine selectors (`<record id>/<n>`, numbered from 1) to
+/// `(record id, item index)` pairs, in record order
+pub fn select_quarantine_items(
+    records: &[&CleanupRecord],
+    selectors: &[String],
+) -> Result<Vec<(String, usize)>, String> {
+    let ids: Vec<(String, String, usize)> = records
+        .iter()
+        .flat_map(|record| {
+            record
+                .items
+                .iter()
+                .enumerate()
+                .map(|(idx, _)| (item_id(&record.id, idx), record.id.clone(), idx))
+        })
+        .collect();
+
+    for selector in selectors {
+        if !ids
+            .iter()
+            .any(|(id, record_id, _)| glob_match(selector, id) || glob_match(selector, record_id))
+        {
+            return Err(format!("Nothing in quarantine matches '{}'", selector));
+        }
+    }
+
+    Ok(ids
+        .into_iter()
+        .filter(|(id, record_id, _)| {
+            selectors
+                .iter()
+                .any(|s| glob_match(s, id) || glob_match(s, record_id))
+        })
+        .map(|(_, record_id, idx)| (record_id, idx))
+        .collect())
+}
+
+/// Match `text` against a shell-style glob where `*` matches any run of
+/// characters (including `/`) and `?` matches exactly one character
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, star_t)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star + 1;
+            t = star_t + 1;
+            backtrack = Some((star, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("docker", "docker"));
+        assert!(!glob_match("docker", "docker/1"));
+        assert!(glob_match("node*", "node_modules"));
+        assert!(glob_match("node*", "nodejs/3"));
+        assert!(glob_match("*/1", "rust/1"));
+        assert!(glob_match("rust/?", "rust/2"));
+        assert!(!glob_match("rust/?", "rust/12"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+    }
+}
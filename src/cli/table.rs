@@ -0,0 +1,79 @@
+//! Plain-text table output
+
+/// A table of left-aligned columns, with optional right-aligned ones
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    right_aligned: Vec<usize>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            right_aligned: Vec::new(),
+        }
+    }
+
+    /// Right-align the given column (e.g. sizes)
+    pub fn with_right_aligned(mut self, column: usize) -> Self {
+        self.right_aligned.push(column);
+        self
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render the table, one line per row, without trailing whitespace
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate().take(widths.len()) {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        for row in std::iter::once(&self.headers).chain(&self.rows) {
+            let cells: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| {
+                    let cell = row.get(i).map(String::as_str).unwrap_or("");
+                    if self.right_aligned.contains(&i) {
+                        format!("{:>width$}", cell, width = width)
+                    } else {
+                        format!("{:<width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            out.push_str(cells.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_aligns_columns() {
+        let mut table = Table::new(&["ID", "SIZE", "NOTE"]).with_right_aligned(1);
+        table.add_row(vec!["docker/1".into(), "1.2 GB".into(), String::new()]);
+        table.add_row(vec!["go/1".into(), "300 MB".into(), "safe".into()]);
+        assert_eq!(
+            table.render(),
+            "ID          SIZE  NOTE\n\
+             docker/1  1.2 GB\n\
+             go/1      300 MB  safe\n"
+        );
+    }
+}
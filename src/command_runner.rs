@@ -0,0 +1,250 @@
+//! Running external tools
+//!
+//! Checkers and the backend never spawn processes themselves; they describe
+//! the command as an `Invocation` and hand it to a `CommandRunner`. The
+//! `ProcessRunner` used in practice runs it with a timeout, while a
+//! `ScriptedRunner` answers from canned output keyed by argv, so parsing of
+//! `docker system df`, `brew leaves` or `git branch --merged` can be tested
+//! without the tools installed.
+
+use crate::cancel::CancellationToken;
+use crate::utils::output_with_timeout;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// An external command to run: program, arguments and the environment it
+/// runs in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Working directory; the caller's when unset
+    pub current_dir: Option<PathBuf>,
+    /// The complete environment; inherited from this process when unset
+    pub env: Option<HashMap<String, String>>,
+    /// How long the command may run before it is killed; unlimited when unset
+    pub timeout: Option<Duration>,
+}
+
+impl Invocation {
+    pub fn new(program: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            args: Vec::new(),
+            current_dir: None,
+            env: None,
+            timeout: None,
+        }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<str>) -> &mut Self {
+        self.args.push(arg.as_ref().to_string());
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    pub fn current_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Run with exactly `env`, nothing inherited
+    pub fn env(&mut self, env: HashMap<String, String>) -> &mut Self {
+        self.env = Some(env);
+        self
+    }
+
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Program followed by its arguments
+    pub fn argv(&self) -> Vec<String> {
+        std::iter::once(self.program.clone())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+}
+
+impl fmt::Display for Invocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.argv().join(" "))
+    }
+}
+
+/// What a finished command printed and how it exited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code; `None` if the command was killed by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// A command that printed `stdout` and exited with 0
+    pub fn success_with(stdout: &str) -> Self {
+        Self {
+            exit_code: Some(0),
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    /// A command that printed `stderr` and exited with `exit_code`
+    pub fn failure_with(exit_code: i32, stderr: &str) -> Self {
+        Self {
+            exit_code: Some(exit_code),
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs external commands for checkers and cleanups
+pub trait CommandRunner: fmt::Debug + Send + Sync {
+    /// Run `invocation` to completion and collect its output. Fails with
+    /// `ErrorKind::TimedOut` past the invocation's timeout and
+    /// `ErrorKind::Interrupted` once `cancel` is triggered.
+    fn run(&self, invocation: &Invocation, cancel: &CancellationToken)
+        -> io::Result<CommandOutput>;
+}
+
+/// Runs commands as real processes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn run(
+        &self,
+        invocation: &Invocation,
+        cancel: &CancellationToken,
+    ) -> io::Result<CommandOutput> {
+        let mut command = Command::new(&invocation.program);
+        command.args(&invocation.args);
+        if let Some(env) = &invocation.env {
+            command.env_clear().envs(env);
+        }
+        if let Some(dir) = &invocation.current_dir {
+            command.current_dir(dir);
+        }
+
+        let timeout = invocation.timeout.unwrap_or(Duration::MAX);
+        let output = output_with_timeout(&mut command, timeout, cancel)?;
+        Ok(CommandOutput {
+            exit_code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// Answers commands from canned output keyed by argv, and records every
+/// invocation it was asked to run. Commands without a script fail as if
+/// the program weren't installed.
+#[derive(Debug, Default)]
+pub struct ScriptedRunner {
+    outputs: HashMap<Vec<String>, CommandOutput>,
+    calls: Mutex<Vec<Invocation>>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `argv` with `stdout` and exit code 0
+    pub fn with_stdout(self, argv: &[&str], stdout: &str) -> Self {
+        self.with_output(argv, CommandOutput::success_with(stdout))
+    }
+
+    /// Answer `argv` with `output`
+    pub fn with_output(mut self, argv: &[&str], output: CommandOutput) -> Self {
+        let argv = argv.iter().map(|arg| arg.to_string()).collect();
+        self.outputs.insert(argv, output);
+        self
+    }
+
+    /// Every invocation run so far, in order
+    pub fn calls(&self) -> Vec<Invocation> {
+        self.calls.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+}
+
+impl CommandRunner for ScriptedRunner {
+    fn run(
+        &self,
+        invocation: &Invocation,
+        cancel: &CancellationToken,
+    ) -> io::Result<CommandOutput> {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(invocation.clone());
+        }
+        if cancel.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        self.outputs
+            .get(&invocation.argv())
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no scripted output for `{}`", invocation),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_runner_collects_output() {
+        let mut invocation = Invocation::new("sh");
+        invocation.args(["-c", "echo out; echo err >&2; exit 3"]);
+        let output = ProcessRunner
+            .run(&invocation, &CancellationToken::new())
+            .unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout.trim(), "out");
+        assert_eq!(output.stderr.trim(), "err");
+        assert!(!output.success());
+    }
+
+    #[test]
+    fn test_scripted_runner_answers_by_argv() {
+        let runner = ScriptedRunner::new().with_stdout(&["brew", "leaves"], "wget\n");
+        let cancel = CancellationToken::new();
+
+        let mut leaves = Invocation::new("brew");
+        leaves.arg("leaves");
+        assert_eq!(runner.run(&leaves, &cancel).unwrap().stdout, "wget\n");
+
+        let mut other = Invocation::new("brew");
+        other.arg("list");
+        let error = runner.run(&other, &cancel).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        assert_eq!(runner.calls(), vec![leaves, other]);
+    }
+}
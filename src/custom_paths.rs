@@ -3,12 +3,13 @@
 //! Allows users to add custom directories to scan for cleanup.
 //! Paths are persisted to disk and scanned alongside built-in checkers.
 
-use crate::types::{CheckResult, CleanupItem};
-use crate::utils::format_size;
+use crate::checkers::{Checker, ScanContext};
+use crate::disk_usage::{DiskUsage, UsageCounter};
+use crate::error::DevSweepError;
+use crate::types::{CheckResult, CleanupItem, SuperCategoryType};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use walkdir::WalkDir;
 
 /// Configuration file name for custom paths
 const CONFIG_FILE: &str = "custom_paths.json";
@@ -59,19 +60,18 @@ impl CustomPathsConfig {
     }
 
     /// Save configuration to disk
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<(), DevSweepError> {
         let config_path = Self::config_path();
 
         // Ensure parent directory exists
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
         }
 
         let content = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
+            .map_err(|e| DevSweepError::serialization(format!("Failed to serialize: {}", e)))?;
 
-        fs::write(&config_path, content).map_err(|e| format!("Failed to write config: {}", e))
+        fs::write(&config_path, content).map_err(|e| DevSweepError::from_io(&e, &config_path))
     }
 
     /// Get the configuration file path
@@ -83,15 +83,17 @@ impl CustomPathsConfig {
     }
 
     /// Add a new custom path
-    pub fn add_path(&mut self, path: PathBuf, label: String) -> Result<(), String> {
+    pub fn add_path(&mut self, path: PathBuf, label: String) -> Result<(), DevSweepError> {
         // Validate path exists
         if !path.exists() {
-            return Err(format!("Path does not exist: {}", path.display()));
+            return Err(DevSweepError::NotFound { path });
         }
 
         // Check for duplicates
         if self.paths.iter().any(|p| p.path == path) {
-            return Err(format!("Path already added: {}", path.display()));
+            return Err(DevSweepError::InvalidInput {
+                message: format!("Path already added: {}", path.display()),
+            });
         }
 
         self.paths.push(CustomPath::new(path, label));
@@ -99,9 +101,9 @@ impl CustomPathsConfig {
     }
 
     /// Remove a custom path by index
-    pub fn remove_path(&mut self, index: usize) -> Result<(), String> {
+    pub fn remove_path(&mut self, index: usize) -> Result<(), DevSweepError> {
         if index >= self.paths.len() {
-            return Err("Invalid path index".to_string());
+            return Err(invalid_index());
         }
 
         self.paths.remove(index);
@@ -109,9 +111,9 @@ impl CustomPathsConfig {
     }
 
     /// Toggle a path's enabled status
-    pub fn toggle_path(&mut self, index: usize) -> Result<(), String> {
+    pub fn toggle_path(&mut self, index: usize) -> Result<(), DevSweepError> {
         if index >= self.paths.len() {
-            return Err("Invalid path index".to_string());
+            return Err(invalid_index());
         }
 
         self.paths[index].enabled = !self.paths[index].enabled;
@@ -124,24 +126,62 @@ impl CustomPathsConfig {
     }
 }
 
+fn invalid_index() -> DevSweepError {
+    DevSweepError::InvalidInput {
+        message: "Invalid path index".to_string(),
+    }
+}
+
+/// Registry entry for the custom path checker
+pub struct CustomPathsChecker;
+
+impl Checker for CustomPathsChecker {
+    fn id(&self) -> &'static str {
+        "custom_paths"
+    }
+
+    fn name(&self) -> &'static str {
+        "Custom Paths"
+    }
+
+    fn super_category(&self) -> SuperCategoryType {
+        SuperCategoryType::ProjectFiles
+    }
+
+    fn default_ttl(&self) -> u64 {
+        300
+    }
+
+    fn check(&self, ctx: &ScanContext) -> CheckResult {
+        check_custom_paths(ctx)
+    }
+}
+
 /// Scan custom paths and return cleanup items
-pub fn check_custom_paths() -> CheckResult {
-    let mut result = CheckResult::new("Custom Paths");
+pub fn check_custom_paths(ctx: &ScanContext) -> CheckResult {
+    let mut result = CheckResult::new(CustomPathsChecker.name());
 
     let config = CustomPathsConfig::load();
 
     for custom_path in config.enabled_paths() {
+        if ctx.is_cancelled() {
+            break;
+        }
+        // Even checking that a path on a share exists can hang
+        if ctx.is_on_remote_mount(&custom_path.path) {
+            continue;
+        }
         if !custom_path.path.exists() {
             continue;
         }
 
-        let size = calculate_path_size(&custom_path.path, custom_path.recursive);
+        let usage = calculate_path_size(ctx, &custom_path.path, custom_path.recursive);
 
-        if size == 0 {
+        if usage.apparent == 0 {
             continue;
         }
 
-        let item = CleanupItem::new(&custom_path.label, size, &format_size(size))
+        let item = CleanupItem::from_usage(&custom_path.label, usage)
             .with_path(custom_path.path.clone())
             .with_safe_to_delete(false) // User should confirm custom paths
             .with_warning("User-configured custom path");
@@ -152,34 +192,24 @@ pub fn check_custom_paths() -> CheckResult {
     result
 }
 
-/// Calculate the size of a path
-fn calculate_path_size(path: &PathBuf, recursive: bool) -> u64 {
-    if path.is_file() {
-        return path.metadata().map(|m| m.len()).unwrap_or(0);
+/// Calculate the disk usage of a path
+fn calculate_path_size(ctx: &ScanContext, path: &PathBuf, recursive: bool) -> DiskUsage {
+    if path.is_file() || recursive {
+        return ctx.disk_usage(path);
     }
 
-    if recursive {
-        WalkDir::new(path)
-            .follow_links(false)
-            .into_iter()
+    // Non-recursive: only direct children
+    let mut counter = UsageCounter::new();
+    if let Ok(entries) = fs::read_dir(path) {
+        for metadata in entries
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
             .filter_map(|e| e.metadata().ok())
-            .map(|m| m.len())
-            .sum()
-    } else {
-        // Non-recursive: only direct children
-        fs::read_dir(path)
-            .map(|entries| {
-                entries
-                    .filter_map(|e| e.ok())
-                    .filter_map(|e| e.metadata().ok())
-                    .filter(|m| m.is_file())
-                    .map(|m| m.len())
-                    .sum()
-            })
-            .unwrap_or(0)
+            .filter(|m| m.is_file())
+        {
+            counter.add_file(&metadata);
+        }
     }
+    counter.finish()
 }
 
 #[cfg(test)]
@@ -215,13 +245,13 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         file.write_all(b"Hello, World!").unwrap();
 
-        let size = calculate_path_size(&file_path, false);
-        assert_eq!(size, 13); // "Hello, World!" is 13 bytes
+        let usage = calculate_path_size(&ScanContext::new(), &file_path, false);
+        assert_eq!(usage.apparent, 13); // "Hello, World!" is 13 bytes
     }
 
     #[test]
     fn test_check_custom_paths_empty() {
-        let result = check_custom_paths();
+        let result = check_custom_paths(&ScanContext::new());
         assert_eq!(result.name, "Custom Paths");
         // Result depends on user's config, but should not panic
     }
@@ -0,0 +1,333 @@
+//! Deletion safety policy
+//!
+//! Scan results can be stale and custom paths can point anywhere, so every
+//! path is checked against a `DeletionPolicy` right before it is deleted or
+//! quarantined (`CleanupHistory::quarantine_item` takes the policy, so nothing
+//! is moved unchecked). The check looks at the filesystem as it is at that
+//! moment: the path is resolved, and refused if
+//!
+//! - it is, or contains, a protected location: the filesystem root, system
+//!   directories, the home directory and its standard folders, or a project
+//!   search root;
+//! - it is inside (or contains) the quarantine directory or a location on the
+//!   user's denylist;
+//! - it is, or contains, a mount point;
+//! - one of its parent directories is a symlink leading out of the directory
+//!   the item was found under (home, the temp directory or the scan root), as
+//!   when a cache directory is swapped for a link to `~/Documents` between
+//!   scan and cleanup.
+//!
+//! A target that is itself a symlink is fine: deleting it only removes the
+//! link. Refusals are `DevSweepError::Protected` errors, recorded with the
+//! item in the cleanup history.
+
+use crate::checkers::ScanContext;
+use crate::error::DevSweepError;
+use crate::mounts::MountTable;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const SETTINGS_FILE: &str = "deletion_policy.json";
+
+/// System directories that are never deleted, nor anything containing them
+const SYSTEM_DIRS: &[&str] = &[
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Users",
+    "/Volumes",
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/home",
+    "/lib",
+    "/opt",
+    "/opt/homebrew",
+    "/private",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/sys",
+    "/tmp",
+    "/usr",
+    "/usr/local",
+    "/var",
+];
+
+/// Folders in the home directory that are never deleted as a whole
+const HOME_DIRS: &[&str] = &[
+    "Applications",
+    "Desktop",
+    "Documents",
+    "Downloads",
+    "Library",
+    "Library/Application Support",
+    "Library/Caches",
+    "Movies",
+    "Music",
+    "Pictures",
+    "Public",
+    ".config",
+    ".gnupg",
+    ".ssh",
+];
+
+/// Deletion preferences chosen in settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeletionSettings {
+    /// Locations nothing may be deleted from; `~` stands for the home directory
+    #[serde(default)]
+    pub denylist: Vec<PathBuf>,
+}
+
+impl DeletionSettings {
+    /// Load settings from disk, or the defaults
+    pub fn load() -> Self {
+        fs::read_to_string(Self::config_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), DevSweepError> {
+        let config_path = Self::config_path();
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| DevSweepError::serialization(format!("Failed to serialize: {}", e)))?;
+        fs::write(&config_path, content).map_err(|e| DevSweepError::from_io(&e, &config_path))
+    }
+
+    fn config_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("devsweep")
+            .join(SETTINGS_FILE)
+    }
+}
+
+/// Decides whether a path may be deleted
+#[derive(Debug, Clone)]
+pub struct DeletionPolicy {
+    /// Locations that may not be deleted, nor any directory containing them
+    protected: Vec<PathBuf>,
+    /// Trees nothing may be deleted from
+    denied: Vec<PathBuf>,
+    /// Directories items are found under; symlinks are not followed below them
+    roots: Vec<PathBuf>,
+    mounts: MountTable,
+}
+
+impl DeletionPolicy {
+    /// Policy protecting the system directories only
+    pub fn new() -> Self {
+        Self {
+            protected: SYSTEM_DIRS.iter().map(PathBuf::from).collect(),
+            denied: Vec::new(),
+            roots: vec![std::env::temp_dir()],
+            mounts: MountTable::empty(),
+        }
+    }
+
+    /// Policy for cleaning up what was found with `ctx`: its home directory,
+    /// project roots and mounts are protected too, and system directories are
+    /// also protected inside `ctx.root`
+    pub fn for_context(ctx: &ScanContext) -> Self {
+        let mut policy = Self::new().with_mounts(match &ctx.mounts {
+            Some(mounts) => MountTable::clone(mounts),
+            None => MountTable::load(),
+        });
+
+        if ctx.root != Path::new("/") {
+            for dir in SYSTEM_DIRS {
+                policy.protected.push(ctx.system_path(dir));
+            }
+            policy.protected.push(ctx.root.clone());
+            policy.roots.push(ctx.root.clone());
+        }
+        if let Some(home) = &ctx.home {
+            policy.protected.push(home.clone());
+            policy
+                .protected
+                .extend(HOME_DIRS.iter().map(|dir| home.join(dir)));
+            policy.roots.push(home.clone());
+        }
+        policy.protected.extend(ctx.project_roots.iter().cloned());
+        policy
+    }
+
+    /// Also refuse to delete `path` or any directory containing it
+    pub fn protect(mut self, path: impl Into<PathBuf>) -> Self {
+        self.protected.push(path.into());
+        self
+    }
+
+    /// Also refuse to delete anything inside `path`, or containing it
+    pub fn deny(mut self, path: impl Into<PathBuf>) -> Self {
+        self.denied.push(path.into());
+        self
+    }
+
+    /// Also refuse the user's denylist, with `~` expanded to `home`
+    pub fn with_settings(mut self, settings: &DeletionSettings, home: Option<&Path>) -> Self {
+        for path in &settings.denylist {
+            let expanded = match (path.strip_prefix("~"), home) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ => path.clone(),
+            };
+            self.denied.push(expanded);
+        }
+        self
+    }
+
+    pub fn with_mounts(mut self, mounts: MountTable) -> Self {
+        self.mounts = mounts;
+        self
+    }
+
+    /// Check that `path` may be deleted as it is on disk right now
+    pub fn check(&self, path: &Path) -> Result<(), DevSweepError> {
+        let refuse = |reason: String| {
+            Err(DevSweepError::Protected {
+                path: path.to_path_buf(),
+                reason,
+            })
+        };
+
+        let normalized = path
+            .components()
+            .all(|c| matches!(c, Component::RootDir | Component::Normal(_)));
+        if !path.is_absolute() || !normalized {
+            return refuse("it is not an absolute path without `.` or `..`".to_string());
+        }
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return refuse("it is the filesystem root".to_string());
+        };
+
+        // The target itself is not resolved: deleting a symlink removes the link
+        fs::symlink_metadata(path).map_err(|e| DevSweepError::from_io(&e, path))?;
+        let resolved = fs::canonicalize(parent)
+            .map_err(|e| DevSweepError::from_io(&e, parent))?
+            .join(name);
+
+        let root = self
+            .roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map_or(Path::new("/"), |root| root.as_path());
+        let below_root = path.strip_prefix(root).unwrap_or(path);
+        if resolved.strip_prefix(canonical(root)).ok() != Some(below_root) {
+            return refuse(format!(
+                "it leads through a symlink to {}",
+                resolved.display()
+            ));
+        }
+
+        for candidate in [path, resolved.as_path()] {
+            // The shallowest protected location inside the candidate, which
+            // is the candidate itself if that is protected
+            let contained = self
+                .protected
+                .iter()
+                .map(|p| (p, [p.clone(), canonical(p)]))
+                .filter(|(_, forms)| forms.iter().any(|p| p.starts_with(candidate)))
+                .min_by_key(|(p, _)| p.components().count());
+            if let Some((protected, forms)) = contained {
+                return refuse(if forms.iter().any(|p| p == candidate) {
+                    "it is a protected location".to_string()
+                } else {
+                    format!("it contains {}", protected.display())
+                });
+            }
+
+            for denied in &self.denied {
+                if [denied.clone(), canonical(denied)]
+                    .iter()
+                    .any(|d| candidate.starts_with(d) || d.starts_with(candidate))
+                {
+                    return refuse(format!("{} is protected", denied.display()));
+                }
+            }
+
+            for mount in self.mounts.mounts() {
+                if mount.mount_point == candidate {
+                    return refuse(format!("it is a mount point ({})", mount.fs_type));
+                }
+                if mount.mount_point.starts_with(candidate) {
+                    return refuse(format!(
+                        "it contains the mounted filesystem {} ({})",
+                        mount.mount_point.display(),
+                        mount.fs_type
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for DeletionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `path` with symlinks resolved, or as given if it doesn't exist
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mounts::Mount;
+
+    #[test]
+    fn test_refuses_protected_locations_and_their_parents() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        fs::create_dir_all(home.join("Documents")).unwrap();
+        fs::create_dir_all(home.join("Library/Caches/pip")).unwrap();
+        let policy = DeletionPolicy::for_context(&ScanContext::for_home(home));
+
+        assert!(policy.check(&home.join("Library/Caches/pip")).is_ok());
+        for refused in [home, &home.join("Documents"), &home.join("Library")] {
+            assert!(matches!(
+                policy.check(refused),
+                Err(DevSweepError::Protected { .. })
+            ));
+        }
+        assert!(policy.check(Path::new("/")).is_err());
+        assert!(policy.check(&home.join("Library/../Documents")).is_err());
+        assert!(policy.check(Path::new("relative/path")).is_err());
+    }
+
+    #[test]
+    fn test_refuses_mount_points_and_denied_trees() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let build = home.join("Projects/app/build");
+        fs::create_dir_all(build.join("mnt")).unwrap();
+        fs::create_dir_all(build.join("out")).unwrap();
+        fs::create_dir_all(home.join("keep/cache")).unwrap();
+
+        let settings = DeletionSettings {
+            denylist: vec![PathBuf::from("~/keep")],
+        };
+        let policy = DeletionPolicy::for_context(&ScanContext::for_home(home))
+            .with_mounts(MountTable::new(vec![Mount::new(build.join("mnt"), "nfs")]))
+            .with_settings(&settings, Some(home));
+
+        let err = policy.check(&build).unwrap_err();
+        assert!(err.to_string().contains("mounted filesystem"), "{}", err);
+        assert!(policy.check(&build.join("mnt")).is_err());
+        assert!(policy.check(&home.join("keep/cache")).is_err());
+        assert!(policy.check(&build.join("out")).is_ok());
+    }
+}
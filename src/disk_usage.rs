@@ -0,0 +1,263 @@
+//! Disk usage of files and directory trees
+//!
+//! Summing `metadata.len()` overstates what a cleanup frees in two ways:
+//! hardlinked files (pnpm stores, Cargo and uv caches) are counted once per
+//! link, and sparse files (Docker and VM images) are counted at their apparent
+//! size. `DiskUsage` reports the apparent size with hardlinks counted once, the
+//! space actually allocated on disk (`st_blocks`), and how much of that would
+//! be freed by deleting the tree: files with links outside it are not.
+//!
+//! `estimate_usage` trades exactness for speed: it lists a sample of a tree's
+//! directories and extrapolates the rest, so a first scan can show every
+//! category before the exact walk finishes.
+
+use crate::cancel::CancellationToken;
+use crate::walk::{read_dir_entries, IoBudget, ParallelWalk};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::ops::{Add, AddAssign};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// `st_blocks` is always counted in 512-byte units
+const BLOCK_SIZE: u64 = 512;
+
+/// Directories `estimate_usage` lists before it extrapolates
+pub const ESTIMATE_DIR_BUDGET: usize = 64;
+
+/// Size of a file or tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// Sum of file lengths, each hardlinked file counted once
+    pub apparent: u64,
+    /// Space allocated on disk, each hardlinked file counted once
+    pub allocated: u64,
+    /// Allocated space freed by deleting the tree: hardlinked files that
+    /// still have a link elsewhere free nothing
+    pub reclaimable: u64,
+}
+
+impl Add for DiskUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            apparent: self.apparent + other.apparent,
+            allocated: self.allocated + other.allocated,
+            reclaimable: self.reclaimable + other.reclaimable,
+        }
+    }
+}
+
+impl AddAssign for DiskUsage {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::iter::Sum for DiskUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// A file with more than one hardlink, kept until the whole tree is counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkedFile {
+    pub dev: u64,
+    pub ino: u64,
+    pub nlink: u64,
+    pub len: u64,
+    pub allocated: u64,
+}
+
+/// Accumulates the files of a tree, deduplicating hardlinks by (dev, inode)
+#[derive(Debug, Clone, Default)]
+pub struct UsageCounter {
+    /// Files with a single link
+    single: DiskUsage,
+    linked: Vec<LinkedFile>,
+}
+
+impl UsageCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a regular file
+    pub fn add_file(&mut self, metadata: &Metadata) {
+        let allocated = metadata.blocks() * BLOCK_SIZE;
+        if metadata.nlink() > 1 {
+            self.linked.push(LinkedFile {
+                dev: metadata.dev(),
+                ino: metadata.ino(),
+                nlink: metadata.nlink(),
+                len: metadata.len(),
+                allocated,
+            });
+        } else {
+            self.single += DiskUsage {
+                apparent: metadata.len(),
+                allocated,
+                reclaimable: allocated,
+            };
+        }
+    }
+
+    /// Add files counted elsewhere (e.g. from the size index)
+    pub fn add_counted(&mut self, single: DiskUsage, linked: &[LinkedFile]) {
+        self.single += single;
+        self.linked.extend_from_slice(linked);
+    }
+
+    pub fn merge(mut self, other: Self) -> Self {
+        self.single += other.single;
+        self.linked.extend(other.linked);
+        self
+    }
+
+    /// Files with a single link, and the hardlinked ones seen so far
+    pub fn parts(&self) -> (DiskUsage, &[LinkedFile]) {
+        (self.single, &self.linked)
+    }
+
+    pub fn finish(self) -> DiskUsage {
+        let mut links: HashMap<(u64, u64), (LinkedFile, u64)> = HashMap::new();
+        for file in self.linked {
+            links.entry((file.dev, file.ino)).or_insert((file, 0)).1 += 1;
+        }
+
+        let mut usage = self.single;
+        for (file, seen) in links.into_values() {
+            usage.apparent += file.len;
+            usage.allocated += file.allocated;
+            // Only freed when every link to the file is inside the tree
+            if seen >= file.nlink {
+                usage.reclaimable += file.allocated;
+            }
+        }
+        usage
+    }
+}
+
+/// Disk usage of `path` (a file or a directory tree, symlinks not followed)
+pub fn disk_usage(path: &Path) -> DiskUsage {
+    disk_usage_until(path, &CancellationToken::new())
+}
+
+/// Like `disk_usage`, but stops walking once `cancel` is triggered and returns
+/// the usage counted so far
+pub fn disk_usage_until(path: &Path, cancel: &CancellationToken) -> DiskUsage {
+    disk_usage_within(path, IoBudget::global(), cancel)
+}
+
+/// Like `disk_usage_until`, reading directories under a permit from `budget`
+pub fn disk_usage_within(path: &Path, budget: &IoBudget, cancel: &CancellationToken) -> DiskUsage {
+    ParallelWalk::new(cancel)
+        .with_budget(budget)
+        .fold_files(
+            path,
+            UsageCounter::new,
+            |mut counter, metadata| {
+                counter.add_file(metadata);
+                counter
+            },
+            UsageCounter::merge,
+        )
+        .finish()
+}
+
+/// A quick estimate of `disk_usage(path)`
+///
+/// Lists about `max_dirs` directories: each directory's budget is split among
+/// its subdirectories, and when there are more subdirectories than budget, an
+/// evenly spaced sample of them is walked and scaled up to all of them. At
+/// least one path is always followed to the bottom of the tree, so small leaf
+/// directories are still found. Trees that fit the budget are counted exactly
+/// (hardlinks to files in other directories aside).
+pub fn estimate_usage(
+    path: &Path,
+    max_dirs: usize,
+    budget: &IoBudget,
+    cancel: &CancellationToken,
+) -> DiskUsage {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => {
+            estimate_dir(path, metadata.dev(), max_dirs, budget, cancel)
+        }
+        Ok(metadata) if metadata.is_file() => {
+            let mut counter = UsageCounter::new();
+            counter.add_file(&metadata);
+            counter.finish()
+        }
+        _ => DiskUsage::default(),
+    }
+}
+
+fn estimate_dir(
+    dir: &Path,
+    device: u64,
+    max_dirs: usize,
+    budget: &IoBudget,
+    cancel: &CancellationToken,
+) -> DiskUsage {
+    if cancel.is_cancelled() {
+        return DiskUsage::default();
+    }
+    let Some(entries) = read_dir_entries(dir, device, budget) else {
+        return DiskUsage::default();
+    };
+    let mut counter = UsageCounter::new();
+    for metadata in &entries.files {
+        counter.add_file(metadata);
+    }
+    let files = counter.finish();
+
+    let subdirs = entries.subdirs;
+    if subdirs.is_empty() {
+        return files;
+    }
+    let max_dirs = max_dirs.saturating_sub(1);
+    let sampled = max_dirs.clamp(1, subdirs.len());
+    let sample: DiskUsage = (0..sampled)
+        .map(|i| {
+            let (subdir, _) = &subdirs[i * subdirs.len() / sampled];
+            estimate_dir(subdir, device, max_dirs / sampled, budget, cancel)
+        })
+        .sum();
+    files + scaled(sample, subdirs.len(), sampled)
+}
+
+/// `usage * num / den`, without overflowing
+fn scaled(usage: DiskUsage, num: usize, den: usize) -> DiskUsage {
+    let scale = |bytes: u64| (bytes as u128 * num as u128 / den as u128) as u64;
+    DiskUsage {
+        apparent: scale(usage.apparent),
+        allocated: scale(usage.allocated),
+        reclaimable: scale(usage.reclaimable),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_adds_up() {
+        let a = DiskUsage {
+            apparent: 1,
+            allocated: 2,
+            reclaimable: 3,
+        };
+        let total: DiskUsage = [a, a].into_iter().sum();
+        assert_eq!(
+            total,
+            DiskUsage {
+                apparent: 2,
+                allocated: 4,
+                reclaimable: 6
+            }
+        );
+    }
+}
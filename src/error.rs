@@ -0,0 +1,286 @@
+//! Error type shared by cleanup, quarantine and persistence code
+//!
+//! Errors keep enough structure (the path involved, the command and its exit
+//! code, ...) for the UI and CLI to explain what failed and suggest a remedy,
+//! and are stored with each failed item in the cleanup history.
+
+use crate::types::InUse;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `EXDEV`: rename across filesystems (same value on macOS and Linux)
+const EXDEV: i32 = 18;
+/// `EBUSY`: device or resource busy
+const EBUSY: i32 = 16;
+/// `ETXTBSY`: text file busy (an executable that is running)
+const ETXTBSY: i32 = 26;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DevSweepError {
+    /// The current user may not read or modify the path
+    PermissionDenied { path: PathBuf },
+    /// The path does not exist (it may already have been removed)
+    NotFound { path: PathBuf },
+    /// Something already exists where a file would be restored
+    AlreadyExists { path: PathBuf },
+    /// The path is on a different filesystem than the quarantine directory
+    CrossDevice { path: PathBuf },
+    /// The path is busy or held by another process
+    InUse {
+        path: PathBuf,
+        /// The process or lock file holding it, when known
+        #[serde(default)]
+        by: Option<InUse>,
+    },
+    /// Any other filesystem error
+    Io { path: PathBuf, message: String },
+    /// A cleanup command could not be started or exited unsuccessfully
+    CommandFailed {
+        command: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// A cache, settings or history file could not be serialized or written
+    Serialization { message: String },
+    /// The item has neither a path nor a cleanup command
+    NoCleanupAction,
+    /// The home directory could not be determined
+    NoHomeDirectory,
+    /// No cleanup record with this id exists
+    RecordNotFound { record_id: String },
+    /// The record has no item at this index
+    ItemNotFound { record_id: String, index: usize },
+    /// The cleanup was not quarantined or has already been undone
+    NotUndoable { record_id: String },
+    /// The item was deleted permanently or failed, so there is nothing to restore
+    NotInQuarantine,
+    /// The quarantined copy has disappeared from the quarantine directory
+    QuarantineMissing { path: PathBuf },
+    /// The path changed after the cleanup plan was made
+    PlanMismatch { path: PathBuf, reason: String },
+    /// The deletion policy refused to touch the path (see `deletion_policy`)
+    Protected { path: PathBuf, reason: String },
+    /// The item comes from a scan's estimate pass; its size is not final
+    SizeEstimated { item_type: String },
+    /// A request was rejected before anything was touched
+    InvalidInput { message: String },
+    /// Failure without a more specific classification
+    Other { message: String },
+}
+
+impl DevSweepError {
+    /// Classify an I/O error that occurred while working on `path`
+    pub fn from_io(err: &io::Error, path: &Path) -> Self {
+        let path = path.to_path_buf();
+        match err.raw_os_error() {
+            Some(EXDEV) => return Self::CrossDevice { path },
+            Some(EBUSY) | Some(ETXTBSY) => return Self::InUse { path, by: None },
+            _ => {}
+        }
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied { path },
+            io::ErrorKind::NotFound => Self::NotFound { path },
+            io::ErrorKind::AlreadyExists => Self::AlreadyExists { path },
+            _ => Self::Io {
+                path,
+                message: err.to_string(),
+            },
+        }
+    }
+
+    pub fn serialization(message: impl fmt::Display) -> Self {
+        Self::Serialization {
+            message: message.to_string(),
+        }
+    }
+
+    /// Whether deleting the item permanently (skipping quarantine) may succeed
+    /// where quarantining it failed
+    pub fn can_retry_permanently(&self) -> bool {
+        matches!(self, Self::CrossDevice { .. })
+    }
+
+    /// A short suggestion for the user, if there is one
+    pub fn remedy(&self) -> Option<&'static str> {
+        match self {
+            Self::PermissionDenied { .. } => Some(
+                "Grant DevSweep Full Disk Access in System Settings or fix the permissions, then retry",
+            ),
+            Self::NotFound { .. } => Some("Rescan; it may already have been removed"),
+            Self::AlreadyExists { .. } => {
+                Some("Move the existing file out of the way, then undo again")
+            }
+            Self::CrossDevice { .. } => Some(
+                "It is on a different disk than the quarantine; retry with permanent delete",
+            ),
+            Self::InUse { .. } => Some("Quit the application using it, then retry"),
+            Self::CommandFailed { .. } => {
+                Some("Check that the tool is installed and running, then retry")
+            }
+            Self::QuarantineMissing { .. } => {
+                Some("The quarantined copy was removed outside DevSweep")
+            }
+            Self::PlanMismatch { .. } => Some("Create a new plan and review it again"),
+            Self::Protected { .. } => Some(
+                "DevSweep never deletes this location; remove it yourself if you are sure",
+            ),
+            Self::SizeEstimated { .. } => Some("Wait for the scan to finish sizing it, then retry"),
+            _ => None,
+        }
+    }
+
+    /// The error followed by its remedy, if it has one
+    pub fn explain(&self) -> String {
+        match self.remedy() {
+            Some(remedy) => format!("{}. {}", self, remedy),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DevSweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionDenied { path } => write!(f, "Permission denied: {}", path.display()),
+            Self::NotFound { path } => write!(f, "Path does not exist: {}", path.display()),
+            Self::AlreadyExists { path } => {
+                write!(f, "Original location already exists: {}", path.display())
+            }
+            Self::CrossDevice { path } => write!(
+                f,
+                "Cannot move across filesystems to quarantine: {}",
+                path.display()
+            ),
+            Self::InUse { path, by: None } => {
+                write!(f, "In use by another process: {}", path.display())
+            }
+            Self::InUse { path, by: Some(by) } => write!(f, "{}: {}", by, path.display()),
+            Self::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            Self::CommandFailed {
+                command,
+                exit_code,
+                stderr,
+            } => {
+                match exit_code {
+                    Some(code) => write!(f, "Command '{}' exited with code {}", command, code)?,
+                    None => write!(f, "Command '{}' failed", command)?,
+                }
+                let stderr = stderr.trim();
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            Self::Serialization { message } => write!(f, "{}", message),
+            Self::NoCleanupAction => write!(f, "No cleanup action available for this item"),
+            Self::NoHomeDirectory => write!(f, "Could not determine home directory"),
+            Self::RecordNotFound { record_id } => write!(f, "Record not found: {}", record_id),
+            Self::ItemNotFound { record_id, index } => {
+                write!(f, "Record {} has no item {}", record_id, index + 1)
+            }
+            Self::NotUndoable { record_id } => {
+                write!(f, "Cleanup {} cannot be undone", record_id)
+            }
+            Self::NotInQuarantine => write!(f, "Item is not in quarantine"),
+            Self::QuarantineMissing { path } => {
+                write!(f, "Quarantined file no longer exists: {}", path.display())
+            }
+            Self::PlanMismatch { path, reason } => write!(
+                f,
+                "{} changed since the plan was made: {}",
+                path.display(),
+                reason
+            ),
+            Self::Protected { path, reason } => {
+                write!(f, "Refusing to delete {}: {}", path.display(), reason)
+            }
+            Self::SizeEstimated { item_type } => write!(
+                f,
+                "{} has only an estimated size; it can be cleaned once the scan finishes",
+                item_type
+            ),
+            Self::InvalidInput { message } | Self::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for DevSweepError {}
+
+impl From<String> for DevSweepError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+impl From<&str> for DevSweepError {
+    fn from(message: &str) -> Self {
+        Self::Other {
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_io_classifies_errors() {
+        let path = Path::new("/some/path");
+        let err = |kind| DevSweepError::from_io(&io::Error::from(kind), path);
+
+        assert!(matches!(
+            err(io::ErrorKind::PermissionDenied),
+            DevSweepError::PermissionDenied { .. }
+        ));
+        assert!(matches!(
+            err(io::ErrorKind::NotFound),
+            DevSweepError::NotFound { .. }
+        ));
+        assert!(matches!(
+            DevSweepError::from_io(&io::Error::from_raw_os_error(EXDEV), path),
+            DevSweepError::CrossDevice { .. }
+        ));
+        assert!(matches!(
+            DevSweepError::from_io(&io::Error::from_raw_os_error(EBUSY), path),
+            DevSweepError::InUse { .. }
+        ));
+    }
+
+    #[test]
+    fn test_serializes_with_kind_tag() {
+        let err = DevSweepError::CommandFailed {
+            command: "docker image prune -f".to_string(),
+            exit_code: Some(1),
+            stderr: "Cannot connect to the Docker daemon".to_string(),
+        };
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["kind"], "command_failed");
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(serde_json::from_value::<DevSweepError>(json).unwrap(), err);
+        assert_eq!(
+            err.to_string(),
+            "Command 'docker image prune -f' exited with code 1: Cannot connect to the Docker daemon"
+        );
+    }
+
+    #[test]
+    fn test_explain_appends_remedy() {
+        let path = PathBuf::from("/some/path");
+        assert_eq!(
+            DevSweepError::NotFound { path: path.clone() }.explain(),
+            "Path does not exist: /some/path. Rescan; it may already have been removed"
+        );
+        assert_eq!(
+            DevSweepError::Io {
+                path,
+                message: "disk full".to_string()
+            }
+            .explain(),
+            "/some/path: disk full"
+        );
+    }
+}
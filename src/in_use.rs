@@ -0,0 +1,422 @@
+//! Detecting items that are in use
+//!
+//! Quarantining a `node_modules` while a dev server runs from it, or a Gradle
+//! cache mid-build, breaks whatever is using it. After each scan, and again
+//! right before a cleanup, items are checked against
+//!
+//! - the processes running now: their working directories and the files they
+//!   have open or mapped, read from `/proc` on Linux and `lsof` elsewhere;
+//! - lock files owned by a running process: `postmaster.pid` and
+//!   `mongod.lock` (which hold the owner's pid) and a Chromium browser's
+//!   `SingletonLock` (a link to `host-pid`) in or up to a few levels above the
+//!   item, and the `.lock` files in Gradle caches, whose holder is asked from
+//!   the kernel.
+//!
+//! The process table is read once per scan or cleanup; checking an item only
+//! looks up its path.
+
+use crate::backend::CategoryData;
+use crate::checkers::ScanContext;
+use crate::types::{CleanupItem, InUse};
+use std::fs;
+use std::os::unix::io::AsRawFd;
+use std::path::{Component, Path, PathBuf};
+
+/// How far above an item lock files are looked for (a Chromium profile's
+/// cache is two levels below the `SingletonLock`)
+const LOCK_SEARCH_LEVELS: usize = 3;
+
+/// Depth below a Gradle directory at which `.lock` files are looked for
+const GRADLE_LOCK_DEPTH: usize = 2;
+
+/// How the owner of a lock file is found
+#[derive(Debug, Clone, Copy)]
+enum LockOwner {
+    /// The first line of the file is the owner's pid
+    PidFile,
+    /// The file is a symlink to `<host>-<pid>`
+    SingletonLink,
+}
+
+/// Lock files left by running tools, by name
+const LOCK_FILES: &[(&str, LockOwner)] = &[
+    ("postmaster.pid", LockOwner::PidFile),
+    ("mongod.lock", LockOwner::PidFile),
+    ("SingletonLock", LockOwner::SingletonLink),
+];
+
+/// A running process and the paths it uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cwd: Option<PathBuf>,
+    /// Files it has open or mapped into memory
+    pub files: Vec<PathBuf>,
+}
+
+/// The processes running at one moment, indexed by the paths they use
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTable {
+    processes: Vec<ProcessInfo>,
+    /// Every path in use with the index of its process, sorted by path so
+    /// everything under a directory is one contiguous run
+    paths: Vec<(PathBuf, usize)>,
+}
+
+impl ProcessTable {
+    pub fn new(processes: Vec<ProcessInfo>) -> Self {
+        let mut paths: Vec<(PathBuf, usize)> = processes
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, process)| {
+                process
+                    .cwd
+                    .iter()
+                    .chain(&process.files)
+                    .map(move |path| (path.clone(), idx))
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        Self { processes, paths }
+    }
+
+    /// The processes running now, other than this one. Read from `/proc` on
+    /// Linux and from `lsof` (through `ctx`'s runner) elsewhere; empty if
+    /// neither is available.
+    pub fn load(ctx: &ScanContext) -> Self {
+        let own = std::process::id();
+        Self::new(
+            system_processes(ctx)
+                .into_iter()
+                .filter(|p| p.pid != own)
+                .collect(),
+        )
+    }
+
+    /// Parse the output of `lsof -nPw -F pcn`
+    pub fn from_lsof(output: &str) -> Self {
+        let mut processes: Vec<ProcessInfo> = Vec::new();
+        let mut fd = "";
+        for line in output.lines() {
+            let Some(field) = line.get(..1) else {
+                continue;
+            };
+            let value = &line[1..];
+            match field {
+                "p" => {
+                    if let Ok(pid) = value.parse() {
+                        processes.push(ProcessInfo {
+                            pid,
+                            name: String::new(),
+                            cwd: None,
+                            files: Vec::new(),
+                        });
+                    }
+                }
+                "c" => {
+                    if let Some(process) = processes.last_mut() {
+                        process.name = value.to_string();
+                    }
+                }
+                "f" => fd = value,
+                // Sockets and pipes have names that aren't paths
+                "n" if value.starts_with('/') => {
+                    if let Some(process) = processes.last_mut() {
+                        if fd == "cwd" {
+                            process.cwd = Some(PathBuf::from(value));
+                        } else {
+                            process.files.push(PathBuf::from(value));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self::new(processes)
+    }
+
+    pub fn processes(&self) -> &[ProcessInfo] {
+        &self.processes
+    }
+
+    /// A process working in `path` or using a file under it
+    pub fn user_of(&self, path: &Path) -> Option<&ProcessInfo> {
+        let start = self.paths.partition_point(|(p, _)| p.as_path() < path);
+        self.paths[start..]
+            .first()
+            .filter(|(p, _)| p.starts_with(path))
+            .map(|&(_, idx)| &self.processes[idx])
+    }
+
+    fn name_of(&self, pid: u32) -> Option<String> {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.name.clone())
+    }
+}
+
+/// Checks whether items are in use right now
+#[derive(Debug, Clone, Default)]
+pub struct InUseDetector {
+    processes: ProcessTable,
+}
+
+impl InUseDetector {
+    /// Detector with the processes running now
+    pub fn new(ctx: &ScanContext) -> Self {
+        Self::with_processes(ProcessTable::load(ctx))
+    }
+
+    pub fn with_processes(processes: ProcessTable) -> Self {
+        Self { processes }
+    }
+
+    /// What is using `path`, if anything
+    pub fn check(&self, path: &Path) -> Option<InUse> {
+        if let Some(process) = self.processes.user_of(path) {
+            return Some(InUse::Process {
+                pid: process.pid,
+                name: process.name.clone(),
+            });
+        }
+        self.pid_lock(path).or_else(|| self.gradle_lock(path))
+    }
+
+    /// What is using `item`'s path, or any path of a grouped item
+    pub fn check_item(&self, item: &CleanupItem) -> Option<InUse> {
+        if item.cleanup_action.is_some() {
+            return None;
+        }
+        match (&item.path, &item.details) {
+            (Some(path), _) => self.check(path),
+            (None, Some(details)) if item.is_group => details
+                .iter()
+                .filter_map(|detail| detail.path.as_deref())
+                .find_map(|path| self.check(path)),
+            (None, _) => None,
+        }
+    }
+
+    /// Set `in_use` on every item of `categories`
+    pub fn mark(&self, categories: &mut [CategoryData]) {
+        for category in categories.iter_mut() {
+            for item in &mut category.items {
+                item.in_use = self.check_item(item);
+            }
+        }
+    }
+
+    /// A pid or singleton lock in `path` or a few levels above it whose
+    /// owner is running
+    fn pid_lock(&self, path: &Path) -> Option<InUse> {
+        path.ancestors()
+            .take(LOCK_SEARCH_LEVELS + 1)
+            .flat_map(|dir| {
+                LOCK_FILES
+                    .iter()
+                    .map(move |(name, owner)| (dir.join(name), *owner))
+            })
+            .find_map(|(lock, owner)| {
+                let pid = lock_owner(&lock, owner).filter(|pid| is_running(*pid))?;
+                Some(self.lock_file(lock, pid))
+            })
+    }
+
+    /// A `.lock` file in a Gradle directory that a process holds locked
+    fn gradle_lock(&self, path: &Path) -> Option<InUse> {
+        let in_gradle = path
+            .components()
+            .any(|c| c == Component::Normal(".gradle".as_ref()));
+        if !in_gradle {
+            return None;
+        }
+        lock_files_below(path, GRADLE_LOCK_DEPTH)
+            .into_iter()
+            .find_map(|lock| {
+                let pid = lock_holder(&lock)?;
+                Some(self.lock_file(lock, pid))
+            })
+    }
+
+    fn lock_file(&self, path: PathBuf, pid: u32) -> InUse {
+        InUse::LockFile {
+            path,
+            pid,
+            name: self.processes.name_of(pid),
+        }
+    }
+}
+
+/// The pid a lock file names, if it is one and names a pid
+fn lock_owner(lock: &Path, owner: LockOwner) -> Option<u32> {
+    match owner {
+        LockOwner::PidFile => fs::read_to_string(lock)
+            .ok()?
+            .lines()
+            .next()?
+            .trim()
+            .parse()
+            .ok(),
+        LockOwner::SingletonLink => {
+            let target = fs::read_link(lock).ok()?;
+            let (_, pid) = target.to_str()?.rsplit_once('-')?;
+            pid.parse().ok()
+        }
+    }
+}
+
+/// Whether a process with `pid` exists (possibly owned by another user)
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: signal 0 only checks that the process exists
+    let exists = unsafe { libc::kill(pid, 0) } == 0;
+    exists || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// The process holding a POSIX lock on `lock`, as Gradle does while it uses
+/// a cache
+fn lock_holder(lock: &Path) -> Option<u32> {
+    let file = fs::File::open(lock).ok()?;
+    // SAFETY: flock is plain data; zero is a valid value for every field
+    let mut query: libc::flock = unsafe { std::mem::zeroed() };
+    query.l_type = libc::F_WRLCK as libc::c_short;
+    query.l_whence = libc::SEEK_SET as libc::c_short;
+    // SAFETY: the descriptor is open for the duration of the call, and
+    // F_GETLK only writes to `query`
+    let result = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut query) };
+    if result != 0 || query.l_type == libc::F_UNLCK as libc::c_short {
+        return None;
+    }
+    u32::try_from(query.l_pid).ok().filter(|pid| *pid != 0)
+}
+
+/// `.lock` files at most `depth` levels below `dir`
+fn lock_files_below(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut locks = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_file() && path.extension().is_some_and(|ext| ext == "lock") {
+            locks.push(path);
+        } else if file_type.is_dir() && depth > 0 {
+            locks.extend(lock_files_below(&path, depth - 1));
+        }
+    }
+    locks
+}
+
+#[cfg(target_os = "linux")]
+fn system_processes(_ctx: &ScanContext) -> Vec<ProcessInfo> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let dir = entry.path();
+            // Processes of other users can't be inspected; skip them
+            let name = fs::read_to_string(dir.join("comm"))
+                .ok()?
+                .trim()
+                .to_string();
+            let mut files: Vec<PathBuf> = fs::read_dir(dir.join("fd"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|fd| fs::read_link(fd.path()).ok())
+                .filter(|path| path.is_absolute())
+                .collect();
+            // Mapped files: executables, shared libraries, native node modules
+            if let Ok(maps) = fs::read_to_string(dir.join("maps")) {
+                files.extend(
+                    maps.lines()
+                        .filter_map(|line| line.find('/').map(|start| &line[start..]))
+                        .map(PathBuf::from),
+                );
+            }
+            files.sort();
+            files.dedup();
+            Some(ProcessInfo {
+                pid,
+                name,
+                cwd: fs::read_link(dir.join("cwd")).ok(),
+                files,
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn system_processes(ctx: &ScanContext) -> Vec<ProcessInfo> {
+    // lsof exits with 1 when some files couldn't be listed; what it did list
+    // is still valid
+    ctx.output(ctx.command("lsof").args(["-nPw", "-F", "pcn"]))
+        .map(|output| ProcessTable::from_lsof(&output.stdout).processes)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSOF: &str = include_str!("../tests/fixtures/commands/lsof.txt");
+
+    #[test]
+    fn test_parses_lsof_output() {
+        let table = ProcessTable::from_lsof(LSOF);
+        assert_eq!(table.processes().len(), 2);
+
+        let node = &table.processes()[0];
+        assert_eq!(node.name, "node");
+        assert_eq!(node.cwd, Some(PathBuf::from("/Users/me/Projects/app")));
+
+        let user = |path: &str| table.user_of(Path::new(path)).map(|p| p.name.as_str());
+        assert_eq!(user("/Users/me/Projects/app/node_modules"), Some("node"));
+        assert_eq!(
+            user("/Users/me/Library/Caches/JetBrains/IntelliJIdea2024.1"),
+            Some("idea")
+        );
+        assert_eq!(user("/Users/me/Projects/other/node_modules"), None);
+        // A sibling whose name starts the same is not inside
+        assert_eq!(user("/Users/me/Projects/ap"), None);
+    }
+
+    #[test]
+    fn test_pid_lock_of_a_running_process() {
+        let data = tempfile::tempdir().unwrap();
+        let wal = data.path().join("pg_wal");
+        fs::create_dir_all(&wal).unwrap();
+        let detector = InUseDetector::default();
+        assert_eq!(detector.check(&wal), None);
+
+        // postmaster.pid naming this test process, one level above the item
+        let lock = data.path().join("postmaster.pid");
+        fs::write(
+            &lock,
+            format!("{}\n{}\n", std::process::id(), data.path().display()),
+        )
+        .unwrap();
+        assert_eq!(
+            detector.check(&wal),
+            Some(InUse::LockFile {
+                path: lock,
+                pid: std::process::id(),
+                name: None,
+            })
+        );
+    }
+}
@@ -7,20 +7,38 @@ pub mod app;
 pub mod assets;
 pub mod backend;
 pub mod cache_settings;
+pub mod cancel;
 pub mod checkers;
 pub mod cleanup_history;
+pub mod cleanup_plan;
+pub mod cli;
+pub mod command_runner;
 pub mod custom_paths;
+pub mod deletion_policy;
+pub mod disk_usage;
+pub mod error;
+pub mod in_use;
+pub mod mounts;
+pub mod overlap;
+pub mod preflight;
+pub mod report;
 pub mod scan_cache;
+pub mod scan_mode;
+pub mod scan_progress;
 pub mod single_instance;
+pub mod size_index;
 pub mod types;
 pub mod ui;
 pub mod update_checker;
 pub mod utils;
+pub mod walk;
 
 // Re-export commonly used types for convenience
 pub use backend::{CategoryData, StorageBackend};
 pub use cache_settings::CacheSettings;
 pub use cleanup_history::{CleanupHistory, CleanupRecord, HistoryStats};
+pub use cleanup_plan::{CleanupMode, CleanupPlan};
+pub use error::DevSweepError;
 pub use scan_cache::ScanCache;
 pub use types::{CheckResult, CleanupItem, ItemDetail};
 pub use update_checker::UpdateInfo;
@@ -42,6 +42,12 @@ fn setup_single_instance_listener(cx: &mut AppContext) {
 }
 
 fn main() {
+    // Subcommands run headless and never open a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if devsweep::cli::is_cli_invocation(&args) {
+        std::process::exit(devsweep::cli::run(&args));
+    }
+
     // Check if another instance is already running
     if try_activate_existing_instance() {
         return;
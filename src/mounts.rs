@@ -0,0 +1,271 @@
+//! Mounted filesystems
+//!
+//! Directory walks stay on the device they start on (see `walk`), but a walk
+//! can also *start* on a network share or FUSE filesystem: a custom path on
+//! an SMB share, a project root mounted with sshfs or rclone. Listing those
+//! can hang for minutes and their sizes say nothing about local disk space,
+//! so scans leave them alone. The mount table is read from
+//! `/proc/self/mountinfo` on Linux and `getfsstat` on macOS.
+
+use std::path::{Path, PathBuf};
+
+/// Filesystem types served over the network
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "davfs",
+    "ftp",
+    "ceph",
+    "glusterfs",
+    "afs",
+    "9p",
+];
+
+/// Filesystem types implemented by a userspace process (other than `fuse.*`)
+const FUSE_FS_TYPES: &[&str] = &["fuse", "osxfuse", "macfuse", "fusefs"];
+
+/// Where a mounted filesystem's data lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountKind {
+    /// A local disk, or a virtual filesystem in memory
+    Local,
+    /// A share on another machine (NFS, SMB, AFP, WebDAV, ...)
+    Network,
+    /// A FUSE filesystem (sshfs, rclone, ...), which may be remote or slow
+    Fuse,
+}
+
+impl MountKind {
+    /// Classify a filesystem type as reported by the mount table
+    pub fn of_fs_type(fs_type: &str) -> Self {
+        // ntfs-3g and friends mount local block devices through FUSE
+        if fs_type == "fuseblk" {
+            return Self::Local;
+        }
+        if NETWORK_FS_TYPES.contains(&fs_type)
+            || matches!(fs_type, "fuse.sshfs" | "fuse.rclone" | "fuse.s3fs")
+        {
+            Self::Network
+        } else if FUSE_FS_TYPES.contains(&fs_type) || fs_type.starts_with("fuse.") {
+            Self::Fuse
+        } else {
+            Self::Local
+        }
+    }
+}
+
+/// One mounted filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub kind: MountKind,
+}
+
+impl Mount {
+    pub fn new(mount_point: impl Into<PathBuf>, fs_type: &str) -> Self {
+        Self {
+            mount_point: mount_point.into(),
+            fs_type: fs_type.to_string(),
+            kind: MountKind::of_fs_type(fs_type),
+        }
+    }
+
+    /// Network and FUSE mounts, which scans don't walk
+    pub fn is_remote(&self) -> bool {
+        self.kind != MountKind::Local
+    }
+}
+
+/// The filesystems mounted when a scan started
+#[derive(Debug, Clone, Default)]
+pub struct MountTable {
+    mounts: Vec<Mount>,
+}
+
+impl MountTable {
+    pub fn new(mounts: Vec<Mount>) -> Self {
+        Self { mounts }
+    }
+
+    pub const fn empty() -> Self {
+        Self { mounts: Vec::new() }
+    }
+
+    /// The current mount table; empty if it can't be read
+    pub fn load() -> Self {
+        Self::new(system_mounts())
+    }
+
+    /// Parse the contents of `/proc/self/mountinfo`
+    pub fn from_mountinfo(mountinfo: &str) -> Self {
+        let mounts = mountinfo
+            .lines()
+            .filter_map(|line| {
+                let (fields, rest) = line.split_once(" - ")?;
+                let mount_point = fields.split(' ').nth(4)?;
+                let fs_type = rest.split(' ').next()?;
+                Some(Mount::new(unescape_mountinfo(mount_point), fs_type))
+            })
+            .collect();
+        Self::new(mounts)
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+
+    /// The mount `path` is on: the one with the longest mount point above it
+    pub fn mount_of(&self, path: &Path) -> Option<&Mount> {
+        self.mounts
+            .iter()
+            .filter(|m| path.starts_with(&m.mount_point))
+            .max_by_key(|m| m.mount_point.components().count())
+    }
+
+    /// The network or FUSE mount `path` is on, if any
+    pub fn remote_mount_of(&self, path: &Path) -> Option<&Mount> {
+        self.mount_of(path).filter(|m| m.is_remote())
+    }
+
+    /// Mounts strictly inside `path`; walks of `path` stop at them
+    pub fn mounts_below<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Mount> {
+        self.mounts
+            .iter()
+            .filter(move |m| m.mount_point != path && m.mount_point.starts_with(path))
+    }
+}
+
+/// A status line listing the mounts a checker left out, if there were any
+pub fn describe_skipped(mounts: &[Mount]) -> Option<String> {
+    let names: Vec<String> = mounts
+        .iter()
+        .map(|m| format!("{} ({})", m.mount_point.display(), m.fs_type))
+        .collect();
+    match names.as_slice() {
+        [] => None,
+        [name] => Some(format!("Skipped mounted filesystem {}", name)),
+        names => Some(format!(
+            "Skipped {} mounted filesystems: {}",
+            names.len(),
+            names.join(", ")
+        )),
+    }
+}
+
+/// Decode the octal escapes (`\040` for a space) mountinfo uses in paths
+fn unescape_mountinfo(field: &str) -> PathBuf {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).and_then(|digits| {
+            let digits = std::str::from_utf8(digits).ok()?;
+            u8::from_str_radix(digits, 8).ok()
+        });
+        match (bytes[i], octal) {
+            (b'\\', Some(byte)) => {
+                decoded.push(byte);
+                i += 4;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+#[cfg(target_os = "linux")]
+fn system_mounts() -> Vec<Mount> {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .map(|mountinfo| MountTable::from_mountinfo(&mountinfo).mounts)
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn system_mounts() -> Vec<Mount> {
+    use std::ffi::CStr;
+
+    let name = |chars: &[libc::c_char]| {
+        // SAFETY: statfs names are NUL-terminated within their fixed-size arrays
+        unsafe { CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    // SAFETY: a null buffer only asks for the number of mounts
+    let count = unsafe { libc::getfsstat(std::ptr::null_mut(), 0, libc::MNT_NOWAIT) };
+    if count <= 0 {
+        return Vec::new();
+    }
+    // Room for a few mounts appearing between the two calls
+    let capacity = count as usize + 8;
+    let mut stats: Vec<libc::statfs> = Vec::with_capacity(capacity);
+    let size = (capacity * std::mem::size_of::<libc::statfs>()) as libc::c_int;
+    // SAFETY: the buffer holds `capacity` entries and `size` says so
+    let count = unsafe { libc::getfsstat(stats.as_mut_ptr(), size, libc::MNT_NOWAIT) };
+    if count <= 0 {
+        return Vec::new();
+    }
+    // SAFETY: getfsstat initialised the first `count` entries
+    unsafe { stats.set_len(count as usize) };
+
+    stats
+        .iter()
+        .map(|stat| Mount::new(name(&stat.f_mntonname), &name(&stat.f_fstypename)))
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn system_mounts() -> Vec<Mount> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+35 22 0:31 / /mnt/nas rw,relatime shared:20 - nfs4 nas:/export rw,vers=4.2
+36 22 0:32 / /home/me/remote\\040box rw,nosuid shared:21 - fuse.sshfs me@box: rw
+37 22 8:17 / /media/usb rw,relatime shared:22 - fuseblk /dev/sdb1 rw";
+
+    #[test]
+    fn test_parses_mountinfo() {
+        let table = MountTable::from_mountinfo(MOUNTINFO);
+        assert_eq!(table.mounts().len(), 4);
+
+        let sshfs = table
+            .remote_mount_of(Path::new("/home/me/remote box/src"))
+            .unwrap();
+        assert_eq!(sshfs.fs_type, "fuse.sshfs");
+        assert_eq!(sshfs.kind, MountKind::Network);
+
+        assert_eq!(
+            table
+                .remote_mount_of(Path::new("/mnt/nas/photos"))
+                .unwrap()
+                .kind,
+            MountKind::Network
+        );
+        assert!(table.remote_mount_of(Path::new("/home/me")).is_none());
+        assert!(table.remote_mount_of(Path::new("/media/usb")).is_none());
+        assert_eq!(table.mounts_below(Path::new("/home/me")).count(), 1);
+    }
+
+    #[test]
+    fn test_classifies_fs_types() {
+        assert_eq!(MountKind::of_fs_type("smbfs"), MountKind::Network);
+        assert_eq!(MountKind::of_fs_type("macfuse"), MountKind::Fuse);
+        assert_eq!(MountKind::of_fs_type("fuse.gocryptfs"), MountKind::Fuse);
+        assert_eq!(MountKind::of_fs_type("apfs"), MountKind::Local);
+    }
+}
@@ -0,0 +1,123 @@
+//! Items reported by more than one category
+//!
+//! Categories overlap: General Caches lists every large folder in
+//! `~/Library/Caches`, including the Homebrew, pip and browser caches that
+//! their own checkers also report. An item whose path is inside another
+//! deletable item's path is *covered* by it: deleting the outer item frees its
+//! space too, so it is left out of totals, and selecting both would delete it
+//! twice.
+
+use crate::backend::CategoryData;
+use crate::types::CleanupItem;
+use std::path::Path;
+
+/// The path deleted when `item` is cleaned, if cleaning it deletes a path
+fn deleted_path(item: &CleanupItem) -> Option<&Path> {
+    match item.cleanup_action {
+        Some(_) => None,
+        None => item.path.as_deref(),
+    }
+}
+
+/// Whether cleaning `outer` also deletes everything `item` would delete
+pub fn covers(outer: &CleanupItem, item: &CleanupItem) -> bool {
+    match (deleted_path(outer), deleted_path(item)) {
+        (Some(outer), Some(inner)) => inner.starts_with(outer),
+        _ => false,
+    }
+}
+
+/// Set `covered_by` on every item inside another category's or item's path
+/// and recompute category totals without them. Of two items with the same
+/// path, the one listed first (the more specific checker, in registry order)
+/// stays uncovered. Safe to call again after categories change.
+pub fn mark_overlaps(categories: &mut [CategoryData]) {
+    let deletions: Vec<(usize, usize, &Path)> = categories
+        .iter()
+        .enumerate()
+        .flat_map(|(c, category)| {
+            category
+                .items
+                .iter()
+                .enumerate()
+                .filter_map(move |(i, item)| deleted_path(item).map(|path| (c, i, path)))
+        })
+        .collect();
+
+    // For each covered item, the outermost path covering it
+    let covered: Vec<(usize, usize, std::path::PathBuf)> = deletions
+        .iter()
+        .filter_map(|&(c, i, path)| {
+            deletions
+                .iter()
+                .filter(|&&(oc, oi, outer)| {
+                    path.starts_with(outer) && (outer != path || (oc, oi) < (c, i))
+                })
+                .min_by_key(|(_, _, outer)| outer.components().count())
+                .map(|(_, _, outer)| (c, i, outer.to_path_buf()))
+        })
+        .collect();
+
+    for category in categories.iter_mut() {
+        for item in &mut category.items {
+            item.covered_by = None;
+        }
+    }
+    for (c, i, outer) in covered {
+        categories[c].items[i].covered_by = Some(outer);
+    }
+    for category in categories.iter_mut() {
+        let total_size = category
+            .items
+            .iter()
+            .filter(|item| item.covered_by.is_none())
+            .map(|item| item.size)
+            .sum();
+        category.set_total_size(total_size);
+    }
+}
+
+/// Drop items that another item in `items` already covers, keeping the first
+/// of several items with the same path. Returns how many were dropped.
+pub fn drop_covered(items: &mut Vec<CleanupItem>) -> usize {
+    let before = items.len();
+    let mut kept: Vec<CleanupItem> = Vec::with_capacity(items.len());
+    for item in items.drain(..) {
+        if kept.iter().any(|k| covers(k, &item)) {
+            continue;
+        }
+        // A later item may cover ones kept earlier
+        kept.retain(|k| !covers(&item, k));
+        kept.push(item);
+    }
+    *items = kept;
+    before - items.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CleanupAction;
+    use std::path::PathBuf;
+
+    fn item(path: &str) -> CleanupItem {
+        CleanupItem::new(path, 10, "10 B").with_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_commands_do_not_cover() {
+        let gc = item("/repo").with_cleanup_action(CleanupAction::command("git", ["gc"]));
+        assert!(!covers(&gc, &item("/repo/.git")));
+        assert!(!covers(&item("/repo"), &gc));
+        assert!(covers(&item("/repo"), &item("/repo/.git")));
+        assert!(!covers(&item("/repo"), &item("/repository")));
+    }
+
+    #[test]
+    fn test_drop_covered_keeps_outermost() {
+        let mut items = vec![item("/a/b"), item("/c"), item("/a"), item("/a/b/c")];
+        assert_eq!(drop_covered(&mut items), 2);
+        let paths: Vec<_> = items.iter().map(|i| i.item_type.as_str()).collect();
+        assert_eq!(paths, vec!["/c", "/a"]);
+    }
+}
@@ -0,0 +1,309 @@
+//! Pre-flight check of a cleanup
+//!
+//! Selected items are captured by a scan that may be hours old, or served
+//! from the scan cache. Before anything is moved, `Preflight::run` re-stats
+//! every selected path: items whose path is gone are dropped. A path whose
+//! metadata and subdirectory fingerprint still match what the scan cache
+//! recorded is taken as unchanged; any other path is walked again, and items
+//! whose size changed by more than `SIZE_CHANGE_THRESHOLD` are re-sized, so
+//! the user confirms (and the history records) what is on disk now rather
+//! than what the scan saw. Like the cache itself, this misses files growing
+//! in place inside an otherwise untouched directory.
+
+use crate::checkers::ScanContext;
+use crate::scan_cache::ScanCache;
+use crate::types::{CleanupItem, ItemDetail};
+use crate::utils::format_size;
+use std::fs;
+use std::path::Path;
+
+/// Relative size change above which an item is reported as resized
+pub const SIZE_CHANGE_THRESHOLD: f64 = 0.1;
+
+/// Size changes smaller than this are never reported, whatever their ratio
+pub const MIN_SIZE_CHANGE: u64 = 1024 * 1024;
+
+/// How a selected item differs from what the scan reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemChange {
+    /// Its path no longer exists; it is left out of the cleanup
+    Vanished,
+    /// Its size changed beyond the threshold
+    Resized { scanned: u64, current: u64 },
+}
+
+impl ItemChange {
+    /// e.g. "grew from 1.0 GB to 2.1 GB"
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Vanished => "no longer exists".to_string(),
+            Self::Resized { scanned, current } => format!(
+                "{} from {} to {}",
+                if current > scanned { "grew" } else { "shrank" },
+                format_size(*scanned),
+                format_size(*current)
+            ),
+        }
+    }
+}
+
+/// A selected item as it is on disk now
+#[derive(Debug, Clone)]
+pub struct CheckedItem {
+    /// The item with its current size (as scanned if vanished)
+    pub item: CleanupItem,
+    pub change: Option<ItemChange>,
+}
+
+/// The result of re-checking a selection, in selection order
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    pub checked: Vec<CheckedItem>,
+}
+
+impl Preflight {
+    /// Re-check `items` against the disk, comparing paths with what `cache`
+    /// tracked and sizing the changed ones with `ctx`
+    ///
+    /// Items cleaned by a command and items with an estimated size are kept
+    /// as they are.
+    pub fn run(items: &[CleanupItem], ctx: &ScanContext, cache: &ScanCache) -> Self {
+        Self {
+            checked: items
+                .iter()
+                .map(|item| check_item(item, ctx, cache))
+                .collect(),
+        }
+    }
+
+    /// The items to clean: every item that still exists, with its current size
+    pub fn items(&self) -> Vec<CleanupItem> {
+        self.checked
+            .iter()
+            .filter(|c| c.change != Some(ItemChange::Vanished))
+            .map(|c| c.item.clone())
+            .collect()
+    }
+
+    /// Items that differ from the scan, with how
+    pub fn changes(&self) -> impl Iterator<Item = (&CleanupItem, ItemChange)> {
+        self.checked
+            .iter()
+            .filter_map(|c| c.change.map(|change| (&c.item, change)))
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.changes().next().is_some()
+    }
+
+    /// Bytes the remaining items take up now
+    pub fn total_bytes(&self) -> u64 {
+        self.items().iter().map(|item| item.size).sum()
+    }
+
+    /// One-line description, e.g. "Since the scan, 1 item no longer exists
+    /// and 2 changed size; 3.1 GB to clean"
+    pub fn summary(&self) -> String {
+        let vanished = self
+            .changes()
+            .filter(|(_, change)| *change == ItemChange::Vanished)
+            .count();
+        let resized = self.changes().count() - vanished;
+
+        let mut parts = Vec::new();
+        match vanished {
+            0 => {}
+            1 => parts.push("1 item no longer exists".to_string()),
+            n => parts.push(format!("{} items no longer exist", n)),
+        }
+        if resized > 0 {
+            parts.push(format!("{} changed size", resized));
+        }
+        if parts.is_empty() {
+            return format!(
+                "All selected items match the scan; {} to clean",
+                format_size(self.total_bytes())
+            );
+        }
+        format!(
+            "Since the scan, {}; {} to clean",
+            parts.join(" and "),
+            format_size(self.total_bytes())
+        )
+    }
+}
+
+fn check_item(item: &CleanupItem, ctx: &ScanContext, cache: &ScanCache) -> CheckedItem {
+    let unchanged = || CheckedItem {
+        item: item.clone(),
+        change: None,
+    };
+    if item.cleanup_action.is_some() || item.is_estimate {
+        return unchanged();
+    }
+
+    let mut current = item.clone();
+    match (&item.path, &item.details) {
+        (Some(path), _) => {
+            let Some(size) = current_size(path, item.size, ctx, cache) else {
+                return CheckedItem {
+                    item: item.clone(),
+                    change: Some(ItemChange::Vanished),
+                };
+            };
+            current.size = size;
+        }
+        // A group of paths reported as one item: drop the parts that are gone
+        (None, Some(details)) if item.is_group => {
+            let remaining: Vec<ItemDetail> = details
+                .iter()
+                .filter_map(|detail| {
+                    let size = current_size(detail.path.as_deref()?, detail.size, ctx, cache)?;
+                    let mut detail = detail.clone();
+                    if is_significant(detail.size, size) {
+                        detail.size = size;
+                        detail.size_str = format_size(size);
+                    }
+                    Some(detail)
+                })
+                .collect();
+            if remaining.is_empty() {
+                return CheckedItem {
+                    item: item.clone(),
+                    change: Some(ItemChange::Vanished),
+                };
+            }
+            current.size = remaining.iter().map(|d| d.size).sum();
+            current.details = Some(remaining);
+        }
+        _ => return unchanged(),
+    }
+
+    if current.size == item.size
+        || (!is_significant(item.size, current.size)
+            && current.details.as_ref().map(Vec::len) == item.details.as_ref().map(Vec::len))
+    {
+        return unchanged();
+    }
+    current.size_str = format_size(current.size);
+    // The reclaimable share is unknown for the new size
+    current.reclaimable = None;
+    CheckedItem {
+        change: Some(ItemChange::Resized {
+            scanned: item.size,
+            current: current.size,
+        }),
+        item: current,
+    }
+}
+
+/// Size of `path` now, or `None` if it no longer exists. A path unchanged
+/// since the scan keeps its `scanned` size without being walked.
+fn current_size(path: &Path, scanned: u64, ctx: &ScanContext, cache: &ScanCache) -> Option<u64> {
+    fs::symlink_metadata(path).ok()?;
+    match cache.tracked_metadata(path) {
+        Some(metadata) if !metadata.has_changed(path) => Some(scanned),
+        _ => Some(ctx.dir_size(path)),
+    }
+}
+
+/// Whether a change from `scanned` to `current` bytes is worth reporting
+fn is_significant(scanned: u64, current: u64) -> bool {
+    let delta = scanned.abs_diff(current);
+    delta >= MIN_SIZE_CHANGE && delta as f64 > scanned as f64 * SIZE_CHANGE_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_cache::PathTracker;
+    use crate::types::CheckResult;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_small_changes_are_not_significant() {
+        assert!(!is_significant(100, 4_000));
+        assert!(!is_significant(
+            100 * MIN_SIZE_CHANGE,
+            105 * MIN_SIZE_CHANGE
+        ));
+        assert!(is_significant(MIN_SIZE_CHANGE, 3 * MIN_SIZE_CHANGE));
+        assert!(is_significant(10 * MIN_SIZE_CHANGE, 0));
+    }
+
+    #[test]
+    fn test_vanished_and_resized_items() {
+        let temp = tempfile::tempdir().unwrap();
+        let grown = temp.path().join("target");
+        fs::create_dir_all(&grown).unwrap();
+        fs::write(
+            grown.join("big.bin"),
+            vec![0u8; 2 * MIN_SIZE_CHANGE as usize],
+        )
+        .unwrap();
+        let kept = temp.path().join("kept.txt");
+        fs::write(&kept, "12345").unwrap();
+
+        let items = vec![
+            CleanupItem::new("target", 100, "100 B").with_path(grown.clone()),
+            CleanupItem::new("gone", 100, "100 B").with_path(PathBuf::from("/nonexistent/gone")),
+            CleanupItem::new("kept", 5, "5 B").with_path(kept),
+        ];
+        let preflight = Preflight::run(
+            &items,
+            &ScanContext::for_home(temp.path()),
+            &ScanCache::new(),
+        );
+
+        let changes: Vec<(&str, ItemChange)> = preflight
+            .changes()
+            .map(|(item, change)| (item.item_type.as_str(), change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "target",
+                    ItemChange::Resized {
+                        scanned: 100,
+                        current: 2 * MIN_SIZE_CHANGE
+                    }
+                ),
+                ("gone", ItemChange::Vanished),
+            ]
+        );
+        let remaining = preflight.items();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].size, 2 * MIN_SIZE_CHANGE);
+        assert_eq!(preflight.total_bytes(), 2 * MIN_SIZE_CHANGE + 5);
+        assert!(preflight.summary().contains("1 item no longer exists"));
+    }
+
+    #[test]
+    fn test_paths_unchanged_since_the_scan_keep_their_size() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_dir = temp.path().join("cache");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("a.bin"), vec![0u8; 1000]).unwrap();
+
+        // Tracked as scanned, with a size the walk would not find
+        let mut tracker = PathTracker::new();
+        tracker.track(&cache_dir);
+        let mut cache = ScanCache::new();
+        let mut result = CheckResult::new("Cache");
+        result.add_item(
+            CleanupItem::new("cache", 50 * MIN_SIZE_CHANGE, "50 MB").with_path(cache_dir.clone()),
+        );
+        cache.update_category("Cache".to_string(), result.clone(), tracker.into_paths());
+
+        let ctx = ScanContext::for_home(temp.path());
+        let preflight = Preflight::run(&result.items, &ctx, &cache);
+        assert!(!preflight.has_changes());
+
+        // A new entry changes the directory's mtime, so it is walked again
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(cache_dir.join("b.bin"), vec![0u8; 1000]).unwrap();
+        let preflight = Preflight::run(&result.items, &ctx, &cache);
+        assert_eq!(preflight.items()[0].size, 2000);
+    }
+}
@@ -0,0 +1,267 @@
+//! Machine-readable scan reports
+//!
+//! A report is a versioned snapshot of a scan that can be diffed, fed to
+//! dashboards or attached to bug reports. It is written as a single JSON
+//! document, as NDJSON with one item per line, or as a flat CSV table.
+//! Bump `REPORT_VERSION` whenever a field is renamed or removed.
+
+use crate::backend::CategoryData;
+use crate::checkers::registry;
+use crate::types::{CleanupItem, SuperCategoryType};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::SystemTime;
+
+/// Version of the report layout
+pub const REPORT_VERSION: u32 = 2;
+
+/// Columns of the CSV format, in order
+pub const CSV_COLUMNS: &[&str] = &[
+    "report_version",
+    "item_id",
+    "category_id",
+    "category",
+    "super_category",
+    "item_type",
+    "path",
+    "size",
+    "size_str",
+    "reclaimable",
+    "safe_to_delete",
+    "warning",
+    "cleanup_command",
+    "detail_count",
+    "scanned_at",
+    "from_cache",
+];
+
+/// Output format of a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// One JSON document with every category
+    Json,
+    /// One JSON object per item, one per line
+    Ndjson,
+    /// One row per item
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "ndjson" => Some(Self::Ndjson),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// A complete scan report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    pub report_version: u32,
+    pub devsweep_version: String,
+    #[serde(with = "timestamp")]
+    pub generated_at: SystemTime,
+    pub total_size: u64,
+    pub item_count: usize,
+    /// Number of categories served from the scan cache
+    pub cached_categories: usize,
+    pub categories: Vec<ReportCategory>,
+}
+
+/// A category in a report, with its stable id and grouping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportCategory {
+    pub id: String,
+    pub super_category: SuperCategoryType,
+    #[serde(flatten)]
+    pub data: CategoryData,
+}
+
+/// A single item, as written to each NDJSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportItem {
+    pub report_version: u32,
+    pub item_id: String,
+    pub category_id: String,
+    pub category: String,
+    pub super_category: SuperCategoryType,
+    #[serde(with = "timestamp")]
+    pub scanned_at: SystemTime,
+    pub from_cache: bool,
+    #[serde(flatten)]
+    pub item: CleanupItem,
+}
+
+impl ScanReport {
+    /// Build a report from scan results, in the order given
+    pub fn new(categories: &[CategoryData]) -> Self {
+        let categories: Vec<ReportCategory> = categories
+            .iter()
+            .map(|data| ReportCategory {
+                id: category_id(&data.name),
+                super_category: SuperCategoryType::from_category_name(&data.name),
+                data: data.clone(),
+            })
+            .collect();
+
+        Self {
+            report_version: REPORT_VERSION,
+            devsweep_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: SystemTime::now(),
+            total_size: categories.iter().map(|c| c.data.total_size).sum(),
+            item_count: categories.iter().map(|c| c.data.items.len()).sum(),
+            cached_categories: categories.iter().filter(|c| c.data.from_cache).count(),
+            categories,
+        }
+    }
+
+    /// Every item in the report, flattened in category order
+    pub fn items(&self) -> impl Iterator<Item = ReportItem> + '_ {
+        let report_version = self.report_version;
+        self.categories.iter().flat_map(move |category| {
+            category
+                .data
+                .items
+                .iter()
+                .enumerate()
+                .map(move |(idx, item)| ReportItem {
+                    report_version,
+                    item_id: item_id(&category.id, idx),
+                    category_id: category.id.clone(),
+                    category: category.data.name.clone(),
+                    super_category: category.super_category,
+                    scanned_at: category.data.scanned_at,
+                    from_cache: category.data.from_cache,
+                    item: item.clone(),
+                })
+        })
+    }
+
+    /// Write the report in the given format
+    pub fn write(&self, format: ReportFormat, out: &mut dyn Write) -> Result<(), String> {
+        let io_err = |e: std::io::Error| format!("Failed to write report: {}", e);
+        let json_err = |e: serde_json::Error| format!("Failed to serialize report: {}", e);
+
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, self).map_err(json_err)?;
+                writeln!(out).map_err(io_err)
+            }
+            ReportFormat::Ndjson => {
+                for item in self.items() {
+                    serde_json::to_writer(&mut *out, &item).map_err(json_err)?;
+                    writeln!(out).map_err(io_err)?;
+                }
+                Ok(())
+            }
+            ReportFormat::Csv => {
+                writeln!(out, "{}", CSV_COLUMNS.join(",")).map_err(io_err)?;
+                for item in self.items() {
+                    writeln!(out, "{}", csv_row(&item)).map_err(io_err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Stable id of a category, falling back to its name for unregistered ones
+pub fn category_id(category_name: &str) -> String {
+    registry::find_by_name(category_name)
+        .map(|c| c.id().to_string())
+        .unwrap_or_else(|| category_name.to_string())
+}
+
+/// Id of the item at `index` (from 0) in a category, e.g. `rust/1`
+pub fn item_id(category_id: &str, index: usize) -> String {
+    format!("{}/{}", category_id, index + 1)
+}
+
+fn csv_row(row: &ReportItem) -> String {
+    let item = &row.item;
+    let fields = [
+        row.report_version.to_string(),
+        row.item_id.clone(),
+        row.category_id.clone(),
+        row.category.clone(),
+        format!("{:?}", row.super_category),
+        item.item_type.clone(),
+        item.path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default(),
+        item.size.to_string(),
+        item.size_str.clone(),
+        item.reclaimable_size().to_string(),
+        item.safe_to_delete.to_string(),
+        item.warning.clone().unwrap_or_default(),
+        item.cleanup_action
+            .as_ref()
+            .map(|action| action.to_string())
+            .unwrap_or_default(),
+        item.details.as_ref().map_or(0, |d| d.len()).to_string(),
+        timestamp::format(row.scanned_at),
+        row.from_cache.to_string(),
+    ];
+    fields
+        .iter()
+        .map(|f| csv_escape(f))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Serde helpers writing `SystemTime` as an RFC 3339 UTC string
+pub mod timestamp {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::SystemTime;
+
+    pub fn format(time: SystemTime) -> String {
+        DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&s)
+            .map(SystemTime::from)
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(
+            ReportFormat::from_name("ndjson"),
+            Some(ReportFormat::Ndjson)
+        );
+        assert_eq!(ReportFormat::from_name("xml"), None);
+    }
+}
@@ -1,9 +1,20 @@
-use crate::types::{CheckResult, CleanupItem};
+use crate::checkers::registry;
+use crate::error::DevSweepError;
+use crate::types::{CheckResult, CleanupAction, CleanupItem, ExtraData};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// How many levels below a tracked directory are folded into its fingerprint
+pub const FINGERPRINT_DEPTH: usize = 3;
+
+/// Most subdirectories folded into one fingerprint, to keep validation cheap
+pub const FINGERPRINT_MAX_DIRS: usize = 512;
 
 /// Metadata for tracking file/directory changes
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,29 +22,78 @@ pub struct PathMetadata {
     pub modified_time: Option<SystemTime>,
     pub size: u64,
     pub is_dir: bool,
+    /// Hash of the modification times of the subdirectories down to
+    /// `FINGERPRINT_DEPTH`; unset for files and directories tracked shallowly
+    #[serde(default)]
+    pub dir_fingerprint: Option<u64>,
 }
 
 impl PathMetadata {
+    /// Metadata of `path`, with a fingerprint of its subdirectories if it is one
     pub fn from_path(path: &Path) -> Option<Self> {
+        let mut metadata = Self::shallow(path)?;
+        if metadata.is_dir {
+            metadata.dir_fingerprint = Some(dir_fingerprint(path));
+        }
+        Some(metadata)
+    }
+
+    /// Metadata of `path` itself; a directory's modification time only changes
+    /// when entries are added to or removed from it directly
+    pub fn shallow(path: &Path) -> Option<Self> {
         let metadata = fs::metadata(path).ok()?;
         Some(Self {
             modified_time: metadata.modified().ok(),
             size: metadata.len(),
             is_dir: metadata.is_dir(),
+            dir_fingerprint: None,
         })
     }
 
     pub fn has_changed(&self, path: &Path) -> bool {
-        match Self::from_path(path) {
+        match Self::shallow(path) {
             Some(current) => {
-                // Check if modified time or size changed
-                self.modified_time != current.modified_time || self.size != current.size
+                // Check if modified time or size changed, then anything nested
+                self.modified_time != current.modified_time
+                    || self.size != current.size
+                    || self
+                        .dir_fingerprint
+                        .is_some_and(|fingerprint| fingerprint != dir_fingerprint(path))
             }
             None => true, // Path no longer exists, so it changed
         }
     }
 }
 
+/// Hash of the paths and modification times of the directories below `path`
+///
+/// Creating or removing anything inside one of those directories changes its
+/// modification time, so nested growth (a new crate in the registry cache, a
+/// new package in `node_modules`) changes the fingerprint. Files growing in
+/// place are not noticed.
+fn dir_fingerprint(path: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for entry in WalkDir::new(path)
+        .min_depth(1)
+        .max_depth(FINGERPRINT_DEPTH)
+        .follow_links(false)
+        .same_file_system(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .take(FINGERPRINT_MAX_DIRS)
+    {
+        entry.path().hash(&mut hasher);
+        entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
 /// Cached result for a single category
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedCategoryResult {
@@ -44,6 +104,10 @@ pub struct CachedCategoryResult {
     pub tracked_paths: HashMap<PathBuf, PathMetadata>,
     #[serde(default)]
     pub ttl_seconds: Option<u64>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub extra_data: ExtraData,
 }
 
 impl CachedCategoryResult {
@@ -59,6 +123,8 @@ impl CachedCategoryResult {
             scan_timestamp: SystemTime::now(),
             tracked_paths,
             ttl_seconds: None,
+            status: result.status,
+            extra_data: result.extra_data,
         }
     }
 
@@ -87,14 +153,30 @@ impl CachedCategoryResult {
         true
     }
 
+    /// Whether an item path or tracked path contains, or is contained in, one of `paths`
+    fn overlaps_any(&self, paths: &[&Path]) -> bool {
+        let overlaps = |cached: &Path| {
+            paths
+                .iter()
+                .any(|p| cached.starts_with(p) || p.starts_with(cached))
+        };
+        self.items
+            .iter()
+            .filter_map(|i| i.path.as_deref())
+            .chain(self.tracked_paths.keys().map(PathBuf::as_path))
+            .any(overlaps)
+    }
+
     /// Convert back to CheckResult
     pub fn to_check_result(&self) -> CheckResult {
         CheckResult {
             name: self.name.clone(),
-            status: None,
+            status: self.status.clone(),
             items: self.items.clone(),
             total_size: self.total_size,
-            extra_data: Default::default(),
+            extra_data: self.extra_data.clone(),
+            // Failed results are never cached
+            failure: None,
         }
     }
 }
@@ -106,23 +188,12 @@ pub struct CacheConfig {
 }
 
 impl CacheConfig {
+    /// Recommended TTLs based on category characteristics, as declared by each checker
     pub fn default_config() -> Self {
-        let mut category_ttls = HashMap::new();
-
-        // Recommended TTLs based on category characteristics (in seconds)
-        category_ttls.insert("Trash".to_string(), 0); // Never cache
-        category_ttls.insert("General Caches".to_string(), 30); // 30 seconds
-        category_ttls.insert("Docker".to_string(), 300); // 5 minutes
-        category_ttls.insert("Homebrew".to_string(), 3600); // 1 hour
-        category_ttls.insert("Node.js/npm/yarn".to_string(), 600); // 10 minutes
-        category_ttls.insert("Python".to_string(), 600); // 10 minutes
-        category_ttls.insert("Rust/Cargo".to_string(), 300); // 5 minutes
-        category_ttls.insert("Xcode".to_string(), 300); // 5 minutes
-        category_ttls.insert("Java (Gradle/Maven)".to_string(), 600); // 10 minutes
-        category_ttls.insert("Go".to_string(), 600); // 10 minutes
-        category_ttls.insert("node_modules in Projects".to_string(), 300); // 5 minutes
-        category_ttls.insert("IDE Caches".to_string(), 600); // 10 minutes
-        category_ttls.insert("Shell Caches".to_string(), 300); // 5 minutes
+        let category_ttls = registry::all()
+            .iter()
+            .map(|checker| (checker.name().to_string(), checker.default_ttl()))
+            .collect();
 
         Self { category_ttls }
     }
@@ -140,25 +211,42 @@ impl CacheConfig {
     pub fn load() -> Self {
         let config_path = Self::config_file_path();
         if let Ok(data) = fs::read_to_string(&config_path) {
-            if let Ok(config) = serde_json::from_str(&data) {
-                return config;
+            if let Ok(config) = serde_json::from_str::<Self>(&data) {
+                return config.migrated();
             }
         }
         Self::default_config()
     }
 
-    pub fn save(&self) -> Result<(), String> {
+    /// Rename categories saved under legacy names and add defaults for new checkers
+    fn migrated(self) -> Self {
+        let mut category_ttls: HashMap<String, u64> = self
+            .category_ttls
+            .into_iter()
+            .map(|(name, ttl)| (registry::migrate_legacy_name(&name).to_string(), ttl))
+            .collect();
+
+        for checker in registry::all() {
+            category_ttls
+                .entry(checker.name().to_string())
+                .or_insert_with(|| checker.default_ttl());
+        }
+
+        Self { category_ttls }
+    }
+
+    pub fn save(&self) -> Result<(), DevSweepError> {
         let config_path = Self::config_file_path();
 
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
         }
 
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            DevSweepError::serialization(format!("Failed to serialize config: {}", e))
+        })?;
 
-        fs::write(&config_path, json).map_err(|e| format!("Failed to write config file: {}", e))?;
+        fs::write(&config_path, json).map_err(|e| DevSweepError::from_io(&e, &config_path))?;
 
         Ok(())
     }
@@ -171,9 +259,16 @@ impl CacheConfig {
     }
 }
 
+/// Version of the cache file layout; caches written by other versions are
+/// discarded. Bump it whenever `CleanupItem` changes incompatibly.
+const CACHE_VERSION: u32 = 2;
+
 /// Cache for scan results
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanCache {
+    /// Missing (0) in caches written before versioning
+    #[serde(default)]
+    pub version: u32,
     pub categories: HashMap<String, CachedCategoryResult>,
     pub last_full_scan: Option<SystemTime>,
     #[serde(skip)]
@@ -183,6 +278,7 @@ pub struct ScanCache {
 impl ScanCache {
     pub fn new() -> Self {
         Self {
+            version: CACHE_VERSION,
             categories: HashMap::new(),
             last_full_scan: None,
             config: CacheConfig::default_config(),
@@ -192,11 +288,13 @@ impl ScanCache {
     /// Load cache from disk
     pub fn load() -> Self {
         let cache_path = Self::cache_file_path();
-        let mut cache = if let Ok(data) = fs::read_to_string(&cache_path) {
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            Self::new()
-        };
+        let mut cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<Self>(&data).ok())
+            // Items from an older layout could lose their cleanup action and
+            // be mistaken for plain deletions
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default();
 
         // Load config separately
         cache.config = CacheConfig::load();
@@ -204,19 +302,19 @@ impl ScanCache {
     }
 
     /// Save cache to disk
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<(), DevSweepError> {
         let cache_path = Self::cache_file_path();
 
         // Ensure parent directory exists
         if let Some(parent) = cache_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
         }
 
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize cache: {}", e))?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            DevSweepError::serialization(format!("Failed to serialize cache: {}", e))
+        })?;
 
-        fs::write(&cache_path, json).map_err(|e| format!("Failed to write cache file: {}", e))?;
+        fs::write(&cache_path, json).map_err(|e| DevSweepError::from_io(&e, &cache_path))?;
 
         Ok(())
     }
@@ -235,6 +333,57 @@ impl ScanCache {
         self.last_full_scan = None;
     }
 
+    /// Drop the cached categories affected by cleaning `items`, so only those
+    /// are rescanned; returns the names of the dropped categories
+    ///
+    /// A category is affected if one of its items or tracked paths contains,
+    /// or is contained in, a cleaned path, or if it holds the same
+    /// command-based item (e.g. `docker image prune`).
+    pub fn invalidate_items(&mut self, items: &[CleanupItem]) -> Vec<String> {
+        let paths: Vec<&Path> = items.iter().filter_map(|i| i.path.as_deref()).collect();
+        let actions: Vec<(&str, &CleanupAction)> = items
+            .iter()
+            .filter_map(|i| Some((i.item_type.as_str(), i.cleanup_action.as_ref()?)))
+            .collect();
+
+        self.invalidate_where(|cached| {
+            cached.overlaps_any(&paths)
+                || cached.items.iter().any(|ci| {
+                    ci.cleanup_action
+                        .as_ref()
+                        .is_some_and(|action| actions.contains(&(ci.item_type.as_str(), action)))
+                })
+        })
+    }
+
+    /// Drop the cached categories with items or tracked paths overlapping
+    /// `paths` (e.g. files restored from quarantine); returns their names
+    pub fn invalidate_paths(&mut self, paths: &[PathBuf]) -> Vec<String> {
+        let paths: Vec<&Path> = paths
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect();
+        self.invalidate_where(|cached| cached.overlaps_any(&paths))
+    }
+
+    fn invalidate_where(
+        &mut self,
+        affected: impl Fn(&CachedCategoryResult) -> bool,
+    ) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .categories
+            .values()
+            .filter(|cached| affected(cached))
+            .map(|cached| cached.name.clone())
+            .collect();
+        names.sort();
+        for name in &names {
+            self.categories.remove(name);
+        }
+        names
+    }
+
     /// Update cache with new scan result
     pub fn update_category(
         &mut self,
@@ -263,6 +412,19 @@ impl ScanCache {
         None
     }
 
+    /// What `path` looked like when a cached category was scanned, if it
+    /// was tracked
+    pub fn tracked_metadata(&self, path: &Path) -> Option<&PathMetadata> {
+        self.categories
+            .values()
+            .find_map(|cached| cached.tracked_paths.get(path))
+    }
+
+    /// When the cached result for a category was produced
+    pub fn scanned_at(&self, name: &str) -> Option<SystemTime> {
+        self.categories.get(name).map(|c| c.scan_timestamp)
+    }
+
     /// Check if a category needs rescanning
     pub fn needs_rescan(&self, name: &str) -> bool {
         match self.categories.get(name) {
@@ -290,18 +452,22 @@ impl Default for ScanCache {
 }
 
 /// Helper to track paths during scanning
+#[derive(Debug)]
 pub struct PathTracker {
     paths: HashMap<PathBuf, PathMetadata>,
+    /// Directories listed to discover items, tracked shallowly
+    dirs: HashMap<PathBuf, PathMetadata>,
 }
 
 impl PathTracker {
     pub fn new() -> Self {
         Self {
             paths: HashMap::new(),
+            dirs: HashMap::new(),
         }
     }
 
-    /// Track a single path
+    /// Track a single path, including a fingerprint of its subdirectories
     pub fn track(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
         if let Some(metadata) = PathMetadata::from_path(path) {
@@ -309,9 +475,35 @@ impl PathTracker {
         }
     }
 
+    /// Track a directory that was listed to discover items, so a new entry
+    /// in it (e.g. a new `node_modules` or `target`) invalidates the cache
+    pub fn track_dir(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if self.dirs.contains_key(path) {
+            return;
+        }
+        if let Some(metadata) = PathMetadata::shallow(path) {
+            if metadata.is_dir {
+                self.dirs.insert(path.to_path_buf(), metadata);
+            }
+        }
+    }
+
     /// Get all tracked paths
+    ///
+    /// Discovery directories inside a tracked path are dropped; the tracked
+    /// path's own fingerprint already covers them.
     pub fn into_paths(self) -> HashMap<PathBuf, PathMetadata> {
-        self.paths
+        let mut paths = self.paths;
+        let dirs: Vec<(PathBuf, PathMetadata)> = self
+            .dirs
+            .into_iter()
+            .filter(|(dir, _)| !paths.keys().any(|p| dir.starts_with(p)))
+            .collect();
+        for (dir, metadata) in dirs {
+            paths.entry(dir).or_insert(metadata);
+        }
+        paths
     }
 }
 
@@ -355,6 +547,7 @@ mod tests {
             items: vec![],
             total_size: 1024,
             extra_data: Default::default(),
+            failure: None,
         };
 
         let mut tracker = PathTracker::new();
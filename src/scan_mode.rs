@@ -0,0 +1,214 @@
+//! Normal and low-impact (background) scanning
+//!
+//! A normal scan runs the checkers on rayon's global pool, one thread per
+//! core, and reads as many directories at once as `IoBudget::global` allows.
+//! That finishes fastest but competes with whatever else the machine is
+//! doing. A background scan runs them on a small dedicated pool whose threads
+//! have lowered CPU and I/O priority, and reads one directory at a time at a
+//! limited rate, so periodic scans don't disturb builds or calls.
+
+use crate::checkers::{ScanContext, DEFAULT_CHECKER_TIMEOUT};
+use crate::error::DevSweepError;
+use crate::utils::DEFAULT_COMMAND_TIMEOUT;
+use crate::walk::IoBudget;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Worker threads of a background scan
+pub const BACKGROUND_THREADS: usize = 2;
+
+/// Directories a background scan starts reading per second, at most
+pub const BACKGROUND_READS_PER_SECOND: u32 = 400;
+
+const SETTINGS_FILE: &str = "scan_settings.json";
+
+/// How hard a scan may work the machine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanMode {
+    /// Every core and as many concurrent reads as the disks allow
+    #[default]
+    Normal,
+    /// A few low-priority threads and rate-limited directory reads
+    Background,
+}
+
+impl ScanMode {
+    pub fn all() -> [Self; 2] {
+        [Self::Normal, Self::Background]
+    }
+
+    /// Name used in settings files and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Background => "background",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|mode| mode.name() == name)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Background => "Background (low impact)",
+        }
+    }
+
+    /// The budget directory reads in this mode are taken from
+    pub fn io_budget(&self) -> &'static IoBudget {
+        static BACKGROUND: OnceLock<IoBudget> = OnceLock::new();
+        match self {
+            Self::Normal => IoBudget::global(),
+            Self::Background => {
+                BACKGROUND.get_or_init(|| IoBudget::rate_limited(1, BACKGROUND_READS_PER_SECOND))
+            }
+        }
+    }
+
+    /// A new pool to run a scan in, or `None` for rayon's global pool.
+    /// Parallel iterators inside `ThreadPool::install` stay on that pool.
+    /// Built once per scan and shared by all its checkers.
+    pub fn thread_pool(&self) -> Option<rayon::ThreadPool> {
+        match self {
+            Self::Normal => None,
+            Self::Background => rayon::ThreadPoolBuilder::new()
+                .num_threads(BACKGROUND_THREADS)
+                .thread_name(|i| format!("devsweep-background-{}", i))
+                .start_handler(|_| lower_thread_priority())
+                .build()
+                .ok(),
+        }
+    }
+}
+
+/// `ioprio_set` arguments selecting the calling thread's idle I/O class,
+/// which only gets the disk when no one else is using it
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_IDLE: libc::c_int = 3 << 13;
+
+/// Lower the calling thread's CPU and I/O priority.
+/// Best effort: failures leave the thread as it was.
+fn lower_thread_priority() {
+    // Darwin's background band throttles both CPU and disk access
+    #[cfg(target_os = "macos")]
+    unsafe {
+        libc::setpriority(libc::PRIO_DARWIN_THREAD, 0, libc::PRIO_DARWIN_BG);
+    }
+    // On Linux, PRIO_PROCESS and IOPRIO_WHO_PROCESS with id 0 apply only to
+    // the calling thread
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_IDLE);
+    }
+}
+
+/// Scan preferences chosen in settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSettings {
+    #[serde(default)]
+    pub mode: ScanMode,
+    /// Seconds an external command (`docker info`, `brew leaves`, ...) may run
+    #[serde(default = "default_command_timeout_secs")]
+    pub command_timeout_secs: u64,
+    /// Seconds a checker may run before it is reported as timed out and the
+    /// scan moves on without it
+    #[serde(default = "default_checker_timeout_secs")]
+    pub checker_timeout_secs: u64,
+}
+
+fn default_command_timeout_secs() -> u64 {
+    DEFAULT_COMMAND_TIMEOUT.as_secs()
+}
+
+fn default_checker_timeout_secs() -> u64 {
+    DEFAULT_CHECKER_TIMEOUT.as_secs()
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            mode: ScanMode::default(),
+            command_timeout_secs: default_command_timeout_secs(),
+            checker_timeout_secs: default_checker_timeout_secs(),
+        }
+    }
+}
+
+impl ScanSettings {
+    /// `ctx` with these settings' mode and timeouts
+    pub fn apply(&self, ctx: ScanContext) -> ScanContext {
+        ctx.with_scan_mode(self.mode)
+            .with_command_timeout(Duration::from_secs(self.command_timeout_secs))
+            .with_checker_timeout(Duration::from_secs(self.checker_timeout_secs))
+    }
+
+    /// Load settings from disk, or the defaults
+    pub fn load() -> Self {
+        fs::read_to_string(Self::config_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), DevSweepError> {
+        let config_path = Self::config_path();
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| DevSweepError::serialization(format!("Failed to serialize: {}", e)))?;
+        fs::write(&config_path, content).map_err(|e| DevSweepError::from_io(&e, &config_path))
+    }
+
+    fn config_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("devsweep")
+            .join(SETTINGS_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_names_round_trip() {
+        for mode in ScanMode::all() {
+            assert_eq!(ScanMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(ScanMode::from_name("turbo"), None);
+    }
+
+    #[test]
+    fn test_settings_without_timeouts_use_defaults() {
+        let settings: ScanSettings = serde_json::from_str(r#"{"mode": "background"}"#).unwrap();
+        assert_eq!(settings.mode, ScanMode::Background);
+        assert_eq!(
+            settings.command_timeout_secs,
+            DEFAULT_COMMAND_TIMEOUT.as_secs()
+        );
+        assert_eq!(
+            settings.checker_timeout_secs,
+            DEFAULT_CHECKER_TIMEOUT.as_secs()
+        );
+    }
+
+    #[test]
+    fn test_background_pool_is_small() {
+        assert!(ScanMode::Normal.thread_pool().is_none());
+        let pool = ScanMode::Background.thread_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), BACKGROUND_THREADS);
+        assert_eq!(ScanMode::Background.io_budget().limit(), 1);
+    }
+}
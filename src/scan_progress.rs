@@ -0,0 +1,150 @@
+//! Live scan progress
+//!
+//! `StorageBackend::scan_with_events` sends `ScanEvent`s over a channel while
+//! it runs, so the UI can show which checkers are still working and render
+//! each category as soon as it is done instead of waiting for the slowest one.
+//! Checkers report progress through `ScanContext::dir_size` and
+//! `ScanContext::report_path`; they never see the channel itself.
+
+use crate::backend::CategoryData;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Minimum time between two `Progress` events from the same checker
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Something that happened during a scan
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    /// The scan began; `checkers` will run, the others are served from cache
+    Started {
+        checkers: Vec<String>,
+        cached: Vec<String>,
+    },
+    /// A category was served from the scan cache
+    CacheHit { category: CategoryData },
+    /// On a first scan, a checker's quick result with estimated sizes, sent
+    /// before it runs for real; its `CheckerFinished` replaces it
+    Estimated { category: CategoryData },
+    /// A checker started running
+    CheckerStarted { name: String },
+    /// A checker is walking `path` and has counted `bytes` so far
+    Progress {
+        name: String,
+        path: PathBuf,
+        bytes: u64,
+    },
+    /// A checker finished with its result
+    CheckerFinished {
+        category: CategoryData,
+        duration: Duration,
+    },
+    /// Every category is done, or the scan was cancelled and the remaining
+    /// checkers were skipped
+    Finished { total_size: u64, cancelled: bool },
+}
+
+/// Progress handle for one running checker
+///
+/// Cheap to clone; clones share the byte counter, so parallel walks inside
+/// one checker add up.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    checker: String,
+    sender: Sender<ScanEvent>,
+    bytes: Arc<AtomicU64>,
+    last_sent: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ProgressReporter {
+    pub fn new(checker: impl Into<String>, sender: Sender<ScanEvent>) -> Self {
+        Self {
+            checker: checker.into(),
+            sender,
+            bytes: Arc::new(AtomicU64::new(0)),
+            last_sent: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Bytes counted so far
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Add to the bytes counted so far
+    pub fn add_bytes(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Report the path currently being walked, at most every `PROGRESS_INTERVAL`
+    pub fn report_path(&self, path: &Path) {
+        let now = Instant::now();
+        {
+            let mut last_sent = match self.last_sent.lock() {
+                Ok(guard) => guard,
+                Err(_) => return,
+            };
+            if last_sent.is_some_and(|t| now.duration_since(t) < PROGRESS_INTERVAL) {
+                return;
+            }
+            *last_sent = Some(now);
+        }
+
+        // The receiver may have gone away (window closed); progress is best effort
+        let _ = self.sender.send(ScanEvent::Progress {
+            name: self.checker.clone(),
+            path: path.to_path_buf(),
+            bytes: self.bytes(),
+        });
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("checker", &self.checker)
+            .field("bytes", &self.bytes())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_progress_is_throttled() {
+        let (tx, rx) = mpsc::channel();
+        let reporter = ProgressReporter::new("Test", tx);
+
+        reporter.add_bytes(10);
+        reporter.report_path(Path::new("/a"));
+        reporter.report_path(Path::new("/b"));
+        drop(reporter);
+
+        let events: Vec<ScanEvent> = rx.iter().collect();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            ScanEvent::Progress { name, path, bytes } => {
+                assert_eq!(name, "Test");
+                assert_eq!(path, Path::new("/a"));
+                assert_eq!(*bytes, 10);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clones_share_byte_count() {
+        let (tx, _rx) = mpsc::channel();
+        let reporter = ProgressReporter::new("Test", tx);
+        reporter.clone().add_bytes(5);
+        reporter.add_bytes(7);
+        assert_eq!(reporter.bytes(), 12);
+    }
+}
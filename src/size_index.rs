@@ -0,0 +1,360 @@
+//! Persistent directory-size index
+//!
+//! Sizing `~/Library/Caches`, the Homebrew Cellar or a large `node_modules`
+//! means reading every directory below it. The index remembers, for each
+//! directory, its inode and modification time, the total size of the files
+//! directly inside it and the names of its subdirectories. A directory whose
+//! inode and mtime haven't moved is not read again: its file total is reused
+//! and only its subdirectories are checked, so a warm scan stats directories
+//! instead of listing them.
+//!
+//! Sizes are `DiskUsage`s: hardlinked files are remembered individually so
+//! that they can be counted once across the whole tree being sized. A
+//! directory holding more than `MAX_INDEXED_LINKS` of them (a pnpm or Nix
+//! style store) is not indexed and is read on every scan instead, which
+//! keeps the index small enough to load and save on each scan.
+//!
+//! A directory's mtime only changes when entries are added, removed or
+//! renamed, so a file growing in place is not noticed until its entry expires
+//! after `MAX_ENTRY_AGE`.
+
+use crate::cancel::CancellationToken;
+use crate::disk_usage::{DiskUsage, LinkedFile, UsageCounter};
+use crate::error::DevSweepError;
+use crate::walk::{self, IoBudget};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long an entry is trusted before the directory is read again
+pub const MAX_ENTRY_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Hardlinked files a directory may hold and still be indexed
+pub const MAX_INDEXED_LINKS: usize = 64;
+
+/// What the index knows about one directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedDir {
+    pub inode: u64,
+    pub mtime: i64,
+    pub mtime_nsec: i64,
+    /// Usage of the files with a single link directly in the directory
+    pub files: DiskUsage,
+    /// Files directly in the directory that have more than one link, at most
+    /// `MAX_INDEXED_LINKS`
+    #[serde(default)]
+    pub linked: Vec<LinkedFile>,
+    /// Names of the subdirectories (symlinks are not followed)
+    pub children: Vec<String>,
+    /// When the directory was read, in seconds since the Unix epoch
+    pub indexed_at: u64,
+}
+
+impl IndexedDir {
+    fn matches(&self, metadata: &fs::Metadata, now: u64) -> bool {
+        self.inode == metadata.ino()
+            && self.mtime == metadata.mtime()
+            && self.mtime_nsec == metadata.mtime_nsec()
+            && now.saturating_sub(self.indexed_at) < MAX_ENTRY_AGE.as_secs()
+    }
+}
+
+/// Directory sizes remembered between scans
+///
+/// Shared between checkers through `ScanContext`; lookups and updates are
+/// safe from parallel walks.
+#[derive(Debug, Default)]
+pub struct SizeIndex {
+    entries: RwLock<HashMap<String, IndexedDir>>,
+    dirs_read: AtomicUsize,
+    dirs_reused: AtomicUsize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    entries: HashMap<String, IndexedDir>,
+}
+
+impl SizeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the index from disk, or start empty
+    pub fn load() -> Self {
+        Self::load_from(&Self::index_file_path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<IndexFile>(&data).ok())
+            .map(|file| file.entries)
+            .unwrap_or_default();
+        Self {
+            entries: RwLock::new(entries),
+            ..Self::default()
+        }
+    }
+
+    /// Save the index to disk, dropping expired entries and ones with more
+    /// hardlinked files than are indexed now
+    pub fn save(&self) -> Result<(), DevSweepError> {
+        self.save_to(&Self::index_file_path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), DevSweepError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| DevSweepError::from_io(&e, parent))?;
+        }
+
+        let now = unix_now();
+        let entries: HashMap<String, IndexedDir> = match self.entries.read() {
+            Ok(entries) => entries
+                .iter()
+                .filter(|(_, e)| {
+                    now.saturating_sub(e.indexed_at) < MAX_ENTRY_AGE.as_secs()
+                        && e.linked.len() <= MAX_INDEXED_LINKS
+                })
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            Err(_) => HashMap::new(),
+        };
+
+        // Compact: the index can hold many thousands of directories
+        let json = serde_json::to_string(&IndexFile { entries }).map_err(|e| {
+            DevSweepError::serialization(format!("Failed to serialize size index: {}", e))
+        })?;
+        fs::write(path, json).map_err(|e| DevSweepError::from_io(&e, path))
+    }
+
+    fn index_file_path() -> PathBuf {
+        let cache_dir = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
+        cache_dir
+            .join("development-cleaner")
+            .join("size_index.json")
+    }
+
+    /// Number of directories indexed
+    pub fn len(&self) -> usize {
+        self.entries.read().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many directories were listed, and how many were reused from the
+    /// index, since this index was loaded
+    pub fn stats(&self) -> (usize, usize) {
+        (
+            self.dirs_read.load(Ordering::Relaxed),
+            self.dirs_reused.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Apparent size of the files under `path`; see `disk_usage`
+    pub fn dir_size(&self, path: &Path, cancel: &CancellationToken) -> u64 {
+        self.disk_usage(path, cancel).apparent
+    }
+
+    /// Disk usage of the files under `path`, reading only the directories
+    /// that changed since they were indexed. Stops early once `cancel` is
+    /// triggered and returns the usage counted so far.
+    pub fn disk_usage(&self, path: &Path, cancel: &CancellationToken) -> DiskUsage {
+        self.disk_usage_within(path, IoBudget::global(), cancel)
+    }
+
+    /// Like `disk_usage`, reading directories under a permit from `budget`
+    pub fn disk_usage_within(
+        &self,
+        path: &Path,
+        budget: &IoBudget,
+        cancel: &CancellationToken,
+    ) -> DiskUsage {
+        let mut counter = UsageCounter::new();
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => {
+                counter = self.count_dir(path, &metadata, metadata.dev(), budget, cancel);
+            }
+            Ok(metadata) if metadata.is_file() => counter.add_file(&metadata),
+            _ => {}
+        }
+        counter.finish()
+    }
+
+    /// Usage of `dir` and the directories below it that are on `device`
+    fn count_dir(
+        &self,
+        dir: &Path,
+        metadata: &fs::Metadata,
+        device: u64,
+        budget: &IoBudget,
+        cancel: &CancellationToken,
+    ) -> UsageCounter {
+        if cancel.is_cancelled() {
+            return UsageCounter::new();
+        }
+
+        let key = dir.to_str();
+        let now = unix_now();
+        let cached = key.and_then(|key| {
+            let entries = self.entries.read().ok()?;
+            entries
+                .get(key)
+                .filter(|e| e.matches(metadata, now))
+                .map(|e| {
+                    let mut files = UsageCounter::new();
+                    files.add_counted(e.files, &e.linked);
+                    let children = e.children.iter().map(|c| (OsString::from(c), None));
+                    (files, children.collect())
+                })
+        });
+
+        // Subdirectories just listed come with their metadata and are on
+        // `device`; remembered ones are statted below, and skipped if a
+        // filesystem has been mounted there since
+        let (files, children): (UsageCounter, Vec<(OsString, Option<fs::Metadata>)>) = match cached
+        {
+            Some(cached) => {
+                self.dirs_reused.fetch_add(1, Ordering::Relaxed);
+                cached
+            }
+            None => {
+                self.dirs_read.fetch_add(1, Ordering::Relaxed);
+                let Some((files, children)) = read_dir_entries(dir, device, budget) else {
+                    return UsageCounter::new();
+                };
+                // Paths and names that aren't UTF-8 can't be stored, and
+                // directories full of hardlinks would bloat the index; just
+                // don't index them
+                let names: Option<Vec<String>> = children
+                    .iter()
+                    .map(|(c, _)| c.to_str().map(String::from))
+                    .collect();
+                let (single, linked) = files.parts();
+                if let (Some(key), Some(names), true) =
+                    (key, names, linked.len() <= MAX_INDEXED_LINKS)
+                {
+                    let entry = IndexedDir {
+                        inode: metadata.ino(),
+                        mtime: metadata.mtime(),
+                        mtime_nsec: metadata.mtime_nsec(),
+                        files: single,
+                        linked: linked.to_vec(),
+                        children: names,
+                        indexed_at: now,
+                    };
+                    if let Ok(mut entries) = self.entries.write() {
+                        entries.insert(key.to_string(), entry);
+                    }
+                }
+                let children = children.into_iter().map(|(c, m)| (c, Some(m)));
+                (files, children.collect())
+            }
+        };
+
+        children
+            .par_iter()
+            .map(|(name, listed)| {
+                let child = dir.join(name);
+                let metadata = match listed {
+                    Some(metadata) => Ok(metadata.clone()),
+                    None => fs::symlink_metadata(&child),
+                };
+                match metadata {
+                    Ok(metadata) if metadata.is_dir() && metadata.dev() == device => {
+                        self.count_dir(&child, &metadata, device, budget, cancel)
+                    }
+                    _ => UsageCounter::new(),
+                }
+            })
+            .reduce(UsageCounter::new, UsageCounter::merge)
+            .merge(files)
+    }
+}
+
+/// The files directly in `dir`, which is on `device`, and the names and
+/// metadata of its subdirectories
+fn read_dir_entries(
+    dir: &Path,
+    device: u64,
+    budget: &IoBudget,
+) -> Option<(UsageCounter, Vec<(OsString, fs::Metadata)>)> {
+    let entries = walk::read_dir_entries(dir, device, budget)?;
+    let mut files = UsageCounter::new();
+    for metadata in &entries.files {
+        files.add_file(metadata);
+    }
+    let children = entries
+        .subdirs
+        .into_iter()
+        .filter_map(|(subdir, metadata)| Some((subdir.file_name()?.to_os_string(), metadata)))
+        .collect();
+    Some((files, children))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_entry_does_not_match() {
+        let dir = std::env::temp_dir();
+        let metadata = fs::metadata(&dir).unwrap();
+        let entry = IndexedDir {
+            inode: metadata.ino(),
+            mtime: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+            files: DiskUsage::default(),
+            linked: Vec::new(),
+            children: Vec::new(),
+            indexed_at: 0,
+        };
+        assert!(!entry.matches(&metadata, unix_now()));
+        assert!(entry.matches(&metadata, 1));
+    }
+
+    #[test]
+    fn test_remembered_child_on_another_device_is_not_walked() {
+        let root = tempfile::TempDir::new().unwrap();
+        // A remembered child name that now leads to another filesystem, as
+        // if one had been mounted there after the directory was indexed
+        let other = Path::new("/dev");
+        let metadata = fs::metadata(root.path()).unwrap();
+        if fs::metadata(other).map_or(true, |m| m.dev() == metadata.dev()) {
+            return;
+        }
+        let up = "../".repeat(root.path().components().count() - 1);
+        let index = SizeIndex::new();
+        index.entries.write().unwrap().insert(
+            root.path().to_str().unwrap().to_string(),
+            IndexedDir {
+                inode: metadata.ino(),
+                mtime: metadata.mtime(),
+                mtime_nsec: metadata.mtime_nsec(),
+                files: DiskUsage::default(),
+                linked: Vec::new(),
+                children: vec![format!("{}dev", up)],
+                indexed_at: unix_now(),
+            },
+        );
+
+        index.disk_usage(root.path(), &CancellationToken::new());
+        assert_eq!(index.stats(), (0, 1));
+    }
+}
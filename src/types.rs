@@ -1,4 +1,9 @@
+use crate::command_runner::Invocation;
+use crate::disk_usage::DiskUsage;
+use crate::utils::format_size;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::PathBuf;
 
 /// Represents a single item that can be cleaned
@@ -10,8 +15,31 @@ pub struct CleanupItem {
     pub size_str: String,
     pub safe_to_delete: bool,
     pub warning: Option<String>,
-    pub cleanup_command: Option<String>,
+    /// Run instead of deleting `path` when set
+    pub cleanup_action: Option<CleanupAction>,
     pub details: Option<Vec<ItemDetail>>,
+    /// Bytes on disk that deleting the item would free, when known. `size`
+    /// is the apparent size, which overstates this for hardlinked and sparse
+    /// files.
+    #[serde(default)]
+    pub reclaimable: Option<u64>,
+    /// Path of an item in this or another category that contains this one;
+    /// cleaning that item frees this one's space too. Set by `overlap`.
+    #[serde(default)]
+    pub covered_by: Option<PathBuf>,
+    /// Sizes come from the quick first pass of a scan and are approximate;
+    /// the item cannot be cleaned until the exact pass replaces it
+    #[serde(default)]
+    pub is_estimate: bool,
+    /// A running process or a held lock file is using the item; found after
+    /// each scan and checked again right before cleanup
+    #[serde(default)]
+    pub in_use: Option<InUse>,
+    /// The item is a group of paths reported as one (a workspace's
+    /// node_modules): cleaning it removes each detail's path. Details of other
+    /// items only describe what the item contains.
+    #[serde(default)]
+    pub is_group: bool,
 }
 
 impl CleanupItem {
@@ -23,11 +51,22 @@ impl CleanupItem {
             size_str: size_str.to_string(),
             safe_to_delete: false,
             warning: None,
-            cleanup_command: None,
+            cleanup_action: None,
             details: None,
+            reclaimable: None,
+            covered_by: None,
+            is_estimate: false,
+            in_use: None,
+            is_group: false,
         }
     }
 
+    /// Create an item sized from a `DiskUsage`
+    pub fn from_usage(item_type: &str, usage: DiskUsage) -> Self {
+        Self::new(item_type, usage.apparent, &format_size(usage.apparent))
+            .with_reclaimable(usage.reclaimable)
+    }
+
     pub fn with_path(mut self, path: PathBuf) -> Self {
         self.path = Some(path);
         self
@@ -43,8 +82,8 @@ impl CleanupItem {
         self
     }
 
-    pub fn with_cleanup_command(mut self, cmd: &str) -> Self {
-        self.cleanup_command = Some(cmd.to_string());
+    pub fn with_cleanup_action(mut self, action: CleanupAction) -> Self {
+        self.cleanup_action = Some(action);
         self
     }
 
@@ -52,6 +91,41 @@ impl CleanupItem {
         self.details = Some(details);
         self
     }
+
+    /// Report `details` as the paths the item consists of; cleaning the item
+    /// removes each of them
+    pub fn with_group(mut self, details: Vec<ItemDetail>) -> Self {
+        self.details = Some(details);
+        self.is_group = true;
+        self
+    }
+
+    pub fn with_reclaimable(mut self, bytes: u64) -> Self {
+        self.reclaimable = Some(bytes);
+        self
+    }
+
+    /// Mark the item's sizes as estimates, shown with a leading `~`
+    pub fn into_estimate(mut self) -> Self {
+        if !self.is_estimate {
+            self.is_estimate = true;
+            self.size_str = format!("~{}", self.size_str);
+            for detail in self.details.iter_mut().flatten() {
+                detail.size_str = format!("~{}", detail.size_str);
+            }
+        }
+        self
+    }
+
+    /// Whether another item already includes this one's space
+    pub fn is_covered(&self) -> bool {
+        self.covered_by.is_some()
+    }
+
+    /// Bytes deleting the item would free, falling back to its size
+    pub fn reclaimable_size(&self) -> u64 {
+        self.reclaimable.unwrap_or(self.size)
+    }
 }
 
 /// Detail for items with multiple sub-components
@@ -87,7 +161,7 @@ impl ItemDetail {
 }
 
 /// Result of checking a storage category
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckResult {
     #[allow(dead_code)]
     pub name: String,
@@ -95,6 +169,9 @@ pub struct CheckResult {
     pub items: Vec<CleanupItem>,
     pub total_size: u64,
     pub extra_data: ExtraData,
+    /// Set when the checker didn't finish; `status` then describes why
+    #[serde(default)]
+    pub failure: Option<CheckFailure>,
 }
 
 impl CheckResult {
@@ -105,17 +182,75 @@ impl CheckResult {
             items: Vec::new(),
             total_size: 0,
             extra_data: ExtraData::default(),
+            failure: None,
         }
     }
 
+    /// A result with no items for a checker that didn't finish
+    pub fn failed(name: &str, failure: CheckFailure) -> Self {
+        let mut result = Self::new(name);
+        result.status = Some(failure.to_string());
+        result.failure = Some(failure);
+        result
+    }
+
     pub fn add_item(&mut self, item: CleanupItem) {
         self.total_size += item.size;
         self.items.push(item);
     }
 }
 
+/// Why a checker produced no result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CheckFailure {
+    /// The checker ran longer than the scan's checker timeout
+    TimedOut { after_secs: u64 },
+    /// The checker panicked
+    Crashed { message: String },
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut { after_secs } => write!(f, "Timed out after {}s", after_secs),
+            Self::Crashed { message } => write!(f, "Checker crashed: {}", message),
+        }
+    }
+}
+
+/// What is using an item that is about to be cleaned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InUse {
+    /// A process has a file open, mapped or as its working directory inside the item
+    Process { pid: u32, name: String },
+    /// A lock file in or next to the item belongs to a running process
+    /// (`postmaster.pid`, a browser's `SingletonLock`, a locked Gradle `.lock`)
+    LockFile {
+        path: PathBuf,
+        pid: u32,
+        name: Option<String>,
+    },
+}
+
+impl fmt::Display for InUse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Process { pid, name } => write!(f, "In use by {} (pid {})", name, pid),
+            Self::LockFile { path, pid, name } => write!(
+                f,
+                "Locked by {} (pid {}) through {}",
+                name.as_deref().unwrap_or("a running process"),
+                pid,
+                path.display()
+            ),
+        }
+    }
+}
+
 /// Extra data specific to certain check types
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExtraData {
     // Docker
     pub dangling_images: Option<usize>,
@@ -131,7 +266,7 @@ pub struct ExtraData {
     pub leaf_packages: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OldVersionInfo {
     pub package: String,
     pub old_count: usize,
@@ -139,7 +274,7 @@ pub struct OldVersionInfo {
     pub size_str: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageInfo {
     pub package: String,
     pub path: PathBuf,
@@ -147,7 +282,7 @@ pub struct PackageInfo {
     pub size_str: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnneededPackage {
     pub package: String,
     pub reason: String,
@@ -155,73 +290,190 @@ pub struct UnneededPackage {
     pub size_str: String,
 }
 
-/// Cleanup action that can be executed
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
+/// Top-level grouping of categories in the UI
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SuperCategoryType {
+    DevelopmentTools,
+    PackageManagers,
+    ProjectFiles,
+    SystemAndBrowsers,
+    Trash,
+}
+
+impl SuperCategoryType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DevelopmentTools => "Development Tools",
+            Self::PackageManagers => "Package Managers",
+            Self::ProjectFiles => "Project Files",
+            Self::SystemAndBrowsers => "System & Browsers",
+            Self::Trash => "Trash",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::DevelopmentTools => "🛠",
+            Self::PackageManagers => "📦",
+            Self::ProjectFiles => "📁",
+            Self::SystemAndBrowsers => "🌐",
+            Self::Trash => "🗑",
+        }
+    }
+
+    /// Map a category name to its super category using the checker registry
+    pub fn from_category_name(name: &str) -> Self {
+        crate::checkers::registry::find_by_name(name)
+            .map(|c| c.super_category())
+            // Default to System & Browsers for unknown categories
+            .unwrap_or(Self::SystemAndBrowsers)
+    }
+
+    /// Get all super category types in display order
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::DevelopmentTools,
+            Self::PackageManagers,
+            Self::ProjectFiles,
+            Self::SystemAndBrowsers,
+            Self::Trash,
+        ]
+    }
+}
+
+/// How an item is cleaned when that isn't simply deleting its path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CleanupAction {
-    pub name: String,
-    pub category: String,
     pub action_type: ActionType,
-    pub warning: Option<String>,
-    pub info: Option<String>,
+    /// Working directory; DevSweep's own when unset
+    #[serde(default)]
+    pub current_dir: Option<PathBuf>,
+    /// Variables set on top of DevSweep's environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Exit codes that count as success
+    #[serde(default = "default_success_codes")]
+    pub success_codes: Vec<i32>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
+fn default_success_codes() -> Vec<i32> {
+    vec![0]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ActionType {
-    Command { cmd: String, args: Vec<String> },
-    DeleteDirectory { path: PathBuf },
+    /// Run `program` with `args` directly; no shell sees the arguments, so
+    /// paths and branch names need no quoting
+    Command { program: String, args: Vec<String> },
+    /// Run a command line through `sh -c`. Only for explicit user-defined
+    /// rules; checkers always use `Command`.
     ShellCommand { command: String },
 }
 
 impl CleanupAction {
-    #[allow(dead_code)]
-    pub fn command(name: &str, category: &str, cmd: &str, args: Vec<&str>) -> Self {
-        Self {
-            name: name.to_string(),
-            category: category.to_string(),
-            action_type: ActionType::Command {
-                cmd: cmd.to_string(),
-                args: args.into_iter().map(|s| s.to_string()).collect(),
-            },
-            warning: None,
-            info: None,
-        }
+    pub fn command<I, S>(program: &str, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::new(ActionType::Command {
+            program: program.to_string(),
+            args: args
+                .into_iter()
+                .map(|arg| arg.as_ref().to_string())
+                .collect(),
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn delete_dir(name: &str, category: &str, path: PathBuf) -> Self {
-        Self {
-            name: name.to_string(),
-            category: category.to_string(),
-            action_type: ActionType::DeleteDirectory { path },
-            warning: None,
-            info: None,
-        }
+    pub fn shell(command: &str) -> Self {
+        Self::new(ActionType::ShellCommand {
+            command: command.to_string(),
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn shell(name: &str, category: &str, command: &str) -> Self {
+    fn new(action_type: ActionType) -> Self {
         Self {
-            name: name.to_string(),
-            category: category.to_string(),
-            action_type: ActionType::ShellCommand {
-                command: command.to_string(),
-            },
-            warning: None,
-            info: None,
+            action_type,
+            current_dir: None,
+            env: BTreeMap::new(),
+            success_codes: default_success_codes(),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn with_warning(mut self, warning: &str) -> Self {
-        self.warning = Some(warning.to_string());
+    pub fn in_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
         self
     }
 
-    #[allow(dead_code)]
-    pub fn with_info(mut self, info: &str) -> Self {
-        self.info = Some(info.to_string());
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_success_codes(mut self, codes: &[i32]) -> Self {
+        self.success_codes = codes.to_vec();
         self
     }
+
+    /// The process to start for this action
+    pub fn invocation(&self) -> Invocation {
+        let mut invocation = match &self.action_type {
+            ActionType::Command { program, args } => {
+                let mut invocation = Invocation::new(program);
+                invocation.args(args);
+                invocation
+            }
+            ActionType::ShellCommand { command } => {
+                let mut invocation = Invocation::new("sh");
+                invocation.args(["-c", command]);
+                invocation
+            }
+        };
+        if let Some(dir) = &self.current_dir {
+            invocation.current_dir(dir);
+        }
+        if !self.env.is_empty() {
+            let mut env: HashMap<String, String> = std::env::vars().collect();
+            env.extend(self.env.clone());
+            invocation.env(env);
+        }
+        invocation
+    }
+
+    pub fn is_success(&self, exit_code: Option<i32>) -> bool {
+        exit_code.is_some_and(|code| self.success_codes.contains(&code))
+    }
+}
+
+/// The action as a command line that could be pasted into a shell
+impl fmt::Display for CleanupAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(dir) = &self.current_dir {
+            write!(f, "cd {} && ", shell_quote(&dir.to_string_lossy()))?;
+        }
+        for (key, value) in &self.env {
+            write!(f, "{}={} ", key, shell_quote(value))?;
+        }
+        match &self.action_type {
+            ActionType::Command { program, args } => {
+                let words: Vec<String> = std::iter::once(program)
+                    .chain(args)
+                    .map(|word| shell_quote(word))
+                    .collect();
+                write!(f, "{}", words.join(" "))
+            }
+            ActionType::ShellCommand { command } => write!(f, "{}", command),
+        }
+    }
+}
+
+/// `word` as a single shell word, single-quoted unless it only has safe characters
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
 }
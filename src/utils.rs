@@ -1,39 +1,111 @@
+use crate::cancel::CancellationToken;
+use crate::command_runner::{CommandRunner, Invocation, ProcessRunner};
+use crate::disk_usage::disk_usage_until;
 use bytesize::ByteSize;
-use rayon::prelude::*;
+use std::io::{self, Read};
 use std::path::Path;
-use std::process::Command;
-use walkdir::WalkDir;
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long an external command may run before it is killed
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest pause between checks on a running command
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Convert bytes to human-readable string
 pub fn format_size(bytes: u64) -> String {
     ByteSize(bytes).to_string_as(true)
 }
 
-/// Get total size of a directory using parallel iteration for better performance
+/// Get total size of a directory, counting each hardlinked file once
+///
+/// This is the apparent size; see `disk_usage` for the space allocated on disk
+/// and how much of it deleting the directory would free.
 pub fn get_dir_size<P: AsRef<Path>>(path: P) -> u64 {
-    let path = path.as_ref();
-    if !path.exists() {
-        return 0;
-    }
+    get_dir_size_until(path, &CancellationToken::new())
+}
 
-    WalkDir::new(path)
-        .into_iter()
-        .par_bridge()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter_map(|e| e.metadata().ok())
-        .map(|m| m.len())
-        .sum()
+/// Like `get_dir_size`, but stops walking once `cancel` is triggered and
+/// returns the size counted so far
+pub fn get_dir_size_until<P: AsRef<Path>>(path: P, cancel: &CancellationToken) -> u64 {
+    disk_usage_until(path.as_ref(), cancel).apparent
 }
 
 /// Run a command and return stdout as String
 pub fn run_command(cmd: &str, args: &[&str]) -> Option<String> {
-    Command::new(cmd)
-        .args(args)
-        .output()
+    let mut invocation = Invocation::new(cmd);
+    invocation.args(args).timeout(DEFAULT_COMMAND_TIMEOUT);
+    ProcessRunner
+        .run(&invocation, &CancellationToken::new())
         .ok()
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|o| o.success())
+        .map(|o| o.stdout.trim().to_string())
+}
+
+/// Like `Command::output`, but kills the command once it has run for
+/// `timeout` (`ErrorKind::TimedOut`) or `cancel` is triggered
+/// (`ErrorKind::Interrupted`). A hung daemon or a broken tool install can't
+/// stall the caller for longer than that.
+pub fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drain both pipes while waiting so a chatty command can't fill one and block
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    // Durations too long to add to now (`Duration::MAX`) mean no timeout
+    let deadline = Instant::now().checked_add(timeout);
+    let mut interval = Duration::from_millis(1);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let stopped = if cancel.is_cancelled() {
+            Some(io::Error::new(io::ErrorKind::Interrupted, "cancelled"))
+        } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {}s", timeout.as_secs()),
+            ))
+        } else {
+            None
+        };
+        if let Some(error) = stopped {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(error);
+        }
+        thread::sleep(interval);
+        interval = (interval * 2).min(MAX_POLL_INTERVAL);
+    };
+
+    let collect = |reader: Option<JoinHandle<Vec<u8>>>| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
 }
 
 /// Get home directory path
@@ -71,6 +143,28 @@ mod tests {
         assert!(!format_size(1024 * 1024).is_empty()); // 1 MiB
     }
 
+    #[test]
+    fn test_output_with_timeout_kills_slow_commands() {
+        let cancel = CancellationToken::new();
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "echo done"]),
+            Duration::from_secs(10),
+            &cancel,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "done");
+
+        let started = Instant::now();
+        let error = output_with_timeout(
+            Command::new("sleep").arg("10"),
+            Duration::from_millis(100),
+            &cancel,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn test_sort_versions() {
         let mut versions = vec![
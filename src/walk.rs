@@ -0,0 +1,381 @@
+//! Parallel directory traversal
+//!
+//! `WalkDir` reads directories one at a time, so feeding it into rayon with
+//! `par_bridge` only parallelises the `metadata()` calls. Here every directory
+//! is a rayon task: its subdirectories are walked as further tasks that idle
+//! threads steal, so directories are read concurrently, and a walk nested in
+//! another parallel iterator shares the pool instead of fighting it.
+//!
+//! Entry types come from the directory listing itself. Regular files and
+//! subdirectories are `lstat`ed once each, a subdirectory to tell it from a
+//! mount point, and a directory is not statted again when it is read.
+//! Listing a directory and statting its entries holds a permit from an
+//! `IoBudget`, which caps how many directories are being read at the same
+//! time across every walk in the process, and can also cap how many are read
+//! per second.
+//!
+//! Walks never leave the filesystem they start on: a subdirectory on another
+//! device (an external drive, a network share or FUSE mount inside the tree)
+//! is listed as a mount point and not descended into.
+
+use crate::cancel::CancellationToken;
+use rayon::prelude::*;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, FileType, Metadata};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Shared limit on concurrent directory reads
+#[derive(Debug)]
+pub struct IoBudget {
+    in_use: Mutex<usize>,
+    released: Condvar,
+    limit: usize,
+    /// Minimum time between the starts of two reads, when rate-limited
+    interval: Option<Duration>,
+    next_read: Mutex<Option<Instant>>,
+}
+
+/// Held while reading a directory; returns its slot to the budget on drop
+pub struct IoPermit<'a> {
+    budget: &'a IoBudget,
+}
+
+impl IoBudget {
+    /// A budget allowing `limit` concurrent reads (at least one)
+    pub fn new(limit: usize) -> Self {
+        Self {
+            in_use: Mutex::new(0),
+            released: Condvar::new(),
+            limit: limit.max(1),
+            interval: None,
+            next_read: Mutex::new(None),
+        }
+    }
+
+    /// A budget allowing `limit` concurrent reads and at most
+    /// `reads_per_second` reads started per second
+    pub fn rate_limited(limit: usize, reads_per_second: u32) -> Self {
+        Self {
+            interval: Some(Duration::from_secs(1) / reads_per_second.max(1)),
+            ..Self::new(limit)
+        }
+    }
+
+    /// The budget shared by every walk that isn't given its own: twice the
+    /// available parallelism, so threads blocked on slow disks don't idle the CPU
+    pub fn global() -> &'static IoBudget {
+        static GLOBAL: OnceLock<IoBudget> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+            IoBudget::new(threads * 2)
+        })
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Wait for a free slot, and for the rate limit if there is one
+    pub fn acquire(&self) -> IoPermit<'_> {
+        let mut in_use = self.in_use.lock().unwrap_or_else(|e| e.into_inner());
+        while *in_use >= self.limit {
+            in_use = self
+                .released
+                .wait(in_use)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        *in_use += 1;
+        drop(in_use);
+
+        let permit = IoPermit { budget: self };
+        if let Some(interval) = self.interval {
+            let now = Instant::now();
+            let start = {
+                let mut next_read = self.next_read.lock().unwrap_or_else(|e| e.into_inner());
+                let start = next_read.map_or(now, |next| next.max(now));
+                *next_read = Some(start + interval);
+                start
+            };
+            std::thread::sleep(start - now);
+        }
+        permit
+    }
+}
+
+impl Drop for IoPermit<'_> {
+    fn drop(&mut self) {
+        let mut in_use = self.budget.in_use.lock().unwrap_or_else(|e| e.into_inner());
+        *in_use -= 1;
+        self.budget.released.notify_one();
+    }
+}
+
+/// The regular files directly in a directory and its subdirectories
+#[derive(Debug, Default)]
+pub struct DirEntries {
+    pub files: Vec<Metadata>,
+    /// Subdirectories on the directory's filesystem, with the metadata read
+    /// to tell them from mount points; symlinks to directories are not
+    /// included
+    pub subdirs: Vec<(PathBuf, Metadata)>,
+    /// Subdirectories on another filesystem, which walks don't descend into
+    pub mount_points: Vec<PathBuf>,
+}
+
+/// List `dir`, which is on `device`, under a permit from `budget`; `None` if
+/// it can't be read. Every entry is statted at most once, and `dir` itself
+/// not at all: its subdirectories are on `device` too.
+pub fn read_dir_entries(dir: &Path, device: u64, budget: &IoBudget) -> Option<DirEntries> {
+    let _permit = budget.acquire();
+    let mut entries = DirEntries::default();
+    for entry in fs::read_dir(dir).ok()?.filter_map(|e| e.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            match entry.metadata() {
+                Ok(metadata) if metadata.dev() != device => entries.mount_points.push(entry.path()),
+                Ok(metadata) => entries.subdirs.push((entry.path(), metadata)),
+                Err(_) => {}
+            }
+        } else if file_type.is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                entries.files.push(metadata);
+            }
+        }
+    }
+    Some(entries)
+}
+
+/// The names and types of the entries directly in one directory, as the
+/// listing reports them (symlinks are not followed)
+#[derive(Debug)]
+pub struct DirListing {
+    pub dir: PathBuf,
+    /// Levels below the root of the walk, which is 0
+    pub depth: usize,
+    pub entries: Vec<(OsString, FileType)>,
+}
+
+impl DirListing {
+    /// Whether the directory has a regular file (or a symlink) called `name`
+    pub fn has_file(&self, name: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|(n, t)| n == name && (t.is_file() || t.is_symlink()))
+    }
+
+    /// Whether the directory has a subdirectory called `name`
+    pub fn has_dir(&self, name: &str) -> bool {
+        self.entries.iter().any(|(n, t)| n == name && t.is_dir())
+    }
+}
+
+/// A parallel walk over the regular files of a tree, symlinks and mount
+/// points not followed
+pub struct ParallelWalk<'a> {
+    cancel: &'a CancellationToken,
+    budget: &'a IoBudget,
+}
+
+impl<'a> ParallelWalk<'a> {
+    /// A walk using the global I/O budget
+    pub fn new(cancel: &'a CancellationToken) -> Self {
+        Self {
+            cancel,
+            budget: IoBudget::global(),
+        }
+    }
+
+    pub fn with_budget(mut self, budget: &'a IoBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Fold every regular file under `root` (or `root` itself when it is a
+    /// file) into per-task accumulators, then combine them with `reduce`.
+    /// A symlinked `root` is followed; links below it are not. Stops reading
+    /// once the walk is cancelled and returns what was counted.
+    pub fn fold_files<T, I, F, R>(&self, root: &Path, identity: I, fold: F, reduce: R) -> T
+    where
+        T: Send,
+        I: Fn() -> T + Sync,
+        F: Fn(T, &Metadata) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+    {
+        match fs::metadata(root) {
+            Ok(metadata) if metadata.is_dir() => {
+                self.fold_dir(root, metadata.dev(), &identity, &fold, &reduce)
+            }
+            Ok(metadata) if metadata.is_file() => fold(identity(), &metadata),
+            _ => identity(),
+        }
+    }
+
+    /// List every directory under `root` down to `max_depth` and map each
+    /// listing with `map`. Results are in walk order: a directory before the
+    /// ones below it, siblings by name. Subdirectories whose name `descend`
+    /// rejects are listed by their parent but not entered; nothing is read
+    /// twice and the only stats are of the subdirectories entered, to stay on
+    /// `root`'s filesystem.
+    pub fn map_dirs<T, D, M>(&self, root: &Path, max_depth: usize, descend: D, map: M) -> Vec<T>
+    where
+        T: Send,
+        D: Fn(&OsStr) -> bool + Sync,
+        M: Fn(&DirListing) -> T + Sync,
+    {
+        match fs::metadata(root) {
+            Ok(metadata) if metadata.is_dir() => {
+                self.map_dir(root, metadata.dev(), 0, max_depth, &descend, &map)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn map_dir<T, D, M>(
+        &self,
+        dir: &Path,
+        device: u64,
+        depth: usize,
+        max_depth: usize,
+        descend: &D,
+        map: &M,
+    ) -> Vec<T>
+    where
+        T: Send,
+        D: Fn(&OsStr) -> bool + Sync,
+        M: Fn(&DirListing) -> T + Sync,
+    {
+        if self.cancel.is_cancelled() {
+            return Vec::new();
+        }
+        let mut listing = DirListing {
+            dir: dir.to_path_buf(),
+            depth,
+            entries: Vec::new(),
+        };
+        let mut subdirs = Vec::new();
+        {
+            let _permit = self.budget.acquire();
+            let Ok(read) = fs::read_dir(dir) else {
+                return Vec::new();
+            };
+            for entry in read.filter_map(|e| e.ok()) {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let name = entry.file_name();
+                let enter = file_type.is_dir()
+                    && depth < max_depth
+                    && descend(&name)
+                    && entry.metadata().is_ok_and(|m| m.dev() == device);
+                if enter {
+                    subdirs.push(entry.path());
+                }
+                listing.entries.push((name, file_type));
+            }
+        }
+        subdirs.sort();
+
+        let mut results = vec![map(&listing)];
+        let below: Vec<Vec<T>> = subdirs
+            .par_iter()
+            .map(|subdir| self.map_dir(subdir, device, depth + 1, max_depth, descend, map))
+            .collect();
+        results.extend(below.into_iter().flatten());
+        results
+    }
+
+    fn fold_dir<T, I, F, R>(&self, dir: &Path, device: u64, identity: &I, fold: &F, reduce: &R) -> T
+    where
+        T: Send,
+        I: Fn() -> T + Sync,
+        F: Fn(T, &Metadata) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+    {
+        if self.cancel.is_cancelled() {
+            return identity();
+        }
+        let Some(entries) = read_dir_entries(dir, device, self.budget) else {
+            return identity();
+        };
+
+        let files = entries.files.iter().fold(identity(), fold);
+        let subdirs = entries
+            .subdirs
+            .par_iter()
+            .map(|(subdir, _)| self.fold_dir(subdir, device, identity, fold, reduce))
+            .reduce(identity, reduce);
+        reduce(files, subdirs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_never_exceeds_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let budget = IoBudget::new(2);
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        (0..64).into_par_iter().for_each(|_| {
+            let _permit = budget.acquire();
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::yield_now();
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_map_dirs_lists_each_directory_once_in_order() {
+        let temp = tempfile::tempdir().unwrap();
+        for dir in ["b/deep/deeper", "a/skip/inner", "a/kept"] {
+            fs::create_dir_all(temp.path().join(dir)).unwrap();
+        }
+        fs::write(temp.path().join("a/marker.txt"), "").unwrap();
+
+        let cancel = CancellationToken::new();
+        let listed = ParallelWalk::new(&cancel).map_dirs(
+            temp.path(),
+            2,
+            |name| name != "skip",
+            |listing| {
+                let dir = listing.dir.strip_prefix(temp.path()).unwrap();
+                (dir.to_path_buf(), listing.has_file("marker.txt"))
+            },
+        );
+        let expected = [
+            ("", false),
+            ("a", true),
+            ("a/kept", false),
+            ("b", false),
+            ("b/deep", false),
+        ];
+        assert_eq!(
+            listed,
+            expected
+                .iter()
+                .map(|(dir, marked)| (PathBuf::from(dir), *marked))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_rate_limited_budget_spaces_reads() {
+        let budget = IoBudget::rate_limited(4, 100);
+        let start = Instant::now();
+        for _ in 0..6 {
+            drop(budget.acquire());
+        }
+        // The first read starts at once, each later one 10ms after the last
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -2,9 +2,19 @@
 //! Testing StorageBackend and CategoryData
 
 use devsweep::backend::{CategoryData, StorageBackend};
-use devsweep::types::{CheckResult, CleanupItem};
+use devsweep::cancel::CancellationToken;
+use devsweep::checkers::ScanContext;
+use devsweep::command_runner::{CommandOutput, ScriptedRunner};
+use devsweep::error::DevSweepError;
+use devsweep::mounts::{Mount, MountTable};
+use devsweep::scan_mode::ScanMode;
+use devsweep::scan_progress::ScanEvent;
+use devsweep::size_index::SizeIndex;
+use devsweep::types::{CheckFailure, CheckResult, CleanupAction, CleanupItem, InUse};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 use tempfile::TempDir;
 
 #[test]
@@ -180,6 +190,443 @@ fn test_execute_cleanup_with_invalid_path() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_execute_cleanup_reports_missing_path() {
+    let backend = StorageBackend::new();
+    let path = PathBuf::from("/nonexistent/path/that/does/not/exist");
+    let item = CleanupItem::new("test", 100, "100 B").with_path(path.clone());
+
+    assert_eq!(
+        backend.execute_cleanup(&item).unwrap_err(),
+        DevSweepError::NotFound { path }
+    );
+    assert_eq!(
+        backend
+            .execute_cleanup(&CleanupItem::new("nothing", 0, "0 B"))
+            .unwrap_err(),
+        DevSweepError::NoCleanupAction
+    );
+}
+
+#[test]
+fn test_failed_command_keeps_exit_code_and_stderr() {
+    let backend = StorageBackend::new();
+    let item = CleanupItem::new("failing", 0, "0 B").with_cleanup_action(CleanupAction::shell(
+        "echo 'daemon not running' >&2; exit 3",
+    ));
+
+    match backend.execute_cleanup(&item).unwrap_err() {
+        DevSweepError::CommandFailed {
+            exit_code, stderr, ..
+        } => {
+            assert_eq!(exit_code, Some(3));
+            assert_eq!(stderr.trim(), "daemon not running");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_cleanup_actions_run_without_a_shell() {
+    let repo = TempDir::new().unwrap();
+    let runner = Arc::new(ScriptedRunner::new().with_output(
+        &["git", "branch", "-d", "fix; rm -rf ~"],
+        CommandOutput::failure_with(1, "warning: not fully merged"),
+    ));
+    let backend = StorageBackend::new()
+        .with_scan_context(ScanContext::for_home(repo.path()).with_runner(runner.clone()));
+    let action =
+        CleanupAction::command("git", ["branch", "-d", "fix; rm -rf ~"]).in_dir(repo.path());
+
+    let item = CleanupItem::new("branches", 0, "0 B").with_cleanup_action(action.clone());
+    assert!(matches!(
+        backend.execute_cleanup(&item),
+        Err(DevSweepError::CommandFailed {
+            exit_code: Some(1),
+            ..
+        })
+    ));
+    let item = item.with_cleanup_action(action.with_success_codes(&[0, 1]));
+    assert!(backend.execute_cleanup(&item).is_ok());
+
+    let calls = runner.calls();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].program, "git");
+    assert_eq!(calls[0].current_dir.as_deref(), Some(repo.path()));
+}
+
+#[test]
+fn test_scan_with_events_reports_every_checker() {
+    let home = TempDir::new().unwrap();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(&trash).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+
+    let mut backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    let (tx, rx) = mpsc::channel();
+    let categories = backend.scan_with_events(false, tx, &CancellationToken::new());
+    let events: Vec<ScanEvent> = rx.try_iter().collect();
+
+    // Don't leave fixture results in the on-disk scan cache
+    backend.scan_cache.clear();
+    let _ = backend.scan_cache.save();
+
+    let checkers = match events.first() {
+        Some(ScanEvent::Started { checkers, cached }) => {
+            assert!(cached.is_empty());
+            checkers.clone()
+        }
+        other => panic!("expected Started, got {:?}", other),
+    };
+    assert!(matches!(
+        events.last(),
+        Some(ScanEvent::Finished {
+            total_size: 1000,
+            cancelled: false
+        })
+    ));
+
+    for name in &checkers {
+        let started = events
+            .iter()
+            .position(|e| matches!(e, ScanEvent::CheckerStarted { name: n } if n == name));
+        let finished = events.iter().position(
+            |e| matches!(e, ScanEvent::CheckerFinished { category, .. } if &category.name == name),
+        );
+        assert!(started.unwrap() < finished.unwrap(), "{}", name);
+    }
+
+    let trash_event = events.iter().find_map(|e| match e {
+        ScanEvent::CheckerFinished { category, .. } if category.name == "Trash" => Some(category),
+        _ => None,
+    });
+    assert_eq!(trash_event.unwrap().total_size, 1000);
+    assert_eq!(categories.len(), checkers.len());
+}
+
+#[test]
+fn test_cancelled_scan_skips_checkers() {
+    let home = TempDir::new().unwrap();
+    let mut backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let (tx, rx) = mpsc::channel();
+    let categories = backend.scan_with_events(false, tx, &cancel);
+    let events: Vec<ScanEvent> = rx.try_iter().collect();
+
+    assert!(categories.is_empty());
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, ScanEvent::CheckerStarted { .. })));
+    assert!(matches!(
+        events.last(),
+        Some(ScanEvent::Finished {
+            cancelled: true,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_first_scan_sends_estimates_before_exact_sizes() {
+    let home = TempDir::new().unwrap();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(&trash).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+
+    let mut backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    // Nothing sized yet: a first scan
+    backend.size_index = Arc::new(SizeIndex::new());
+    assert!(backend.needs_estimate_pass());
+
+    let (tx, rx) = mpsc::channel();
+    let categories = backend.scan_with_events(false, tx, &CancellationToken::new());
+    let events: Vec<ScanEvent> = rx.try_iter().collect();
+    backend.scan_cache.clear();
+    let _ = backend.scan_cache.save();
+
+    let estimated = events
+        .iter()
+        .position(|e| matches!(e, ScanEvent::Estimated { category } if category.name == "Trash"))
+        .expect("Trash was estimated");
+    let finished = events
+        .iter()
+        .position(
+            |e| matches!(e, ScanEvent::CheckerFinished { category, .. } if category.name == "Trash"),
+        )
+        .unwrap();
+    assert!(estimated < finished);
+    if let ScanEvent::Estimated { category } = &events[estimated] {
+        assert!(category.is_estimate());
+        assert!(category.size.starts_with('~'));
+        assert!(category.items.iter().all(|i| i.is_estimate));
+    }
+
+    // The exact pass replaces every estimate
+    assert!(categories.iter().all(|c| !c.is_estimate()));
+}
+
+#[test]
+fn test_scan_estimate_is_not_cached() {
+    let home = TempDir::new().unwrap();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(&trash).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+
+    let backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    let categories = backend.scan_estimate(&CancellationToken::new());
+    let trash = categories.iter().find(|c| c.name == "Trash").unwrap();
+    // A tree this small is counted exactly, but still marked as an estimate
+    assert_eq!(trash.total_size, 1000);
+    assert!(trash.is_estimate());
+    assert!(backend.categories.is_empty());
+}
+
+#[test]
+fn test_background_scan_finds_the_same_items() {
+    let home = TempDir::new().unwrap();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(trash.join("nested")).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+    fs::write(trash.join("nested/older.txt"), vec![0u8; 500]).unwrap();
+
+    let context = ScanContext::for_home(home.path()).with_scan_mode(ScanMode::Background);
+    let mut backend = StorageBackend::new().with_scan_context(context);
+    let categories = backend.scan_with_cache(false);
+    backend.scan_cache.clear();
+    let _ = backend.scan_cache.save();
+
+    let trash = categories.iter().find(|c| c.name == "Trash").unwrap();
+    assert_eq!(trash.total_size, 1500);
+}
+
+#[test]
+fn test_network_mounts_are_skipped_and_reported() {
+    let home = TempDir::new().unwrap();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(trash.join("share")).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+    fs::write(trash.join("share/remote.bin"), vec![0u8; 5000]).unwrap();
+
+    // Pretend `share` is an SMB share mounted in the Trash
+    let mounts = MountTable::new(vec![Mount::new(trash.join("share"), "smbfs")]);
+    let context = ScanContext::for_home(home.path()).with_mounts(Arc::new(mounts));
+    let mut backend = StorageBackend::new().with_scan_context(context);
+    let categories = backend.scan_with_cache(false);
+    backend.scan_cache.clear();
+    let _ = backend.scan_cache.save();
+
+    let trash_category = categories.iter().find(|c| c.name == "Trash").unwrap();
+    assert_eq!(trash_category.total_size, 1000);
+    let status = trash_category.status.as_deref().unwrap_or_default();
+    assert!(status.contains("Skipped mounted filesystem"), "{}", status);
+    assert!(status.contains("smbfs"), "{}", status);
+}
+
+#[test]
+fn test_checkers_that_overrun_their_timeout_are_reported_as_failed() {
+    let home = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".Trash")).unwrap();
+    fs::write(home.path().join(".Trash/old.txt"), vec![0u8; 1000]).unwrap();
+
+    let context = ScanContext::for_home(home.path()).with_checker_timeout(Duration::ZERO);
+    let mut backend = StorageBackend::new().with_scan_context(context);
+    backend.scan_cache.clear();
+    let categories = backend.scan_with_cache(false);
+
+    let trash = categories.iter().find(|c| c.name == "Trash").unwrap();
+    assert_eq!(
+        trash.failure,
+        Some(CheckFailure::TimedOut { after_secs: 0 })
+    );
+    assert_eq!(trash.status.as_deref(), Some("Timed out after 0s"));
+    assert!(trash.items.is_empty());
+    // Nothing from a failed checker is cached
+    assert!(backend.scan_cache.get_valid_category("Trash").is_none());
+}
+
+#[test]
+fn test_estimated_items_cannot_be_cleaned() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("estimated.txt");
+    fs::write(&file, "not yet").unwrap();
+
+    let mut backend = StorageBackend::new();
+    let items = vec![CleanupItem::new("estimated", 7, "7 B")
+        .with_path(file.clone())
+        .into_estimate()];
+    let record = backend.execute_cleanup_with_history(&items, false);
+
+    assert_eq!(record.error_count, 1);
+    assert!(matches!(
+        record.failures().next().map(|(_, e)| e),
+        Some(DevSweepError::SizeEstimated { .. })
+    ));
+    assert!(file.exists());
+}
+
+#[test]
+fn test_cleanup_refuses_protected_locations() {
+    let home = TempDir::new().unwrap();
+    let documents = home.path().join("Documents");
+    let projects = home.path().join("Projects");
+    fs::create_dir_all(&documents).unwrap();
+    fs::create_dir_all(&projects).unwrap();
+    fs::write(documents.join("thesis.tex"), "keep").unwrap();
+
+    let mut backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    let items = vec![
+        CleanupItem::new("documents", 4, "4 B").with_path(documents.clone()),
+        CleanupItem::new("projects", 0, "0 B").with_path(projects.clone()),
+    ];
+    let record = backend.execute_cleanup_with_history(&items, true);
+
+    // Refusals are recorded with the item, and nothing is touched
+    assert_eq!(record.error_count, 2);
+    assert!(record
+        .failures()
+        .all(|(_, e)| matches!(e, DevSweepError::Protected { .. })));
+    assert!(documents.join("thesis.tex").exists());
+    assert!(projects.exists());
+
+    let home_item = CleanupItem::new("home", 4, "4 B").with_path(home.path().to_path_buf());
+    let err = backend.execute_cleanup(&home_item).unwrap_err();
+    assert!(
+        err.to_string().ends_with("it is a protected location"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cleanup_refuses_paths_swapped_for_symlinks() {
+    let home = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    fs::write(outside.path().join("precious.txt"), "keep").unwrap();
+
+    // A cache directory replaced by a link to somewhere else after the scan
+    let cache = home.path().join(".cache");
+    std::os::unix::fs::symlink(outside.path(), &cache).unwrap();
+
+    let backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    let item = CleanupItem::new("cached file", 4, "4 B").with_path(cache.join("precious.txt"));
+    let err = backend.execute_cleanup(&item).unwrap_err();
+    assert!(err.to_string().contains("symlink"), "{}", err);
+    assert!(outside.path().join("precious.txt").exists());
+
+    // Deleting the link itself only removes the link
+    let link = CleanupItem::new("link", 0, "0 B").with_path(cache.clone());
+    backend.execute_cleanup(&link).unwrap();
+    assert!(fs::symlink_metadata(&cache).is_err());
+    assert!(outside.path().join("precious.txt").exists());
+}
+
+#[test]
+fn test_cleanup_refuses_denylist_and_quarantine() {
+    let home = TempDir::new().unwrap();
+    let keep = home.path().join("work/cache");
+    fs::create_dir_all(&keep).unwrap();
+
+    let mut backend = StorageBackend::new().with_scan_context(ScanContext::for_home(home.path()));
+    backend.deletion_settings.denylist = vec![PathBuf::from("~/work")];
+
+    let item = CleanupItem::new("work cache", 0, "0 B").with_path(keep.clone());
+    assert!(matches!(
+        backend.execute_cleanup(&item),
+        Err(DevSweepError::Protected { .. })
+    ));
+    assert!(keep.exists());
+
+    let quarantine = backend.cleanup_history.quarantine_dir().to_path_buf();
+    let item = CleanupItem::new("quarantine", 0, "0 B").with_path(quarantine.clone());
+    assert!(matches!(
+        backend.execute_cleanup(&item),
+        Err(DevSweepError::Protected { .. })
+    ));
+    assert!(quarantine.exists());
+}
+
+#[test]
+fn test_preflight_refreshes_stale_items_before_cleanup() {
+    let temp = TempDir::new().unwrap();
+    let target = temp.path().join("target");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("lib.rlib"), vec![0u8; 4 * 1024 * 1024]).unwrap();
+
+    // Sizes as a scan saw them hours ago
+    let items = vec![
+        CleanupItem::new("target", 1024, "1 KB").with_path(target.clone()),
+        CleanupItem::new("removed cache", 1024, "1 KB").with_path(temp.path().join("gone")),
+    ];
+    let mut backend = StorageBackend::new();
+    let preflight = backend.preflight(&items);
+
+    assert!(preflight.has_changes());
+    let remaining = preflight.items();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].size, 4 * 1024 * 1024);
+
+    // The history records the size that was actually cleaned
+    let record = backend.execute_cleanup_with_history(&remaining, false);
+    assert_eq!(record.success_count, 1);
+    assert_eq!(record.total_size, 4 * 1024 * 1024);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cleanup_skips_directories_a_process_is_running_in() {
+    let project = TempDir::new().unwrap();
+    let node_modules = project.path().join("node_modules");
+    fs::create_dir_all(node_modules.join(".bin")).unwrap();
+
+    // Stands in for a dev server started from the directory
+    let mut server = std::process::Command::new("sleep")
+        .arg("30")
+        .current_dir(&node_modules)
+        .spawn()
+        .unwrap();
+
+    let mut backend = StorageBackend::new();
+    let items = vec![CleanupItem::new("node_modules", 0, "0 B").with_path(node_modules.clone())];
+    let record = backend.execute_cleanup_with_history(&items, true);
+    let _ = server.kill();
+    let _ = server.wait();
+
+    assert_eq!(record.error_count, 1);
+    match record.failures().next().map(|(_, e)| e) {
+        Some(DevSweepError::InUse {
+            by: Some(InUse::Process { pid, name }),
+            ..
+        }) => {
+            assert_eq!(*pid, server.id());
+            assert_eq!(name, "sleep");
+        }
+        other => panic!("expected the item to be in use, got {:?}", other),
+    }
+    assert!(node_modules.exists());
+}
+
+#[test]
+fn test_cancelled_cleanup_leaves_items_untouched() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("keep.txt");
+    fs::write(&file, "still here").unwrap();
+
+    let mut backend = StorageBackend::new();
+    let items = vec![CleanupItem::new("keep", 10, "10 B").with_path(file.clone())];
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let record = backend.execute_cleanup_with_cancel(&items, false, &cancel);
+    assert!(record.cancelled);
+    assert_eq!(record.skipped_count, 1);
+    assert!(record.items.is_empty());
+    assert!(record.summary().starts_with("Cancelled"));
+    assert!(file.exists());
+}
+
 #[test]
 fn test_category_data_clone() {
     let mut result = CheckResult::new("Test");
@@ -439,3 +886,32 @@ fn test_rescan_consistency() {
 
     assert_eq!(count1, count3);
 }
+
+#[test]
+fn test_cleanup_invalidates_only_affected_categories() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("cache.bin");
+    fs::write(&file, "cached").unwrap();
+    let item = CleanupItem::new("cache.bin", 6, "6 B").with_path(file.clone());
+
+    let mut backend = StorageBackend::new();
+    let mut owner = CheckResult::new("Owner");
+    owner.add_item(item.clone());
+    backend
+        .scan_cache
+        .update_category("Owner".to_string(), owner, Default::default());
+    backend.scan_cache.update_category(
+        "Unrelated".to_string(),
+        CheckResult::new("Unrelated"),
+        Default::default(),
+    );
+
+    let record = backend.execute_cleanup_with_history(&[item], false);
+    assert_eq!(record.success_count, 1);
+
+    assert!(backend.scan_cache.needs_rescan("Owner"));
+    assert!(!backend.scan_cache.needs_rescan("Unrelated"));
+
+    backend.scan_cache.clear();
+    let _ = backend.scan_cache.save();
+}
@@ -1,6 +1,7 @@
 //! Cache settings module tests
 
 use devsweep::cache_settings::CacheSettings;
+use devsweep::checkers::registry;
 
 #[test]
 fn test_format_ttl() {
@@ -21,10 +22,24 @@ fn test_presets_exist() {
 fn test_trash_always_zero() {
     let presets = CacheSettings::get_presets();
     for (_name, categories) in presets {
-        for (cat_name, ttl) in categories {
-            if cat_name == "Trash" {
+        for (id, ttl) in categories {
+            if id == "trash" {
                 assert_eq!(ttl, 0, "Trash should always have TTL = 0");
             }
         }
     }
 }
+
+#[test]
+fn test_preset_ids_are_registered() {
+    for (preset, categories) in CacheSettings::get_presets() {
+        for (id, _ttl) in categories {
+            assert!(
+                registry::find(id).is_some(),
+                "preset '{}' names unknown checker '{}'",
+                preset,
+                id
+            );
+        }
+    }
+}
@@ -1,131 +1,161 @@
 //! Tests for checker modules
 
-use devsweep::checkers;
+use devsweep::checkers::{self, ProjectKind, ScanContext};
+use devsweep::command_runner::ScriptedRunner;
+use devsweep::scan_cache::{CacheConfig, PathTracker};
+use devsweep::scan_progress::{ProgressReporter, ScanEvent};
+use devsweep::types::SuperCategoryType;
+use std::fs;
+use std::sync::{mpsc, Arc, Mutex};
+use tempfile::TempDir;
+
+/// An empty fixture home with no tools installed
+fn empty_home() -> (TempDir, ScanContext) {
+    let temp = TempDir::new().unwrap();
+    let ctx = ScanContext::for_home(temp.path());
+    (temp, ctx)
+}
 
 #[test]
 fn test_check_docker_returns_result() {
-    let result = checkers::check_docker();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_docker(&ctx);
     assert_eq!(result.name, "Docker");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_homebrew_returns_result() {
-    let result = checkers::check_homebrew();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_homebrew(&ctx);
     assert_eq!(result.name, "Homebrew");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_python_returns_result() {
-    let result = checkers::check_python();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_python(&ctx);
     assert_eq!(result.name, "Python");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_rust_returns_result() {
-    let result = checkers::check_rust();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_rust(&ctx);
     assert_eq!(result.name, "Rust/Cargo");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_xcode_returns_result() {
-    let result = checkers::check_xcode();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_xcode(&ctx);
     assert_eq!(result.name, "Xcode");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_go_returns_result() {
-    let result = checkers::check_go();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_go(&ctx);
     assert_eq!(result.name, "Go");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_gradle_maven_returns_result() {
-    let result = checkers::check_gradle_maven();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_gradle_maven(&ctx);
     assert_eq!(result.name, "Java Build Tools");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_ide_caches_returns_result() {
-    let result = checkers::check_ide_caches();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_ide_caches(&ctx);
     assert_eq!(result.name, "IDE Caches");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_shell_caches_returns_result() {
-    let result = checkers::check_shell_caches();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_shell_caches(&ctx);
     assert_eq!(result.name, "Shell Caches");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_db_caches_returns_result() {
-    let result = checkers::check_db_caches();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_db_caches(&ctx);
     assert_eq!(result.name, "Database Caches");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_system_logs_returns_result() {
-    let result = checkers::check_system_logs();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_system_logs(&ctx);
     assert_eq!(result.name, "System Logs & Crash Reports");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_browser_caches_returns_result() {
-    let result = checkers::check_browser_caches();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_browser_caches(&ctx);
     assert_eq!(result.name, "Browser Caches");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_node_modules_returns_result() {
-    let result = checkers::check_node_modules();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_node_modules(&ctx);
     assert_eq!(result.name, "node_modules in Projects");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_general_caches_returns_result() {
-    let result = checkers::check_general_caches();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_general_caches(&ctx);
     assert_eq!(result.name, "General Caches");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_check_trash_returns_result() {
-    let result = checkers::check_trash();
+    let (_home, ctx) = empty_home();
+    let result = checkers::check_trash(&ctx);
     assert_eq!(result.name, "Trash");
-    // total_size is u64, always non-negative by type
+    assert!(result.items.is_empty());
 }
 
 #[test]
 fn test_all_checkers_have_valid_structure() {
+    let ctx = ScanContext::new();
     let checkers = vec![
-        checkers::check_docker(),
-        checkers::check_homebrew(),
-        checkers::check_npm_yarn(),
-        checkers::check_python(),
-        checkers::check_rust(),
-        checkers::check_xcode(),
-        checkers::check_go(),
-        checkers::check_gradle_maven(),
-        checkers::check_ide_caches(),
-        checkers::check_shell_caches(),
-        checkers::check_db_caches(),
-        checkers::check_system_logs(),
-        checkers::check_browser_caches(),
-        checkers::check_node_modules(),
-        checkers::check_general_caches(),
-        checkers::check_trash(),
+        checkers::check_docker(&ctx),
+        checkers::check_homebrew(&ctx),
+        checkers::check_npm_yarn(&ctx),
+        checkers::check_python(&ctx),
+        checkers::check_rust(&ctx),
+        checkers::check_xcode(&ctx),
+        checkers::check_go(&ctx),
+        checkers::check_gradle_maven(&ctx),
+        checkers::check_ide_caches(&ctx),
+        checkers::check_shell_caches(&ctx),
+        checkers::check_db_caches(&ctx),
+        checkers::check_system_logs(&ctx),
+        checkers::check_browser_caches(&ctx),
+        checkers::check_node_modules(&ctx),
+        checkers::check_general_caches(&ctx),
+        checkers::check_trash(&ctx),
     ];
 
     for result in checkers {
@@ -142,3 +172,299 @@ fn test_all_checkers_have_valid_structure() {
         }
     }
 }
+
+#[test]
+fn test_registered_checkers_report_their_own_name() {
+    let (_home, ctx) = empty_home();
+    for checker in checkers::registry::all() {
+        let result = checker.check(&ctx);
+        assert_eq!(result.name, checker.name(), "checker '{}'", checker.id());
+    }
+}
+
+#[test]
+fn test_every_registered_checker_has_default_ttl_and_super_category() {
+    let config = CacheConfig::default_config();
+    for checker in checkers::registry::all() {
+        assert_eq!(config.get_ttl(checker.name()), Some(checker.default_ttl()));
+        assert_eq!(
+            SuperCategoryType::from_category_name(checker.name()),
+            checker.super_category()
+        );
+    }
+}
+
+#[test]
+fn test_rust_checker_finds_fixture_registry_cache() {
+    let (home, ctx) = empty_home();
+    let cache = home.path().join(".cargo/registry/cache/index");
+    fs::create_dir_all(&cache).unwrap();
+    fs::write(cache.join("serde-1.0.0.crate"), vec![0u8; 4096]).unwrap();
+
+    let result = checkers::check_rust(&ctx);
+    let item = result
+        .items
+        .iter()
+        .find(|i| i.path.as_deref() == Some(home.path().join(".cargo/registry/cache").as_path()))
+        .expect("registry cache item");
+    assert_eq!(item.size, 4096);
+}
+
+#[test]
+fn test_homebrew_checker_flags_deprecated_leaves() {
+    let (home, ctx) = empty_home();
+    let prefix = home.path().join("opt/homebrew");
+    for (package, size) in [("openssl@1.1", 2048), ("youtube-dl", 1024), ("wget", 512)] {
+        let keg = prefix.join("Cellar").join(package);
+        fs::create_dir_all(&keg).unwrap();
+        fs::write(keg.join("lib.a"), vec![0u8; size]).unwrap();
+    }
+    let runner = ScriptedRunner::new()
+        .with_stdout(&["brew", "--version"], "Homebrew 4.2.0\n")
+        .with_stdout(&["brew", "--prefix"], &prefix.to_string_lossy())
+        .with_stdout(
+            &["brew", "leaves"],
+            include_str!("fixtures/commands/brew_leaves.txt"),
+        );
+    let ctx = ctx.with_runner(Arc::new(runner));
+
+    let result = checkers::check_homebrew(&ctx);
+
+    assert_eq!(
+        result.extra_data.leaf_packages.as_ref().map(Vec::len),
+        Some(3)
+    );
+    let item = result
+        .items
+        .iter()
+        .find(|i| i.item_type == "Potentially Unneeded Packages")
+        .expect("unneeded packages item");
+    assert_eq!(item.size, 3072);
+    let packages: Vec<&str> = item
+        .details
+        .iter()
+        .flatten()
+        .map(|d| d.name.as_str())
+        .collect();
+    assert_eq!(packages, ["openssl@1.1", "youtube-dl"]);
+}
+
+#[test]
+fn test_git_checker_lists_merged_branches() {
+    let (home, ctx) = empty_home();
+    let repo = home.path().join("Projects/app");
+    fs::create_dir_all(repo.join(".git")).unwrap();
+    let runner = Arc::new(
+        ScriptedRunner::new()
+            .with_stdout(
+                &["git", "symbolic-ref", "refs/remotes/origin/HEAD", "--short"],
+                "origin/main\n",
+            )
+            .with_stdout(
+                &["git", "branch", "--merged", "main"],
+                include_str!("fixtures/commands/git_branch_merged.txt"),
+            ),
+    );
+    let ctx = ctx.with_runner(runner.clone());
+
+    let result = checkers::check_git_repos(&ctx);
+
+    let item = result
+        .items
+        .iter()
+        .find(|i| i.item_type == "Merged branches in app")
+        .expect("merged branches item");
+    assert_eq!(item.size_str, "2 branches");
+    assert_eq!(
+        item.warning.as_deref(),
+        Some("Branches: feature/login, fix-typo")
+    );
+    assert!(runner
+        .calls()
+        .iter()
+        .all(|call| call.current_dir.as_deref() == Some(repo.as_path())));
+}
+
+#[test]
+fn test_checker_reports_progress_through_context() {
+    let (home, ctx) = empty_home();
+    let cache = home.path().join(".cargo/registry/cache/index");
+    fs::create_dir_all(&cache).unwrap();
+    fs::write(cache.join("serde-1.0.0.crate"), vec![0u8; 4096]).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let reporter = ProgressReporter::new("Rust/Cargo", tx);
+    checkers::check_rust(&ctx.with_progress(reporter.clone()));
+
+    assert!(reporter.bytes() >= 4096);
+    drop(reporter);
+    assert!(rx
+        .try_iter()
+        .any(|event| matches!(event, ScanEvent::Progress { name, .. } if name == "Rust/Cargo")));
+}
+
+#[test]
+fn test_cargo_home_env_overrides_default_location() {
+    let (home, ctx) = empty_home();
+    let cargo_home = home.path().join("elsewhere/cargo");
+    let cache = cargo_home.join("registry/cache/index");
+    fs::create_dir_all(&cache).unwrap();
+    fs::write(cache.join("serde-1.0.0.crate"), vec![0u8; 4096]).unwrap();
+    let ctx = ctx.with_env("CARGO_HOME", &cargo_home.to_string_lossy());
+
+    let result = checkers::check_rust(&ctx);
+    assert!(result
+        .items
+        .iter()
+        .any(|i| i.path.as_deref() == Some(cargo_home.join("registry/cache").as_path())));
+}
+
+#[test]
+fn test_trash_checker_reads_fixture_trash() {
+    let (home, ctx) = empty_home();
+    let trash = home.path().join(".Trash");
+    fs::create_dir_all(&trash).unwrap();
+    fs::write(trash.join("old.txt"), vec![0u8; 1000]).unwrap();
+    fs::write(trash.join("older.txt"), vec![0u8; 500]).unwrap();
+
+    let result = checkers::check_trash(&ctx);
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].size, 1500);
+    assert_eq!(result.items[0].path.as_deref(), Some(trash.as_path()));
+}
+
+#[test]
+fn test_project_roots_limit_project_discovery() {
+    let (home, ctx) = empty_home();
+    let project = home.path().join("elsewhere/app");
+    let node_modules = project.join("node_modules");
+    fs::create_dir_all(&node_modules).unwrap();
+    fs::write(project.join("package.json"), "{}").unwrap();
+    // Sparse file: reported length counts towards the size threshold
+    fs::File::create(node_modules.join("blob"))
+        .unwrap()
+        .set_len(60 * 1024 * 1024)
+        .unwrap();
+
+    assert!(checkers::check_node_modules(&ctx).items.is_empty());
+
+    let ctx = ctx.with_project_roots(vec![home.path().join("elsewhere")]);
+    let result = checkers::check_node_modules(&ctx);
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(
+        result.items[0].path.as_deref(),
+        Some(node_modules.as_path())
+    );
+}
+
+#[test]
+fn test_project_walk_tracks_discovery_dirs() {
+    let (temp, ctx) = empty_home();
+    fs::create_dir_all(temp.path().join(".cargo")).unwrap();
+    fs::create_dir_all(temp.path().join("Projects/app/src")).unwrap();
+
+    let tracker = Arc::new(Mutex::new(PathTracker::new()));
+    checkers::check_rust(&ctx.with_tracker(tracker.clone()));
+
+    let paths = std::mem::take(&mut *tracker.lock().unwrap()).into_paths();
+    assert!(paths.contains_key(&temp.path().join("Projects")));
+    assert!(paths.contains_key(&temp.path().join("Projects/app")));
+}
+
+#[test]
+fn test_project_walk_classifies_projects_once() {
+    let (home, ctx) = empty_home();
+    let projects = home.path().join("Projects");
+    fs::create_dir_all(projects.join("tool/.git")).unwrap();
+    fs::write(projects.join("tool/Cargo.toml"), "[package]").unwrap();
+    fs::create_dir_all(projects.join("web")).unwrap();
+    fs::write(projects.join("web/package.json"), "{}").unwrap();
+    // Projects inside dependency and build directories are not discovered
+    fs::create_dir_all(projects.join("web/node_modules/dep")).unwrap();
+    fs::write(projects.join("web/node_modules/dep/package.json"), "{}").unwrap();
+    fs::create_dir_all(projects.join("tool/target/pkg")).unwrap();
+    fs::write(projects.join("tool/target/pkg/Cargo.toml"), "").unwrap();
+
+    let checker_ctx = ctx.clone();
+    assert!(!ctx.projects.is_done());
+    let index = checker_ctx.projects();
+    assert!(ctx.projects.is_done());
+
+    let paths = |kind| {
+        index
+            .of_kind(kind)
+            .map(|p| p.path.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths(ProjectKind::Cargo), vec![projects.join("tool")]);
+    assert_eq!(paths(ProjectKind::Git), vec![projects.join("tool")]);
+    assert_eq!(paths(ProjectKind::Npm), vec![projects.join("web")]);
+    assert!(paths(ProjectKind::Python).is_empty());
+}
+
+#[test]
+fn test_project_walk_respects_max_depth() {
+    let (home, ctx) = empty_home();
+    let deep = home.path().join("Projects/a/b/c");
+    fs::create_dir_all(&deep).unwrap();
+    fs::write(deep.join("pyproject.toml"), "").unwrap();
+
+    assert_eq!(ctx.projects().of_kind(ProjectKind::Python).count(), 1);
+    let shallow = ctx.with_max_depth(2);
+    assert_eq!(shallow.projects().of_kind(ProjectKind::Python).count(), 0);
+}
+
+/// A node_modules of `mb` megabytes (sparse) in `project`, with a package.json
+fn npm_project(project: &std::path::Path, package_json: &str, mb: u64) {
+    let node_modules = project.join("node_modules");
+    fs::create_dir_all(&node_modules).unwrap();
+    fs::write(project.join("package.json"), package_json).unwrap();
+    fs::File::create(node_modules.join("blob"))
+        .unwrap()
+        .set_len(mb * 1024 * 1024)
+        .unwrap();
+}
+
+#[test]
+fn test_nested_node_modules_are_not_reported_twice() {
+    let (home, ctx) = empty_home();
+    let project = home.path().join("Projects/app");
+    npm_project(&project, "{}", 60);
+    npm_project(&project.join("node_modules/dep"), "{}", 60);
+
+    let result = checkers::check_node_modules(&ctx);
+    assert_eq!(result.items.len(), 1);
+    // The nested node_modules (and its package.json) count towards the outer one
+    assert!(result.items[0].size > 120 * 1024 * 1024);
+    assert_eq!(
+        result.items[0].path.as_deref(),
+        Some(project.join("node_modules").as_path())
+    );
+}
+
+#[test]
+fn test_workspace_packages_are_grouped_under_root() {
+    let (home, ctx) = empty_home();
+    let repo = home.path().join("Projects/mono");
+    npm_project(&repo, r#"{"workspaces": ["packages/*"]}"#, 40);
+    npm_project(&repo.join("packages/web"), "{}", 20);
+    npm_project(&repo.join("packages/api"), "{}", 10);
+    // A separate project next to the workspace stays its own item
+    npm_project(&home.path().join("Projects/solo"), "{}", 60);
+
+    let result = checkers::check_node_modules(&ctx);
+    assert_eq!(result.items.len(), 2);
+
+    let workspace = &result.items[0];
+    assert!(workspace.item_type.contains("workspace"));
+    assert_eq!(workspace.size, 70 * 1024 * 1024);
+    assert!(workspace.path.is_none());
+    assert!(workspace.is_group);
+    let details = workspace.details.as_ref().unwrap();
+    let names: Vec<&str> = details.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, vec!["(root)", "packages/web", "packages/api"]);
+    assert_eq!(
+        details[1].path.as_deref(),
+        Some(repo.join("packages/web/node_modules").as_path())
+    );
+}
@@ -1,6 +1,8 @@
 //! Cleanup history and quarantine tests
 
 use devsweep::cleanup_history::{CleanupHistory, CleanupItemRecord, CleanupRecord};
+use devsweep::deletion_policy::DeletionPolicy;
+use devsweep::error::DevSweepError;
 use devsweep::types::CleanupItem;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -39,7 +41,7 @@ fn test_quarantine_item_with_tempfile() {
     let history = CleanupHistory::new();
     let item = CleanupItem::new("test file", 100, "100 B").with_path(test_file.clone());
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     match result {
         Ok(quarantine_path) => {
@@ -60,10 +62,24 @@ fn test_quarantine_nonexistent_file() {
     let item = CleanupItem::new("nonexistent", 100, "100 B")
         .with_path(PathBuf::from("/tmp/nonexistent_file_12345.txt"));
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
     assert!(result.is_err(), "Should fail for nonexistent file");
 }
 
+#[test]
+fn test_quarantine_refuses_protected_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let protected = temp_dir.path().join("Documents");
+    std::fs::create_dir_all(&protected).unwrap();
+
+    let history = CleanupHistory::new();
+    let item = CleanupItem::new("documents", 100, "100 B").with_path(protected.clone());
+    let result = history.quarantine_item(&item, &DeletionPolicy::new().protect(&protected));
+
+    assert!(matches!(result, Err(DevSweepError::Protected { .. })));
+    assert!(protected.exists(), "Protected path must not be moved");
+}
+
 #[test]
 fn test_history_stats_structure() {
     let history = CleanupHistory::new();
@@ -102,7 +118,7 @@ fn test_restore_from_quarantine() {
     let item = CleanupItem::new("restore test", 20, "20 B").with_path(test_file.clone());
 
     // First, quarantine the file
-    let quarantine_result = history.quarantine_item(&item);
+    let quarantine_result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     if let Ok(quarantine_path) = quarantine_result {
         // Verify file is in quarantine
@@ -149,8 +165,8 @@ fn test_restore_multiple_items() {
     let item2 = CleanupItem::new("file2", 8, "8 B").with_path(file2.clone());
 
     if let (Ok(q_path1), Ok(_q_path2)) = (
-        history.quarantine_item(&item1),
-        history.quarantine_item(&item2),
+        history.quarantine_item(&item1, &DeletionPolicy::new()),
+        history.quarantine_item(&item2, &DeletionPolicy::new()),
     ) {
         // Create record for first file
         let record1 = CleanupItemRecord::success(&item1, Some(q_path1));
@@ -173,7 +189,7 @@ fn test_permanent_delete_from_quarantine() {
     let item = CleanupItem::new("delete test", 19, "19 B").with_path(test_file.clone());
 
     // Quarantine the file
-    let quarantine_result = history.quarantine_item(&item);
+    let quarantine_result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     if let Ok(quarantine_path) = quarantine_result {
         assert!(quarantine_path.exists());
@@ -206,7 +222,7 @@ fn test_quarantine_directory() {
     let history = CleanupHistory::new();
     let item = CleanupItem::new("test directory", 10, "10 B").with_path(test_subdir.clone());
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     match result {
         Ok(quarantine_path) => {
@@ -233,7 +249,7 @@ fn test_quarantine_stats_after_operations() {
     let item = CleanupItem::new("stats test", 13, "13 B").with_path(test_file.clone());
 
     // Quarantine an item
-    let _ = history.quarantine_item(&item);
+    let _ = history.quarantine_item(&item, &DeletionPolicy::new());
 
     // Get stats after quarantine
     let after_stats = history.stats();
@@ -298,7 +314,7 @@ fn test_quarantine_preserves_file_content() {
     let item = CleanupItem::new("content test", test_content.len() as u64, "50 B")
         .with_path(test_file.clone());
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     if let Ok(quarantine_path) = result {
         // Read content from quarantine
@@ -317,7 +333,7 @@ fn test_quarantine_empty_file() {
     let history = CleanupHistory::new();
     let item = CleanupItem::new("empty file", 0, "0 B").with_path(test_file.clone());
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     // Should handle empty files gracefully
     match result {
@@ -345,6 +361,7 @@ fn test_restore_nonexistent_file() {
         size: 100,
         success: true,
         error_message: None,
+        error: None,
         deleted_permanently: false,
     };
 
@@ -384,6 +401,64 @@ fn test_history_persistence() {
     // Just verify it loads without crashing
 }
 
+#[test]
+fn test_failed_items_carry_structured_errors() {
+    let on_other_disk = CleanupItem::new("external cache", 10, "10 B")
+        .with_path(PathBuf::from("/Volumes/ext/cache"));
+    let locked = CleanupItem::new("locked", 10, "10 B").with_path(PathBuf::from("/tmp/locked"));
+
+    let mut record = CleanupRecord::new("errors-test".to_string());
+    record.add_item(CleanupItemRecord::error(
+        &on_other_disk,
+        DevSweepError::CrossDevice {
+            path: PathBuf::from("/Volumes/ext/cache"),
+        },
+    ));
+    record.add_item(CleanupItemRecord::error(
+        &locked,
+        DevSweepError::PermissionDenied {
+            path: PathBuf::from("/tmp/locked"),
+        },
+    ));
+
+    assert_eq!(record.error_count, 2);
+    assert_eq!(record.failures().count(), 2);
+    assert!(record.items[1]
+        .error_message
+        .as_deref()
+        .unwrap()
+        .contains("Permission denied"));
+
+    // Only the cross-device failure is worth retrying as a permanent delete
+    let retry = record.permanent_retry_items(&[on_other_disk.clone(), locked]);
+    assert_eq!(retry.len(), 1);
+    assert_eq!(retry[0].item_type, "external cache");
+
+    // The error survives a save/load round trip
+    let json = serde_json::to_string(&record).unwrap();
+    let loaded: CleanupRecord = serde_json::from_str(&json).unwrap();
+    assert!(matches!(
+        loaded.items[0].error,
+        Some(DevSweepError::CrossDevice { .. })
+    ));
+}
+
+#[test]
+fn test_history_without_structured_errors_still_loads() {
+    let json = r#"{
+        "item_type": "cache",
+        "original_path": "/tmp/cache",
+        "quarantine_path": null,
+        "size": 10,
+        "success": false,
+        "error_message": "Failed to move to quarantine: Permission denied",
+        "deleted_permanently": false
+    }"#;
+    let item: CleanupItemRecord = serde_json::from_str(json).unwrap();
+    assert!(item.error.is_none());
+    assert!(item.error_message.is_some());
+}
+
 #[test]
 fn test_record_can_undo_flag() {
     let record = CleanupRecord::new("undo-test".to_string());
@@ -406,7 +481,7 @@ fn test_quarantine_with_unicode_filename() {
     let history = CleanupHistory::new();
     let item = CleanupItem::new("unicode file", 15, "15 B").with_path(test_file.clone());
 
-    let result = history.quarantine_item(&item);
+    let result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     // Should handle unicode filenames
     match result {
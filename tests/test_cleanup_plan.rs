@@ -0,0 +1,201 @@
+//! Tests for cleanup plans
+
+use devsweep::backend::StorageBackend;
+use devsweep::cancel::CancellationToken;
+use devsweep::cleanup_plan::{CleanupMode, CleanupPlan, PLAN_VERSION};
+use devsweep::error::DevSweepError;
+use devsweep::types::{CleanupAction, CleanupItem, ItemDetail};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn file_item(dir: &TempDir, name: &str, contents: &str) -> CleanupItem {
+    let path = dir.path().join(name);
+    fs::write(&path, contents).unwrap();
+    CleanupItem::new(name, contents.len() as u64, "")
+        .with_path(path)
+        .with_safe_to_delete(true)
+}
+
+#[test]
+fn test_plan_resolves_modes_and_warnings() {
+    let items = vec![
+        CleanupItem::new("cache", 100, "100 B")
+            .with_path(PathBuf::from("/tmp/devsweep-plan-cache"))
+            .with_safe_to_delete(true),
+        CleanupItem::new("Trash", 200, "200 B").with_path(PathBuf::from("/Users/dev/.Trash")),
+        CleanupItem::new("images", 300, "300 B")
+            .with_cleanup_action(CleanupAction::command("docker", ["image", "prune", "-f"]))
+            .with_warning("Removes dangling images"),
+    ];
+    let plan = CleanupPlan::new(&items, true);
+
+    let modes: Vec<CleanupMode> = plan.operations.iter().map(|op| op.mode).collect();
+    assert_eq!(
+        modes,
+        vec![
+            CleanupMode::Quarantine,
+            CleanupMode::Permanent,
+            CleanupMode::Command
+        ]
+    );
+    assert!(plan.operations[0].warnings.is_empty());
+    assert!(plan.operations[1].warnings[0].contains("Bypasses quarantine"));
+    assert!(plan.operations[2]
+        .warnings
+        .contains(&"Removes dangling images".to_string()));
+    assert_eq!(plan.operations[2].target(), "$ docker image prune -f");
+    assert_eq!(plan.total_bytes(), 600);
+    assert_eq!(
+        plan.summary(),
+        "3 operations (600 B): 1 quarantined, 1 deleted permanently, 1 commands"
+    );
+}
+
+#[test]
+fn test_plan_save_and_load_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let plan = CleanupPlan::new(&[file_item(&dir, "a.log", "hello")], false);
+    let plan_path = dir.path().join("plan.json");
+    plan.save(&plan_path).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&plan_path).unwrap()).unwrap();
+    assert_eq!(json["plan_version"], PLAN_VERSION);
+    assert_eq!(json["operations"][0]["mode"], "permanent");
+    assert_eq!(json["operations"][0]["item_type"], "a.log");
+
+    let loaded = CleanupPlan::load(&plan_path).unwrap();
+    assert_eq!(loaded.operations.len(), 1);
+    assert_eq!(
+        loaded.operations[0].fingerprint,
+        plan.operations[0].fingerprint
+    );
+    assert!(loaded.verify().is_empty());
+}
+
+#[test]
+fn test_load_rejects_newer_plan_version() {
+    let dir = TempDir::new().unwrap();
+    let plan_path = dir.path().join("plan.json");
+    let mut plan = CleanupPlan::new(&[], true);
+    plan.plan_version = PLAN_VERSION + 1;
+    plan.save(&plan_path).unwrap();
+
+    assert!(matches!(
+        CleanupPlan::load(&plan_path),
+        Err(DevSweepError::InvalidInput { .. })
+    ));
+}
+
+#[test]
+fn test_load_rejects_older_plan_version() {
+    let dir = TempDir::new().unwrap();
+    let plan_path = dir.path().join("plan.json");
+    let mut plan = CleanupPlan::new(&[], true);
+    plan.plan_version = PLAN_VERSION - 1;
+    plan.save(&plan_path).unwrap();
+
+    assert!(matches!(
+        CleanupPlan::load(&plan_path),
+        Err(DevSweepError::InvalidInput { .. })
+    ));
+}
+
+#[test]
+fn test_verify_detects_changed_and_missing_paths() {
+    let dir = TempDir::new().unwrap();
+    let changed = file_item(&dir, "changed.log", "short");
+    let removed = file_item(&dir, "removed.log", "gone");
+    let plan = CleanupPlan::new(&[changed.clone(), removed.clone()], false);
+
+    fs::write(changed.path.as_ref().unwrap(), "much longer contents").unwrap();
+    fs::remove_file(removed.path.as_ref().unwrap()).unwrap();
+
+    let problems = plan.verify();
+    assert_eq!(problems.len(), 2);
+    assert!(matches!(problems[0].1, DevSweepError::PlanMismatch { .. }));
+    assert!(matches!(problems[1].1, DevSweepError::NotFound { .. }));
+}
+
+#[test]
+fn test_apply_plan_skips_changed_items() {
+    let dir = TempDir::new().unwrap();
+    let unchanged = file_item(&dir, "unchanged.log", "same");
+    let changed = file_item(&dir, "changed.log", "before");
+    let plan = CleanupPlan::new(&[unchanged.clone(), changed.clone()], false);
+
+    fs::write(changed.path.as_ref().unwrap(), "after the plan was made").unwrap();
+
+    let mut backend = StorageBackend::new();
+    let record = backend.apply_plan(&plan, &CancellationToken::new());
+
+    assert_eq!(record.success_count, 1);
+    assert_eq!(record.error_count, 1);
+    assert!(!unchanged.path.unwrap().exists());
+    assert!(changed.path.unwrap().exists());
+    let (_, error) = record.failures().next().unwrap();
+    assert!(matches!(error, DevSweepError::PlanMismatch { .. }));
+}
+
+#[test]
+fn test_grouped_item_is_planned_per_detail() {
+    let group = CleanupItem::new("node_modules (workspace): /repo", 300, "300 B")
+        .with_group(vec![
+            ItemDetail::new("(root)", 200, "200 B").with_path(PathBuf::from("/repo/node_modules")),
+            ItemDetail::new("packages/web", 100, "100 B")
+                .with_path(PathBuf::from("/repo/packages/web/node_modules")),
+        ])
+        .with_warning("Run 'npm install' to restore");
+    let plan = CleanupPlan::new(&[group], true);
+
+    let targets: Vec<String> = plan.operations.iter().map(|op| op.target()).collect();
+    assert_eq!(
+        targets,
+        vec![
+            "/repo/node_modules".to_string(),
+            "/repo/packages/web/node_modules".to_string()
+        ]
+    );
+    assert!(plan
+        .operations
+        .iter()
+        .all(|op| op.mode == CleanupMode::Quarantine));
+    assert_eq!(plan.total_bytes(), 300);
+}
+
+#[test]
+fn test_items_listing_their_contents_are_not_planned_per_detail() {
+    let dir = TempDir::new().unwrap();
+    let wal = dir.path().join("var-16/pg_wal");
+    let journal = dir.path().join("data/db/journal");
+    fs::create_dir_all(&wal).unwrap();
+    fs::create_dir_all(&journal).unwrap();
+    let postgres = CleanupItem::new("PostgreSQL Logs & Cache", 200, "200 B")
+        .with_details(vec![ItemDetail::new(
+            "PostgreSQL WAL (var-16)",
+            200,
+            "200 B",
+        )
+        .with_path(wal.clone())])
+        .with_safe_to_delete(true);
+    let mongo = CleanupItem::new("MongoDB Logs & Cache", 100, "100 B")
+        .with_details(vec![
+            ItemDetail::new("MongoDB Journal", 100, "100 B").with_path(journal.clone())
+        ])
+        .with_safe_to_delete(true);
+    let plan = CleanupPlan::new(&[postgres, mongo], true);
+
+    assert_eq!(plan.operations.len(), 2);
+    assert!(plan.operations.iter().all(|op| op.target().is_empty()));
+
+    let mut backend = StorageBackend::new();
+    let record = backend.apply_plan(&plan, &CancellationToken::new());
+    assert_eq!(record.success_count, 0);
+    assert_eq!(record.error_count, 2);
+    assert!(record
+        .failures()
+        .all(|(_, error)| matches!(error, DevSweepError::NoCleanupAction)));
+    assert!(wal.exists());
+    assert!(journal.exists());
+}
@@ -0,0 +1,248 @@
+//! Tests for the headless CLI
+
+use devsweep::backend::{CategoryData, StorageBackend};
+use devsweep::cleanup_history::{CleanupHistory, CleanupItemRecord, CleanupRecord};
+use devsweep::cleanup_plan::CleanupPlan;
+use devsweep::cli::select::{
+    scanned_items, select_categories, select_items, select_quarantine_items,
+};
+use devsweep::cli::{self, Command};
+use devsweep::types::{CheckResult, CleanupItem};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+fn category(name: &str, items: &[(&str, u64)]) -> CategoryData {
+    let mut result = CheckResult::new(name);
+    for (item_type, size) in items {
+        result.add_item(
+            CleanupItem::new(item_type, *size, "")
+                .with_path(PathBuf::from(format!("/tmp/{}", item_type))),
+        );
+    }
+    CategoryData::new(name.to_string(), result)
+}
+
+fn sample_scan() -> Vec<CategoryData> {
+    vec![
+        category("Docker", &[("images", 100), ("volumes", 200)]),
+        category("Rust/Cargo", &[("registry", 300)]),
+        category("node_modules in Projects", &[("app", 400)]),
+        category("Node.js Package Managers", &[]),
+    ]
+}
+
+fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn test_scanned_items_use_category_ids() {
+    let ids: Vec<String> = scanned_items(&sample_scan())
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    assert_eq!(
+        ids,
+        vec!["docker/1", "docker/2", "rust/1", "node_modules/1"]
+    );
+}
+
+#[test]
+fn test_select_by_category_item_and_glob() {
+    let select = |selectors: &[&str]| -> Vec<String> {
+        let selectors: Vec<String> = selectors.iter().map(|s| s.to_string()).collect();
+        select_items(scanned_items(&sample_scan()), &selectors)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect()
+    };
+
+    assert_eq!(select(&["docker"]), vec!["docker/1", "docker/2"]);
+    assert_eq!(select(&["rust/1", "docker/2"]), vec!["docker/2", "rust/1"]);
+    assert_eq!(select(&["node*"]), vec!["node_modules/1"]);
+    assert_eq!(
+        select(&["*/1"]),
+        vec!["docker/1", "rust/1", "node_modules/1"]
+    );
+    assert_eq!(select(&["*"]).len(), 4);
+}
+
+#[test]
+fn test_select_registered_category_without_items_is_not_an_error() {
+    let selected = select_items(scanned_items(&sample_scan()), &["nodejs".to_string()]).unwrap();
+    assert!(selected.is_empty());
+}
+
+#[test]
+fn test_select_unknown_selector_is_an_error() {
+    let err = select_items(scanned_items(&sample_scan()), &["dokcer".to_string()]).unwrap_err();
+    assert!(err.contains("dokcer"));
+    assert!(select_items(scanned_items(&sample_scan()), &["docker/9".to_string()]).is_err());
+}
+
+#[test]
+fn test_select_categories_narrows_items_and_totals() {
+    let selected =
+        select_categories(sample_scan(), &["docker/2".to_string(), "rust".to_string()]).unwrap();
+
+    let names: Vec<&str> = selected.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["Docker", "Rust/Cargo"]);
+    assert_eq!(selected[0].items.len(), 1);
+    assert_eq!(selected[0].items[0].item_type, "volumes");
+    assert_eq!(selected[0].total_size, 200);
+    assert_eq!(selected[0].item_count, 1);
+    assert_eq!(selected[1].total_size, 300);
+}
+
+#[test]
+fn test_select_quarantine_items() {
+    let item = CleanupItem::new("cache", 10, "10 B").with_path(PathBuf::from("/tmp/cache"));
+    let mut first = CleanupRecord::new("cleanup_1".to_string());
+    first.add_item(CleanupItemRecord::success(
+        &item,
+        Some(PathBuf::from("/q/1")),
+    ));
+    first.add_item(CleanupItemRecord::success(
+        &item,
+        Some(PathBuf::from("/q/2")),
+    ));
+    let mut second = CleanupRecord::new("cleanup_2".to_string());
+    second.add_item(CleanupItemRecord::success(
+        &item,
+        Some(PathBuf::from("/q/3")),
+    ));
+    let records = vec![&first, &second];
+
+    assert_eq!(
+        select_quarantine_items(&records, &["cleanup_1/2".to_string()]).unwrap(),
+        vec![("cleanup_1".to_string(), 1)]
+    );
+    assert_eq!(
+        select_quarantine_items(&records, &["cleanup_*/1".to_string()]).unwrap(),
+        vec![("cleanup_1".to_string(), 0), ("cleanup_2".to_string(), 0)]
+    );
+    assert_eq!(
+        select_quarantine_items(&records, &["cleanup_2".to_string()]).unwrap(),
+        vec![("cleanup_2".to_string(), 0)]
+    );
+    assert!(select_quarantine_items(&records, &["cleanup_3/1".to_string()]).is_err());
+}
+
+#[test]
+fn test_is_cli_invocation() {
+    assert!(!cli::is_cli_invocation(&[]));
+    assert!(!cli::is_cli_invocation(&args("-psn_0_12345")));
+    assert!(cli::is_cli_invocation(&args("scan")));
+    assert!(cli::is_cli_invocation(&args("--help")));
+}
+
+#[test]
+fn test_parse_clean_flags() {
+    assert_eq!(
+        cli::parse(&args("clean --permanent --no-cache docker")).unwrap(),
+        Command::Clean {
+            selectors: vec!["docker".to_string()],
+            use_cache: false,
+            permanent: true,
+            yes: false,
+            dry_run: false,
+            save_plan: None,
+            background: false,
+        }
+    );
+}
+
+#[test]
+fn test_help_lists_every_subcommand() {
+    for command in [
+        "scan",
+        "clean",
+        "apply",
+        "undo",
+        "quarantine list",
+        "quarantine delete",
+    ] {
+        assert!(
+            cli::USAGE.contains(command),
+            "usage is missing '{}'",
+            command
+        );
+    }
+}
+
+#[test]
+fn test_undo_unknown_record_fails() {
+    let mut backend = StorageBackend::new();
+    let mut out = Vec::new();
+    let result = cli::execute(
+        Command::Undo {
+            record_id: Some("cleanup_does_not_exist".to_string()),
+        },
+        &mut backend,
+        &mut out,
+        &mut Cursor::new(Vec::new()),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_quarantine_delete_without_confirmation_is_aborted() {
+    let mut backend = StorageBackend::new();
+    let item = CleanupItem::new("cache", 10, "10 B").with_path(PathBuf::from("/tmp/cache"));
+    let mut record = CleanupRecord::new("cleanup_cli_test".to_string());
+    record.add_item(CleanupItemRecord::success(
+        &item,
+        Some(PathBuf::from("/q/1")),
+    ));
+    backend.cleanup_history = CleanupHistory::new();
+    backend.cleanup_history.add_record(record);
+
+    // EOF on stdin counts as "no"
+    let mut out = Vec::new();
+    let result = cli::execute(
+        Command::QuarantineDelete {
+            selectors: vec!["cleanup_cli_test/1".to_string()],
+            yes: false,
+        },
+        &mut backend,
+        &mut out,
+        &mut Cursor::new(Vec::new()),
+    );
+    assert_eq!(result.unwrap_err(), "Aborted");
+    assert!(String::from_utf8(out).unwrap().contains("[y/N]"));
+    assert!(!backend.get_quarantine_records()[0].items[0].deleted_permanently);
+}
+
+#[test]
+fn test_apply_refuses_plan_that_no_longer_matches() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("build.log");
+    std::fs::write(&path, "log").unwrap();
+    let item = CleanupItem::new("build.log", 3, "3 B").with_path(path.clone());
+    let plan_path = dir.path().join("plan.json");
+    CleanupPlan::new(&[item], true).save(&plan_path).unwrap();
+
+    std::fs::write(&path, "log with more lines").unwrap();
+
+    let mut backend = StorageBackend::new();
+    let mut out = Vec::new();
+    let result = cli::execute(
+        Command::Apply {
+            plan: plan_path,
+            dry_run: false,
+            yes: true,
+        },
+        &mut backend,
+        &mut out,
+        &mut Cursor::new(Vec::new()),
+    );
+
+    assert!(result
+        .unwrap_err()
+        .contains("changed since the plan was made"));
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("quarantine"));
+    assert!(out.contains("its size changed"));
+    assert!(path.exists());
+}
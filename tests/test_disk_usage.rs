@@ -0,0 +1,130 @@
+//! Tests for hardlink-aware and allocated-size accounting
+
+use devsweep::cancel::CancellationToken;
+use devsweep::disk_usage::{disk_usage, estimate_usage};
+use devsweep::size_index::SizeIndex;
+use devsweep::types::CleanupItem;
+use devsweep::utils::get_dir_size;
+use devsweep::walk::IoBudget;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_hardlinks_inside_tree_are_counted_once() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::create_dir_all(root.join("store")).unwrap();
+    fs::write(root.join("store/pkg.bin"), vec![1u8; 10_000]).unwrap();
+    fs::hard_link(root.join("store/pkg.bin"), root.join("linked.bin")).unwrap();
+
+    let usage = disk_usage(root);
+    assert_eq!(usage.apparent, 10_000);
+    assert_eq!(get_dir_size(root), 10_000);
+    assert!(usage.allocated > 0);
+    // Every link is inside the tree, so deleting it frees the file
+    assert_eq!(usage.reclaimable, usage.allocated);
+}
+
+#[test]
+fn test_hardlinks_outside_tree_are_not_reclaimable() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::create_dir_all(root.join("store")).unwrap();
+    fs::create_dir_all(root.join("project")).unwrap();
+    fs::write(root.join("store/pkg.bin"), vec![1u8; 10_000]).unwrap();
+    fs::write(root.join("project/own.bin"), vec![1u8; 5_000]).unwrap();
+    fs::hard_link(root.join("store/pkg.bin"), root.join("project/pkg.bin")).unwrap();
+
+    let usage = disk_usage(&root.join("project"));
+    assert_eq!(usage.apparent, 15_000);
+    assert!(usage.reclaimable < usage.allocated);
+    assert_eq!(
+        usage.reclaimable,
+        disk_usage(&root.join("project/own.bin")).allocated
+    );
+}
+
+#[test]
+fn test_sparse_file_allocates_less_than_its_length() {
+    let temp = TempDir::new().unwrap();
+    let image = temp.path().join("disk.img");
+    let file = fs::File::create(&image).unwrap();
+    file.set_len(64 * 1024 * 1024).unwrap();
+
+    let usage = disk_usage(temp.path());
+    assert_eq!(usage.apparent, 64 * 1024 * 1024);
+    assert!(usage.allocated < usage.apparent);
+}
+
+#[test]
+fn test_size_index_matches_full_walk_with_hardlinks() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("a/pkg.bin"), vec![1u8; 4_096]).unwrap();
+    fs::hard_link(root.join("a/pkg.bin"), root.join("b/pkg.bin")).unwrap();
+
+    let index = SizeIndex::new();
+    let cancel = CancellationToken::new();
+    assert_eq!(index.disk_usage(root, &cancel), disk_usage(root));
+    // A second, warm pass is served from the index and must agree
+    assert_eq!(index.disk_usage(root, &cancel), disk_usage(root));
+    assert_eq!(
+        index.disk_usage(&root.join("a"), &cancel),
+        disk_usage(&root.join("a"))
+    );
+}
+
+#[test]
+fn test_cleanup_item_reclaimable_falls_back_to_size() {
+    let item = CleanupItem::new("cache", 100, "100 B");
+    assert_eq!(item.reclaimable_size(), 100);
+    assert_eq!(item.with_reclaimable(40).reclaimable_size(), 40);
+}
+
+#[test]
+fn test_estimate_of_small_tree_is_exact() {
+    let temp = TempDir::new().unwrap();
+    for dir in ["a/b", "a/c", "d"] {
+        fs::create_dir_all(temp.path().join(dir)).unwrap();
+        fs::write(temp.path().join(dir).join("f.bin"), vec![1u8; 3_000]).unwrap();
+    }
+
+    let cancel = CancellationToken::new();
+    assert_eq!(
+        estimate_usage(temp.path(), 64, IoBudget::global(), &cancel),
+        disk_usage(temp.path())
+    );
+}
+
+#[test]
+fn test_estimate_of_wide_tree_is_close() {
+    let temp = TempDir::new().unwrap();
+    for d in 0..300 {
+        let dir = temp
+            .path()
+            .join(format!("l1-{}", d % 10))
+            .join(format!("l2-{}", d % 7))
+            .join(format!("dir-{}", d));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("f.bin"), vec![1u8; 1_000]).unwrap();
+    }
+
+    let estimate = estimate_usage(
+        temp.path(),
+        16,
+        IoBudget::global(),
+        &CancellationToken::new(),
+    )
+    .apparent;
+    let exact = disk_usage(temp.path()).apparent;
+    assert_eq!(exact, 300_000);
+    // Far fewer directories listed, but the same order of magnitude
+    assert!(
+        estimate > exact / 2 && estimate < exact * 2,
+        "estimated {} for {}",
+        estimate,
+        exact
+    );
+}
@@ -1,18 +1,86 @@
 //! Docker checker comprehensive tests
 //! Testing Docker container cache, image cache, and build cache detection with real file structures
 
-use devsweep::checkers;
+use devsweep::checkers::{self, ScanContext};
+use devsweep::command_runner::ScriptedRunner;
+use devsweep::types::CleanupAction;
 use std::fs;
+use std::sync::Arc;
 use tempfile::TempDir;
 
 #[test]
 fn test_docker_checker_basic_functionality() {
-    let result = checkers::check_docker();
+    let result = checkers::check_docker(&ScanContext::new());
 
     // Should return a result (items may be empty if Docker not installed)
     assert!(!result.name.is_empty());
 }
 
+#[test]
+fn test_docker_checker_parses_recorded_output() {
+    let home = TempDir::new().unwrap();
+    let runner = ScriptedRunner::new()
+        .with_stdout(&["docker", "info"], "Server Version: 24.0.7\n")
+        .with_stdout(
+            &["docker", "system", "df"],
+            include_str!("fixtures/commands/docker_system_df.txt"),
+        )
+        .with_stdout(
+            &["docker", "images", "-f", "dangling=true", "-q"],
+            "3f1a2b4c5d6e\n9a8b7c6d5e4f\n",
+        )
+        .with_stdout(
+            &["docker", "ps", "-a", "-f", "status=exited", "-q"],
+            "c0ffee123456\n",
+        )
+        .with_stdout(
+            &[
+                "docker",
+                "system",
+                "df",
+                "--format",
+                "{{.Type}}\t{{.Size}}\t{{.Reclaimable}}",
+            ],
+            include_str!("fixtures/commands/docker_system_df_format.txt"),
+        );
+    let ctx = ScanContext::for_home(home.path()).with_runner(Arc::new(runner));
+
+    let result = checkers::check_docker(&ctx);
+
+    assert_eq!(result.status.as_deref(), Some("installed"));
+    assert_eq!(result.extra_data.dangling_images, Some(2));
+    assert_eq!(result.extra_data.stopped_containers, Some(1));
+    assert!(result
+        .extra_data
+        .docker_summary
+        .as_deref()
+        .is_some_and(|summary| summary.contains("Build Cache")));
+
+    const MB: u64 = 1024 * 1024;
+    let reclaimable = (3.1 * (1024 * MB) as f64) as u64 + 100 * MB + 800 * MB;
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].size, reclaimable);
+    assert_eq!(
+        result.items[0].cleanup_action,
+        Some(CleanupAction::command("docker", ["system", "prune"]))
+    );
+}
+
+#[test]
+fn test_docker_checker_reports_missing_daemon() {
+    let home = TempDir::new().unwrap();
+    // No scripted output: every docker command fails as if not installed
+    let ctx = ScanContext::for_home(home.path()).with_runner(Arc::new(ScriptedRunner::new()));
+
+    let result = checkers::check_docker(&ctx);
+
+    assert_eq!(
+        result.status.as_deref(),
+        Some("Docker not installed or not running")
+    );
+    assert!(result.items.is_empty());
+}
+
 #[test]
 fn test_docker_container_cache_detection() {
     let temp = TempDir::new().unwrap();
@@ -257,7 +325,7 @@ fn test_docker_stopped_containers() {
 
 #[test]
 fn test_docker_checker_returns_valid_structure() {
-    let result = checkers::check_docker();
+    let result = checkers::check_docker(&ScanContext::new());
 
     // Verify result structure
     assert!(!result.name.is_empty());
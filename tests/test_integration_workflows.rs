@@ -4,6 +4,7 @@
 
 use devsweep::backend::StorageBackend;
 use devsweep::cleanup_history::{CleanupHistory, CleanupItemRecord, CleanupRecord};
+use devsweep::deletion_policy::DeletionPolicy;
 use devsweep::scan_cache::{PathTracker, ScanCache};
 use devsweep::types::{CheckResult, CleanupItem};
 use std::collections::HashMap;
@@ -85,7 +86,7 @@ fn test_quarantine_and_restore_workflow() {
         .with_path(test_file.clone());
 
     // Step 3: Quarantine the file
-    let quarantine_result = history.quarantine_item(&item);
+    let quarantine_result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     match quarantine_result {
         Ok(quarantine_path) => {
@@ -145,7 +146,7 @@ fn test_quarantine_multiple_files_workflow() {
         let item =
             CleanupItem::new(&format!("file {}", i), 10, "10 B").with_path(file_path.clone());
 
-        if let Ok(qpath) = history.quarantine_item(&item) {
+        if let Ok(qpath) = history.quarantine_item(&item, &DeletionPolicy::new()) {
             quarantine_paths.push((file_path.clone(), qpath));
         }
     }
@@ -569,7 +570,7 @@ fn test_full_workflow_with_history() {
     // Quarantine file
     let item = CleanupItem::new("workflow file", 16, "16 B").with_path(test_file.clone());
 
-    let quarantine_result = history.quarantine_item(&item);
+    let quarantine_result = history.quarantine_item(&item, &DeletionPolicy::new());
 
     match quarantine_result {
         Ok(qpath) => {
@@ -662,6 +663,7 @@ fn test_restore_nonexistent_quarantine_recovery() {
         size: 100,
         success: true,
         error_message: None,
+        error: None,
         deleted_permanently: false,
     };
 
@@ -1,13 +1,13 @@
 //! Node.js checker comprehensive tests
 //! Testing npm cache, node_modules, and yarn cache detection with real file structures
 
-use devsweep::checkers;
+use devsweep::checkers::{self, ScanContext};
 use std::fs;
 use tempfile::TempDir;
 
 #[test]
 fn test_nodejs_checker_basic_functionality() {
-    let result = checkers::check_npm_yarn();
+    let result = checkers::check_npm_yarn(&ScanContext::new());
 
     // Should return a result (items may be empty if no Node.js caches exist)
     assert!(!result.name.is_empty());
@@ -359,7 +359,7 @@ fn test_npm_cache_index_v5() {
 
 #[test]
 fn test_nodejs_checker_returns_valid_structure() {
-    let result = checkers::check_npm_yarn();
+    let result = checkers::check_npm_yarn(&ScanContext::new());
 
     // Verify result structure
     assert!(!result.name.is_empty());
@@ -0,0 +1,97 @@
+//! Tests for de-duplicating items reported by several categories
+
+use devsweep::backend::CategoryData;
+use devsweep::cleanup_plan::CleanupPlan;
+use devsweep::overlap::mark_overlaps;
+use devsweep::types::{CheckResult, CleanupAction, CleanupItem};
+use std::path::PathBuf;
+
+fn item(name: &str, path: &str, size: u64) -> CleanupItem {
+    CleanupItem::new(name, size, "").with_path(PathBuf::from(path))
+}
+
+fn category(name: &str, items: Vec<CleanupItem>) -> CategoryData {
+    let mut result = CheckResult::new(name);
+    for item in items {
+        result.add_item(item);
+    }
+    CategoryData::new(name.to_string(), result)
+}
+
+#[test]
+fn test_general_cache_duplicates_are_covered() {
+    let caches = "/home/dev/Library/Caches";
+    let mut categories = vec![
+        category(
+            "Homebrew",
+            vec![item("Homebrew cache", &format!("{}/Homebrew", caches), 500)],
+        ),
+        category(
+            "Browser Caches",
+            vec![item(
+                "Chrome cache",
+                &format!("{}/Google/Chrome/Default/Cache", caches),
+                300,
+            )],
+        ),
+        category(
+            "General Caches",
+            vec![
+                item("cache: Homebrew", &format!("{}/Homebrew", caches), 500),
+                item("cache: Google", &format!("{}/Google", caches), 400),
+                item("cache: Other", &format!("{}/Other", caches), 200),
+            ],
+        ),
+    ];
+
+    mark_overlaps(&mut categories);
+
+    // Same path: the specific checker listed first keeps it
+    assert!(!categories[0].items[0].is_covered());
+    assert_eq!(
+        categories[2].items[0].covered_by,
+        Some(PathBuf::from(format!("{}/Homebrew", caches)))
+    );
+    // Nested path: the outer item covers it
+    assert_eq!(
+        categories[1].items[0].covered_by,
+        Some(PathBuf::from(format!("{}/Google", caches)))
+    );
+    assert!(!categories[2].items[1].is_covered());
+
+    let totals: Vec<u64> = categories.iter().map(|c| c.total_size).collect();
+    assert_eq!(totals, vec![500, 0, 600]);
+    assert_eq!(totals.iter().sum::<u64>(), 1100);
+}
+
+#[test]
+fn test_marking_is_recomputed_from_scratch() {
+    let mut categories = vec![category(
+        "General Caches",
+        vec![item("a", "/a", 10), item("b", "/a/b", 5)],
+    )];
+    mark_overlaps(&mut categories);
+    assert!(categories[0].items[1].is_covered());
+
+    categories[0].items.remove(0);
+    mark_overlaps(&mut categories);
+    assert!(!categories[0].items[0].is_covered());
+    assert_eq!(categories[0].total_size, 5);
+}
+
+#[test]
+fn test_plan_skips_items_inside_selected_items() {
+    let items = vec![
+        item("inner", "/caches/Google/Chrome", 300),
+        item("outer", "/caches/Google", 400),
+        item("sweep", "/projects/app/target", 0)
+            .with_cleanup_action(CleanupAction::command("cargo", ["sweep"])),
+    ];
+    let plan = CleanupPlan::new(&items, true);
+    let planned: Vec<&str> = plan
+        .operations
+        .iter()
+        .map(|op| op.item.item_type.as_str())
+        .collect();
+    assert_eq!(planned, vec!["outer", "sweep"]);
+}
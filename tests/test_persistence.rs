@@ -441,8 +441,8 @@ fn test_save_error_message_quality() {
         }
         Err(e) => {
             // Error message should be descriptive
-            assert!(!e.is_empty());
-            assert!(e.len() > 5); // Not just a code
+            assert!(!e.to_string().is_empty());
+            assert!(e.to_string().len() > 5); // Not just a code
         }
     }
 }
@@ -457,7 +457,7 @@ fn test_history_save_error_message() {
         }
         Err(e) => {
             // Error message should be descriptive
-            assert!(!e.is_empty());
+            assert!(!e.to_string().is_empty());
         }
     }
 }
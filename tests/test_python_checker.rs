@@ -1,13 +1,13 @@
 //! Python checker comprehensive tests
 //! Testing __pycache__, pip cache, and virtualenv detection with real file structures
 
-use devsweep::checkers;
+use devsweep::checkers::{self, ScanContext};
 use std::fs;
 use tempfile::TempDir;
 
 #[test]
 fn test_python_checker_basic_functionality() {
-    let result = checkers::check_python();
+    let result = checkers::check_python(&ScanContext::new());
 
     // Should return a result (items may be empty if no Python caches exist)
     assert!(!result.name.is_empty());
@@ -344,7 +344,7 @@ fn test_empty_pycache() {
 
 #[test]
 fn test_python_checker_returns_valid_structure() {
-    let result = checkers::check_python();
+    let result = checkers::check_python(&ScanContext::new());
 
     // Verify result structure
     assert!(!result.name.is_empty());
@@ -0,0 +1,146 @@
+//! Tests for machine-readable scan reports
+
+use devsweep::backend::CategoryData;
+use devsweep::report::{ReportFormat, ReportItem, ScanReport, CSV_COLUMNS, REPORT_VERSION};
+use devsweep::types::{CheckResult, CleanupAction, CleanupItem, ItemDetail, SuperCategoryType};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn sample_categories() -> Vec<CategoryData> {
+    let mut docker = CheckResult::new("Docker");
+    docker.add_item(
+        CleanupItem::new("Dangling images", 2048, "2.0 KiB")
+            .with_cleanup_action(CleanupAction::command("docker", ["image", "prune", "-f"]))
+            .with_safe_to_delete(true),
+    );
+    docker.extra_data.dangling_images = Some(4);
+    docker.extra_data.docker_summary = Some("4 dangling images".to_string());
+
+    let mut rust = CheckResult::new("Rust/Cargo");
+    rust.add_item(
+        CleanupItem::new("target: my,app", 4096, "4.0 KiB")
+            .with_path(PathBuf::from("/home/dev/Projects/my,app/target"))
+            .with_warning("Rebuild needed: \"cargo build\"")
+            .with_details(vec![ItemDetail::new("debug", 4096, "4.0 KiB")]),
+    );
+
+    let mut go = CheckResult::new("Go");
+    go.status = Some("Go not installed".to_string());
+
+    let cached_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    vec![
+        CategoryData::new("Docker".to_string(), docker),
+        CategoryData::new("Rust/Cargo".to_string(), rust).with_cached_at(cached_at),
+        CategoryData::new("Go".to_string(), go),
+    ]
+}
+
+fn render(report: &ScanReport, format: ReportFormat) -> String {
+    let mut out = Vec::new();
+    report.write(format, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_report_summary() {
+    let report = ScanReport::new(&sample_categories());
+    assert_eq!(report.report_version, REPORT_VERSION);
+    assert_eq!(report.devsweep_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(report.total_size, 6144);
+    assert_eq!(report.item_count, 2);
+    assert_eq!(report.cached_categories, 1);
+
+    let ids: Vec<&str> = report.categories.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec!["docker", "rust", "go"]);
+    assert_eq!(
+        report.categories[0].super_category,
+        SuperCategoryType::DevelopmentTools
+    );
+}
+
+#[test]
+fn test_json_report_round_trips() {
+    let report = ScanReport::new(&sample_categories());
+    let json = render(&report, ReportFormat::Json);
+
+    let parsed: ScanReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.report_version, REPORT_VERSION);
+    assert_eq!(parsed.categories.len(), 3);
+
+    let docker = &parsed.categories[0].data;
+    assert_eq!(docker.extra_data.dangling_images, Some(4));
+    assert_eq!(
+        docker.items[0].cleanup_action,
+        Some(CleanupAction::command("docker", ["image", "prune", "-f"]))
+    );
+
+    let rust = &parsed.categories[1].data;
+    assert!(rust.from_cache);
+    assert_eq!(
+        rust.scanned_at,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    );
+    assert_eq!(rust.items[0].details.as_ref().unwrap().len(), 1);
+
+    assert_eq!(
+        parsed.categories[2].data.status.as_deref(),
+        Some("Go not installed")
+    );
+}
+
+#[test]
+fn test_json_report_uses_readable_timestamps() {
+    let json = render(&ScanReport::new(&sample_categories()), ReportFormat::Json);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        value["categories"][1]["scanned_at"],
+        "2023-11-14T22:13:20.000Z"
+    );
+    assert_eq!(value["categories"][1]["id"], "rust");
+    assert_eq!(value["categories"][1]["name"], "Rust/Cargo");
+}
+
+#[test]
+fn test_ndjson_has_one_item_per_line() {
+    let ndjson = render(&ScanReport::new(&sample_categories()), ReportFormat::Ndjson);
+    let items: Vec<ReportItem> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].item_id, "docker/1");
+    assert_eq!(items[0].category, "Docker");
+    assert!(items[0].item.safe_to_delete);
+    assert_eq!(items[1].item_id, "rust/1");
+    assert!(items[1].from_cache);
+    assert_eq!(items[1].report_version, REPORT_VERSION);
+}
+
+#[test]
+fn test_csv_has_header_and_escaped_rows() {
+    let csv = render(&ScanReport::new(&sample_categories()), ReportFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], CSV_COLUMNS.join(","));
+    assert!(lines[1].starts_with(&format!(
+        "{},docker/1,docker,Docker,DevelopmentTools,Dangling images,,2048,",
+        REPORT_VERSION
+    )));
+    assert!(lines[2].contains("\"target: my,app\""));
+    assert!(lines[2].contains("\"/home/dev/Projects/my,app/target\""));
+    assert!(lines[2].contains("\"Rebuild needed: \"\"cargo build\"\"\""));
+    assert!(lines[2].ends_with(",1,2023-11-14T22:13:20.000Z,true"));
+}
+
+#[test]
+fn test_empty_scan_report() {
+    let report = ScanReport::new(&[]);
+    assert_eq!(report.item_count, 0);
+    assert_eq!(render(&report, ReportFormat::Ndjson), "");
+    assert_eq!(
+        render(&report, ReportFormat::Csv),
+        format!("{}\n", CSV_COLUMNS.join(","))
+    );
+}
@@ -1,10 +1,11 @@
 //! Scan cache module tests
 //! Testing cache TTL, invalidation, and metadata change detection
 
-use devsweep::scan_cache::{CacheConfig, PathTracker, ScanCache};
-use devsweep::types::{CheckResult, CleanupItem};
+use devsweep::scan_cache::{CacheConfig, PathMetadata, PathTracker, ScanCache};
+use devsweep::types::{CheckResult, CleanupAction, CleanupItem};
 use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -59,6 +60,30 @@ fn test_get_valid_category() {
     }
 }
 
+#[test]
+fn test_cached_category_keeps_status_and_extra_data() {
+    let mut cache = ScanCache::new();
+
+    let mut result = CheckResult::new("Docker");
+    result.status = Some("Docker not running".to_string());
+    result.extra_data.dangling_images = Some(3);
+    result.extra_data.docker_summary = Some("3 images".to_string());
+    cache.update_category("Docker".to_string(), result, HashMap::new());
+
+    // Round-trip through JSON as the on-disk cache does
+    let json = serde_json::to_string(&cache).unwrap();
+    let cache: ScanCache = serde_json::from_str(&json).unwrap();
+
+    let cached = cache.get_valid_category("Docker").unwrap();
+    assert_eq!(cached.status.as_deref(), Some("Docker not running"));
+    assert_eq!(cached.extra_data.dangling_images, Some(3));
+    assert_eq!(
+        cached.extra_data.docker_summary.as_deref(),
+        Some("3 images")
+    );
+    assert!(cache.scanned_at("Docker").is_some());
+}
+
 #[test]
 fn test_get_valid_category_nonexistent() {
     let cache = ScanCache::new();
@@ -82,13 +107,133 @@ fn test_clear_cache() {
     assert!(cache.needs_rescan("cat2"));
 }
 
+#[test]
+fn test_invalidate_items_drops_only_affected_categories() {
+    let mut cache = ScanCache::new();
+
+    let mut npm = CheckResult::new("npm");
+    npm.add_item(
+        CleanupItem::new("npm cache", 10, "10 B").with_path(PathBuf::from("/home/dev/.npm")),
+    );
+    let mut projects = CheckResult::new("projects");
+    projects.add_item(
+        CleanupItem::new("app", 10, "10 B").with_path(PathBuf::from("/home/dev/Projects/app")),
+    );
+    let mut docker = CheckResult::new("docker");
+    docker.add_item(
+        CleanupItem::new("images", 10, "10 B")
+            .with_cleanup_action(CleanupAction::command("docker", ["image", "prune", "-f"])),
+    );
+
+    cache.update_category("npm".to_string(), npm, HashMap::new());
+    cache.update_category("projects".to_string(), projects, HashMap::new());
+    cache.update_category("docker".to_string(), docker, HashMap::new());
+
+    // A path inside a cached item, and a command-based item
+    let cleaned = vec![
+        CleanupItem::new("npm/_cacache", 5, "5 B")
+            .with_path(PathBuf::from("/home/dev/.npm/_cacache")),
+        CleanupItem::new("images", 10, "10 B")
+            .with_cleanup_action(CleanupAction::command("docker", ["image", "prune", "-f"])),
+    ];
+    assert_eq!(cache.invalidate_items(&cleaned), vec!["docker", "npm"]);
+
+    assert!(cache.needs_rescan("npm"));
+    assert!(cache.needs_rescan("docker"));
+    assert!(!cache.needs_rescan("projects"));
+}
+
+#[test]
+fn test_invalidate_paths_matches_tracked_paths() {
+    let mut cache = ScanCache::new();
+    let mut tracked = HashMap::new();
+    tracked.insert(
+        PathBuf::from("/home/dev/Library/Caches/pip"),
+        PathMetadata {
+            modified_time: None,
+            size: 0,
+            is_dir: true,
+            dir_fingerprint: None,
+        },
+    );
+    cache.update_category("pip".to_string(), CheckResult::new("pip"), tracked);
+    cache.update_category("go".to_string(), CheckResult::new("go"), HashMap::new());
+
+    // Restoring the whole cache directory affects the category tracking a path inside it
+    assert_eq!(
+        cache.invalidate_paths(&[PathBuf::from("/home/dev/Library/Caches")]),
+        vec!["pip"]
+    );
+    // An empty original path (item without a path) matches nothing
+    assert!(cache.invalidate_paths(&[PathBuf::new()]).is_empty());
+    assert!(!cache.needs_rescan("go"));
+}
+
+#[test]
+fn test_nested_directory_change_invalidates_cache() {
+    let temp = TempDir::new().unwrap();
+    let registry = temp.path().join("registry/cache/index");
+    fs::create_dir_all(&registry).unwrap();
+
+    let mut tracker = PathTracker::new();
+    tracker.track(temp.path().join("registry"));
+    let mut cache = ScanCache::new();
+    cache.update_category(
+        "Rust".to_string(),
+        CheckResult::new("Rust"),
+        tracker.into_paths(),
+    );
+    assert!(!cache.needs_rescan("Rust"));
+
+    // Two levels down: the tracked directory's own mtime does not change
+    thread::sleep(Duration::from_millis(10));
+    fs::create_dir(registry.join("serde-1.0.0")).unwrap();
+    assert!(cache.needs_rescan("Rust"));
+}
+
+#[test]
+fn test_new_entry_in_discovery_dir_invalidates_cache() {
+    let temp = TempDir::new().unwrap();
+    let projects = temp.path().join("Projects");
+    fs::create_dir_all(projects.join("old-app/node_modules")).unwrap();
+
+    let mut tracker = PathTracker::new();
+    tracker.track(projects.join("old-app/node_modules"));
+    tracker.track_dir(&projects);
+    tracker.track_dir(projects.join("old-app"));
+    // Listed while walking, but covered by the tracked item itself
+    tracker.track_dir(projects.join("old-app/node_modules"));
+    let paths = tracker.into_paths();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[&projects].dir_fingerprint.is_none());
+
+    let mut cache = ScanCache::new();
+    cache.update_category(
+        "node_modules".to_string(),
+        CheckResult::new("node_modules"),
+        paths,
+    );
+    assert!(!cache.needs_rescan("node_modules"));
+
+    thread::sleep(Duration::from_millis(10));
+    fs::create_dir(projects.join("new-app")).unwrap();
+    assert!(cache.needs_rescan("node_modules"));
+}
+
+#[test]
+fn test_path_metadata_without_fingerprint_still_loads() {
+    let json = r#"{"modified_time":null,"size":4096,"is_dir":true}"#;
+    let metadata: PathMetadata = serde_json::from_str(json).unwrap();
+    assert!(metadata.dir_fingerprint.is_none());
+}
+
 #[test]
 fn test_cache_config_default() {
     let config = CacheConfig::default_config();
 
     // Should have TTLs for common categories
     assert!(config.get_ttl("Docker").is_some());
-    assert!(config.get_ttl("Node.js/npm/yarn").is_some());
+    assert!(config.get_ttl("Node.js Package Managers").is_some());
 }
 
 #[test]
@@ -0,0 +1,111 @@
+//! Tests for the persistent directory-size index
+
+use devsweep::cancel::CancellationToken;
+use devsweep::size_index::{SizeIndex, MAX_INDEXED_LINKS};
+use devsweep::utils::get_dir_size;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// root/{a.bin, sub/{b.bin, deep/c.bin}, other/d.bin}
+fn fixture_tree() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::create_dir_all(root.join("sub/deep")).unwrap();
+    fs::create_dir_all(root.join("other")).unwrap();
+    fs::write(root.join("a.bin"), vec![0u8; 1000]).unwrap();
+    fs::write(root.join("sub/b.bin"), vec![0u8; 200]).unwrap();
+    fs::write(root.join("sub/deep/c.bin"), vec![0u8; 30]).unwrap();
+    fs::write(root.join("other/d.bin"), vec![0u8; 4]).unwrap();
+    temp
+}
+
+fn size(index: &SizeIndex, path: &Path) -> u64 {
+    index.dir_size(path, &CancellationToken::new())
+}
+
+#[test]
+fn test_indexed_size_matches_full_walk() {
+    let temp = fixture_tree();
+    let index = SizeIndex::new();
+
+    assert_eq!(size(&index, temp.path()), 1234);
+    assert_eq!(size(&index, temp.path()), get_dir_size(temp.path()));
+    assert_eq!(size(&index, &temp.path().join("a.bin")), 1000);
+    assert_eq!(size(&index, &temp.path().join("missing")), 0);
+    assert_eq!(index.len(), 4);
+}
+
+#[test]
+fn test_warm_scan_reads_only_changed_directories() {
+    let temp = fixture_tree();
+    let index = SizeIndex::new();
+    size(&index, temp.path());
+    assert_eq!(index.stats(), (4, 0));
+
+    // Nothing changed: every directory is reused
+    assert_eq!(size(&index, temp.path()), 1234);
+    assert_eq!(index.stats(), (4, 4));
+
+    // A new file deep in the tree only moves that directory's mtime
+    fs::write(temp.path().join("sub/deep/e.bin"), vec![0u8; 5]).unwrap();
+    assert_eq!(size(&index, temp.path()), 1239);
+    assert_eq!(index.stats(), (5, 7));
+}
+
+#[test]
+fn test_removed_subdirectory_is_not_counted() {
+    let temp = fixture_tree();
+    let index = SizeIndex::new();
+    size(&index, temp.path());
+
+    fs::remove_dir_all(temp.path().join("other")).unwrap();
+    assert_eq!(size(&index, temp.path()), 1230);
+}
+
+#[test]
+fn test_index_survives_save_and_load() {
+    let temp = fixture_tree();
+    let index_dir = TempDir::new().unwrap();
+    let index_file = index_dir.path().join("size_index.json");
+
+    let index = SizeIndex::new();
+    size(&index, temp.path());
+    index.save_to(&index_file).unwrap();
+
+    let loaded = SizeIndex::load_from(&index_file);
+    assert_eq!(loaded.len(), 4);
+    assert_eq!(size(&loaded, temp.path()), 1234);
+    assert_eq!(loaded.stats(), (0, 4));
+}
+
+#[test]
+fn test_directory_full_of_hardlinks_is_not_indexed() {
+    let temp = TempDir::new().unwrap();
+    let store = temp.path().join("store");
+    let project = temp.path().join("project");
+    fs::create_dir_all(&store).unwrap();
+    fs::create_dir_all(&project).unwrap();
+    for i in 0..=MAX_INDEXED_LINKS {
+        let name = format!("{}.bin", i);
+        fs::write(store.join(&name), [0u8; 10]).unwrap();
+        fs::hard_link(store.join(&name), project.join(&name)).unwrap();
+    }
+
+    let index = SizeIndex::new();
+    assert_eq!(size(&index, &project), 10 * (MAX_INDEXED_LINKS as u64 + 1));
+    assert!(index.is_empty());
+    assert_eq!(size(&index, &project), 10 * (MAX_INDEXED_LINKS as u64 + 1));
+    assert_eq!(index.stats(), (2, 0));
+}
+
+#[test]
+fn test_cancelled_sizing_stops() {
+    let temp = fixture_tree();
+    let index = SizeIndex::new();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    assert_eq!(index.dir_size(temp.path(), &cancel), 0);
+    assert!(index.is_empty());
+}
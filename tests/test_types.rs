@@ -1,6 +1,6 @@
 //! Tests for types module
 
-use devsweep::types::{CheckResult, CleanupItem, ItemDetail};
+use devsweep::types::{ActionType, CheckResult, CleanupAction, CleanupItem, ItemDetail};
 use std::path::PathBuf;
 
 #[test]
@@ -39,11 +39,56 @@ fn test_cleanup_item_with_warning() {
 }
 
 #[test]
-fn test_cleanup_item_with_cleanup_command() {
-    let item = CleanupItem::new("test", 100, "100 B").with_cleanup_command("rm -rf /tmp/test");
+fn test_cleanup_item_with_cleanup_action() {
+    let item = CleanupItem::new("test", 100, "100 B")
+        .with_cleanup_action(CleanupAction::command("go", ["clean", "-cache"]));
+
+    let action = item.cleanup_action.unwrap();
+    assert_eq!(
+        action.action_type,
+        ActionType::Command {
+            program: "go".to_string(),
+            args: vec!["clean".to_string(), "-cache".to_string()],
+        }
+    );
+    assert_eq!(action.success_codes, vec![0]);
+    assert_eq!(action.to_string(), "go clean -cache");
+}
 
-    assert!(item.cleanup_command.is_some());
-    assert_eq!(item.cleanup_command.unwrap(), "rm -rf /tmp/test");
+#[test]
+fn test_cleanup_action_keeps_arguments_intact() {
+    let action = CleanupAction::command("git", ["branch", "-d", "it's; rm -rf ~"])
+        .in_dir("/work/my repo")
+        .with_env("GIT_TERMINAL_PROMPT", "0");
+
+    let invocation = action.invocation();
+    assert_eq!(invocation.argv(), ["git", "branch", "-d", "it's; rm -rf ~"]);
+    assert_eq!(
+        invocation.current_dir.as_deref(),
+        Some(std::path::Path::new("/work/my repo"))
+    );
+    assert_eq!(
+        invocation
+            .env
+            .as_ref()
+            .and_then(|env| env.get("GIT_TERMINAL_PROMPT"))
+            .map(String::as_str),
+        Some("0")
+    );
+    assert_eq!(
+        action.to_string(),
+        r"cd '/work/my repo' && GIT_TERMINAL_PROMPT=0 git branch -d 'it'\''s; rm -rf ~'"
+    );
+}
+
+#[test]
+fn test_shell_cleanup_action_runs_through_sh() {
+    let action = CleanupAction::shell("brew cleanup && brew autoremove");
+    assert_eq!(
+        action.invocation().argv(),
+        ["sh", "-c", "brew cleanup && brew autoremove"]
+    );
+    assert_eq!(action.to_string(), "brew cleanup && brew autoremove");
 }
 
 #[test]
@@ -1,6 +1,9 @@
 //! Utils module tests
 
-use devsweep::utils::{format_size, sort_versions};
+use devsweep::cancel::CancellationToken;
+use devsweep::utils::{format_size, get_dir_size, get_dir_size_until, sort_versions};
+use std::fs;
+use tempfile::TempDir;
 
 #[test]
 fn test_format_size() {
@@ -26,3 +29,16 @@ fn test_sort_versions() {
     assert_eq!(versions[2], "1.10.0");
     assert_eq!(versions[3], "2.0.0");
 }
+
+#[test]
+fn test_get_dir_size_stops_when_cancelled() {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("a.bin"), vec![0u8; 2048]).unwrap();
+    fs::write(temp.path().join("b.bin"), vec![0u8; 1024]).unwrap();
+
+    assert_eq!(get_dir_size(temp.path()), 3072);
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    assert_eq!(get_dir_size_until(temp.path(), &cancel), 0);
+}
@@ -1,13 +1,13 @@
 //! Xcode checker comprehensive tests
 //! Testing DerivedData, Archives, and DeviceSupport detection with real file structures
 
-use devsweep::checkers;
+use devsweep::checkers::{self, ScanContext};
 use std::fs;
 use tempfile::TempDir;
 
 #[test]
 fn test_xcode_checker_basic_functionality() {
-    let result = checkers::check_xcode();
+    let result = checkers::check_xcode(&ScanContext::new());
 
     // Should return a result (items may be empty if Xcode not installed)
     assert!(!result.name.is_empty());
@@ -360,7 +360,7 @@ fn test_build_products_debug_release() {
 
 #[test]
 fn test_xcode_checker_returns_valid_structure() {
-    let result = checkers::check_xcode();
+    let result = checkers::check_xcode(&ScanContext::new());
 
     // Verify result structure
     assert!(!result.name.is_empty());